@@ -21,7 +21,7 @@
           targets = [ "x86_64-unknown-linux-gnu" "x86_64-pc-windows-gnu" ];
         };
 
-        # Libraries needed for egui/eframe and rdev
+        # Libraries needed for egui/eframe, rdev and the metronome click
         buildInputs = with pkgs; [
           libxkbcommon
           wayland
@@ -33,6 +33,7 @@
           xorg.libXxf86vm
           xorg.libXtst
           libevdev
+          alsa-lib
         ];
 
         nativeBuildInputs = with pkgs; [
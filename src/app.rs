@@ -0,0 +1,662 @@
+use crate::config::{Config, ConfigWatcher};
+use crate::diagonal::{MovementState, StopResult};
+use crate::drills::{Drill, DrillRun, DrillSummary};
+use crate::events::{Control, EventListener, GameEventKind, ListenerStatus};
+use crate::feedback::FeedSystem;
+use crate::history::History;
+use crate::input::{Backend, InputSource, ReplaySource};
+use crate::keymap::{Action, KeyMap};
+use crate::metronome::{Click, Cue};
+use crate::profiles::Weapon;
+use crate::reaction::{Outcome, ReactionMode, ReactionTimes};
+use crate::recording::{Recorder, Recording};
+use crate::shots::ShotTracker;
+use crate::simulation::MovementSimulation;
+use crate::state::Quality;
+use crate::stats::{Attempt, Stats};
+use crate::ui::Theme;
+use std::collections::HashMap;
+use std::mem::Discriminant;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// What to start the trainer with. Without paths nothing is read or written to disk.
+#[derive(Default)]
+pub struct TrainerOptions {
+    pub config_path: Option<PathBuf>,
+    pub bindings_path: Option<PathBuf>,
+    pub history_path: Option<PathBuf>,
+    pub weapon: Weapon,
+    /// Write the raw input and results to this file
+    pub record: Option<PathBuf>,
+    /// Play back a recording instead of listening to the keyboard
+    pub replay: Option<Recording>,
+    /// Input backend and, for evdev, a single device to read
+    pub backend: Backend,
+    pub device: Option<PathBuf>,
+    /// Use this input source instead of opening `backend`; it cannot be restarted
+    pub source: Option<Box<dyn InputSource>>,
+    /// Start this drill right away
+    pub drill: Option<Drill>,
+    /// Start in reaction mode
+    pub reaction: bool,
+}
+
+/// Trainer core shared by the GUI and the terminal UI: turns captured input into
+/// results, feed entries, stats and history. Frontends only render and forward settings.
+pub struct Trainer {
+    event_listener: EventListener,
+    movement: MovementState,
+    simulation: MovementSimulation,
+    shots: ShotTracker,
+    feed: FeedSystem,
+    weapon: Weapon,
+    /// Stats are kept separately for each weapon profile
+    stats: HashMap<Weapon, Stats>,
+    /// Stats of all recorded sessions, including the current one
+    all_time_stats: HashMap<Weapon, Stats>,
+    history: Option<History>,
+    config: Config,
+    /// Hot reload of the config file
+    config_watcher: Option<ConfigWatcher>,
+    bindings_path: Option<PathBuf>,
+    rebinding: Option<Action>,
+    /// Backend to reopen on retry; `None` for replays and custom sources
+    input: Option<(Backend, Option<PathBuf>)>,
+    /// Kind of listener problem the user chose to ignore
+    dismissed_problem: Option<Discriminant<ListenerStatus>>,
+    recorder: Option<Recorder>,
+    /// Input comes from a recording; nothing is written to the history
+    replaying: bool,
+    drill: Option<DrillRun>,
+    /// Audible beat of a rhythm drill
+    click: Option<Click>,
+    /// Result of the last drill until the user closes it
+    drill_summary: Option<DrillSummary>,
+    /// Random strafe and STOP prompts, when reaction mode is on
+    reaction: Option<ReactionMode>,
+    should_quit: bool,
+}
+
+impl Trainer {
+    /// Load the config, key bindings and history, then start listening for input.
+    /// An invalid config file is an error; it is watched for changes afterwards.
+    /// Input backend failures are reported by `listener_status`.
+    pub fn new(options: TrainerOptions) -> Result<Self, Box<dyn std::error::Error>> {
+        let TrainerOptions {
+            config_path,
+            bindings_path,
+            history_path,
+            mut weapon,
+            record,
+            replay,
+            backend,
+            device,
+            source,
+            drill,
+            reaction,
+        } = options;
+        let config = match &config_path {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+
+        let keymap = match &bindings_path {
+            Some(path) => KeyMap::load(path).unwrap_or_else(|e| {
+                log::warn!("Failed to load key bindings, using defaults: {}", e);
+                KeyMap::default()
+            }),
+            None => KeyMap::default(),
+        };
+
+        let history = history_path.and_then(|path| {
+            History::open(&path)
+                .map_err(|e| log::warn!("Failed to open history, attempts will not be saved: {}", e))
+                .ok()
+        });
+        let all_time_stats = history
+            .as_ref()
+            .map(History::stats_by_weapon)
+            .unwrap_or_default();
+        log::info!("Loaded {} attempts from the history", history.as_ref().map_or(0, |h| h.records().len()));
+
+        let mut movement = MovementState::new();
+        movement.set_overlap_window(config.overlap_window());
+        let custom_source = source.is_some();
+        let event_listener = match (&replay, source) {
+            (Some(recording), _) => {
+                weapon = recording.weapon;
+                movement.set_overlap_window(recording.overlap_window);
+                EventListener::with_source(Box::new(ReplaySource::new(recording)), keymap)
+            }
+            (None, Some(source)) => EventListener::with_source(source, keymap),
+            (None, None) => EventListener::open(backend, device.as_deref(), keymap),
+        };
+        let input = (replay.is_none() && !custom_source).then_some((backend, device));
+
+        let recorder = match &record {
+            Some(path) => Some(
+                Recorder::create(path, weapon, movement.overlap_window())
+                    .map_err(|e| format!("Failed to create recording {}: {}", path.display(), e))?,
+            ),
+            None => None,
+        };
+
+        let mut trainer = Self {
+            event_listener,
+            movement,
+            simulation: MovementSimulation::default(),
+            shots: ShotTracker::new(),
+            feed: FeedSystem::with_settings(config.feed()),
+            weapon: Weapon::default(),
+            stats: HashMap::new(),
+            all_time_stats,
+            history,
+            config,
+            config_watcher: config_path.map(ConfigWatcher::new),
+            bindings_path,
+            rebinding: None,
+            input,
+            dismissed_problem: None,
+            recorder,
+            replaying: replay.is_some(),
+            drill: None,
+            click: None,
+            drill_summary: None,
+            reaction: None,
+            should_quit: false,
+        };
+        trainer.event_listener.set_no_input_after(trainer.no_input_after());
+        trainer.select_weapon(weapon);
+        if let Some(drill) = drill {
+            trainer.start_drill(drill);
+        }
+        if reaction {
+            trainer.toggle_reaction();
+        }
+        Ok(trainer)
+    }
+
+    /// Handle all captured input since the last call, then check for timeouts
+    pub fn process_events(&mut self) {
+        let events = self.event_listener.drain_events();
+
+        for event in events {
+            // Use the capture timestamp, not the frame time, so events drained together stay distinct
+            let now = event.time;
+            self.record(|recorder| recorder.record_input(event.kind, now));
+            match event.kind {
+                GameEventKind::Press(Action::Fire) => {
+                    self.simulation.update(now);
+                    let (speed, accurate) = (self.simulation.speed(), self.simulation.is_accurate());
+                    if let Some(shot) = self.shots.on_fire(now, speed, accurate) {
+                        self.stats_mut().record_shot(&shot);
+                        self.feed.add_shot(&shot);
+                    }
+                }
+                GameEventKind::Press(Action::Quit) => {
+                    self.should_quit = true;
+                }
+                GameEventKind::Press(action) => {
+                    if let Some(key) = action.strafe_key() {
+                        if let Some(reaction) = &mut self.reaction {
+                            reaction.on_key_press(key, now);
+                        }
+                        self.simulation.on_key_press(key, now);
+                        if let Some(result) = self.movement.on_key_press(key, now) {
+                            self.handle_completion(result, now);
+                        }
+                    }
+                }
+                GameEventKind::Release(action) => {
+                    if let Some(key) = action.strafe_key() {
+                        if let Some(reaction) = &mut self.reaction {
+                            reaction.on_key_release(key, now);
+                        }
+                        self.simulation.on_key_release(key, now);
+                        if let Some(result) = self.movement.on_key_release(key, now) {
+                            self.handle_completion(result, now);
+                        }
+                    }
+                }
+                GameEventKind::KeyCaptured(key) => {
+                    if let Some(action) = self.rebinding.take() {
+                        let mut keymap = self.event_listener.keymap();
+                        keymap.bind(action, key);
+                        self.apply_keymap(keymap);
+                    }
+                }
+                GameEventKind::Control(Control::SelectWeapon(weapon)) => self.select_weapon(weapon),
+                GameEventKind::Control(Control::SetOverlapWindow(window)) => {
+                    self.set_overlap_window(window.as_secs_f32())
+                }
+            }
+        }
+
+        let now = Instant::now();
+        self.simulation.update(now);
+
+        // Check for timeout
+        if let Some(result) = self.movement.check_timeout(now) {
+            self.handle_completion(result, now);
+        }
+
+        if let Some(outcome) = self.reaction.as_mut().and_then(|reaction| reaction.update(now)) {
+            self.feed.add_reaction(outcome);
+        }
+
+        self.update_drill(now);
+
+        // Cleanup expired feed entries
+        self.feed.cleanup(now);
+
+        self.reload_config(now);
+    }
+
+    /// Apply the config file if it changed; a broken edit keeps the previous settings
+    fn reload_config(&mut self, now: Instant) {
+        let Some(watcher) = &mut self.config_watcher else {
+            return;
+        };
+        match watcher.poll(now) {
+            Some(Ok(config)) => {
+                log::info!("Reloaded config from {}", watcher.path().display());
+                self.apply_config(config);
+            }
+            Some(Err(e)) => {
+                log::error!("{}", e);
+                self.feed.add("Config error, see log - keeping previous settings".to_string(), Quality::Failed);
+            }
+            None => {}
+        }
+    }
+
+    /// Use new settings for timing, feed and theme
+    fn apply_config(&mut self, config: Config) {
+        // Only a changed window is applied, so reloads keep the windows of a replayed recording
+        let overlap_window = config.overlap_window();
+        let overlap_window_changed = overlap_window != self.config.overlap_window();
+        self.config = config;
+        self.movement.set_timing(self.config.timing(self.weapon));
+        self.feed.set_settings(self.config.feed());
+        self.event_listener.set_no_input_after(self.no_input_after());
+        if overlap_window_changed {
+            self.set_overlap_window(overlap_window);
+        }
+    }
+
+    /// Colors and font sizes from the config
+    pub fn theme(&self) -> Theme {
+        self.config.theme()
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Apply settings changed in the UI and optionally write them to the config file.
+    /// Invalid settings are rejected.
+    pub fn update_config(&mut self, config: Config, save: bool) {
+        if let Err(problems) = config.validate() {
+            log::warn!("Ignoring invalid settings: {}", problems.join("; "));
+            return;
+        }
+        self.apply_config(config);
+
+        if save
+            && let Some(watcher) = &mut self.config_watcher
+            && let Err(e) = watcher.save(&self.config)
+        {
+            log::error!("Failed to save config: {}", e);
+        }
+    }
+
+    /// Health of the input backend
+    pub fn listener_status(&self) -> ListenerStatus {
+        self.event_listener.status(Instant::now())
+    }
+
+    /// Listener failure or silence to show to the user, unless dismissed
+    pub fn listener_problem(&self) -> Option<ListenerStatus> {
+        let status = self.listener_status();
+        (status.is_problem() && self.dismissed_problem != Some(std::mem::discriminant(&status))).then_some(status)
+    }
+
+    /// Keep training despite the current listener problem, e.g. no input yet
+    pub fn dismiss_listener_problem(&mut self) {
+        self.dismissed_problem = Some(std::mem::discriminant(&self.listener_status()));
+    }
+
+    /// Whether `restart_input` can reopen the backend. A silent rdev hook is still
+    /// running and cannot be replaced, only one that has ended.
+    pub fn can_restart_input(&self) -> bool {
+        self.input.is_some() && self.event_listener.can_reopen()
+    }
+
+    /// Reopen the input backend, e.g. after fixing permissions. Keeps the key bindings.
+    pub fn restart_input(&mut self) {
+        if !self.can_restart_input() {
+            return;
+        }
+        let Some((backend, device)) = &self.input else {
+            return;
+        };
+        log::info!("Restarting input backend");
+        self.event_listener = EventListener::open(*backend, device.as_deref(), self.event_listener.keymap());
+        self.event_listener.set_no_input_after(self.no_input_after());
+        self.dismissed_problem = None;
+        self.rebinding = None;
+    }
+
+    /// Silence after which the input is reported; a replay may pause as long as the recording did
+    fn no_input_after(&self) -> Option<Duration> {
+        if self.replaying { None } else { self.config.no_input_after() }
+    }
+
+    /// Whether the input is played back from a recording
+    pub fn is_replaying(&self) -> bool {
+        self.replaying
+    }
+
+    /// Write to the recording, if any. A write error stops the recording.
+    fn record(&mut self, write: impl FnOnce(&mut Recorder) -> std::io::Result<()>) {
+        if let Some(recorder) = &mut self.recorder
+            && let Err(e) = write(recorder)
+        {
+            log::error!("Failed to write recording, stopped recording: {}", e);
+            self.recorder = None;
+        }
+    }
+
+    /// Built-in drills followed by the ones from the config file
+    pub fn drills(&self) -> Vec<Drill> {
+        self.config.drills()
+    }
+
+    /// Start `drill`, replacing any drill in progress. Its weapon, if any, is selected.
+    pub fn start_drill(&mut self, drill: Drill) {
+        if let Some(weapon) = drill.weapon {
+            self.select_weapon(weapon);
+        }
+        self.feed.add(format!("Drill {}: {}", drill.name, drill.describe()), Quality::Good);
+        let run = DrillRun::new(drill, Instant::now());
+        if run.metronome().is_some() && self.reaction.take().is_some() {
+            self.feed.add("Reaction mode off".to_string(), Quality::Good);
+        }
+        self.click = run.metronome().filter(|_| self.config.metronome_click()).map(|metronome| Click::start(*metronome));
+        self.drill = Some(run);
+        self.drill_summary = None;
+    }
+
+    /// Abandon the drill in progress without a summary
+    pub fn stop_drill(&mut self) {
+        self.drill = None;
+        self.click = None;
+    }
+
+    /// Drill in progress, if any
+    pub fn drill(&self) -> Option<&DrillRun> {
+        self.drill.as_ref()
+    }
+
+    /// Reaction prompt, or the metronome cue of a rhythm drill and how far into the beat `now` is
+    pub fn cue(&self, now: Instant) -> Option<(Cue, f32)> {
+        if let Some(reaction) = &self.reaction {
+            return Some((reaction.cue(), 0.0));
+        }
+        self.drill.as_ref()?.metronome().map(|metronome| metronome.cue(now))
+    }
+
+    /// Whether reaction mode is on
+    pub fn is_reaction_mode(&self) -> bool {
+        self.reaction.is_some()
+    }
+
+    /// Switch reaction mode on or off. It replaces the cues of a rhythm drill, so such a drill is stopped.
+    pub fn toggle_reaction(&mut self) {
+        if self.reaction.take().is_some() {
+            self.feed.add("Reaction mode off".to_string(), Quality::Good);
+            return;
+        }
+        if self.drill.as_ref().is_some_and(|drill| drill.metronome().is_some()) {
+            self.stop_drill();
+        }
+        self.reaction = Some(ReactionMode::new(Instant::now()));
+        self.feed.add("Reaction mode: strafe when told, stop on STOP".to_string(), Quality::Good);
+    }
+
+    /// Result of the last finished drill, until closed
+    pub fn drill_summary(&self) -> Option<&DrillSummary> {
+        self.drill_summary.as_ref()
+    }
+
+    pub fn close_drill_summary(&mut self) {
+        self.drill_summary = None;
+    }
+
+    /// Run the last finished drill again
+    pub fn repeat_drill(&mut self) {
+        if let Some(summary) = self.drill_summary.take() {
+            self.start_drill(summary.drill);
+        }
+    }
+
+    /// End the drill once one of its limits is reached
+    fn update_drill(&mut self, now: Instant) {
+        let Some(summary) = self.drill.as_mut().and_then(|drill| drill.update(&self.movement, now)) else {
+            return;
+        };
+        log::info!("Drill {} finished: {}", summary.drill.name, summary.headline());
+        let quality = if summary.passed { Quality::Perfect } else { Quality::Failed };
+        self.feed.add(format!("Drill {}: {}", summary.drill.name, summary.headline()), quality);
+        self.drill = None;
+        self.click = None;
+        self.drill_summary = Some(summary);
+    }
+
+    /// Whether the quit key was pressed
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    pub fn movement(&self) -> &MovementState {
+        &self.movement
+    }
+
+    pub fn simulation(&self) -> &MovementSimulation {
+        &self.simulation
+    }
+
+    pub fn feed(&self) -> &FeedSystem {
+        &self.feed
+    }
+
+    pub fn weapon(&self) -> Weapon {
+        self.weapon
+    }
+
+    /// Session stats of the active weapon
+    pub fn stats(&self) -> &Stats {
+        &self.stats[&self.weapon]
+    }
+
+    /// All-time stats of the active weapon
+    pub fn all_time_stats(&self) -> &Stats {
+        &self.all_time_stats[&self.weapon]
+    }
+
+    fn stats_mut(&mut self) -> &mut Stats {
+        self.stats.entry(self.weapon).or_default()
+    }
+
+    fn all_time_stats_mut(&mut self) -> &mut Stats {
+        self.all_time_stats.entry(self.weapon).or_default()
+    }
+
+    /// Switch weapon profile: timing windows and movement parameters follow the weapon
+    pub fn select_weapon(&mut self, weapon: Weapon) {
+        self.weapon = weapon;
+        self.stats.entry(weapon).or_default();
+        self.all_time_stats.entry(weapon).or_default();
+        self.movement.set_timing(self.config.timing(weapon));
+        self.simulation.set_params(weapon.movement());
+        self.record(|recorder| recorder.record_weapon(weapon));
+    }
+
+    /// Step the tolerated key overlap through off, the perfect and the max overlap, and save it to the config
+    pub fn cycle_overlap_window(&mut self) {
+        let timing = self.movement.timing();
+        let current = self.movement.overlap_window();
+        let next = [timing.perfect_overlap, timing.max_overlap]
+            .into_iter()
+            .find(|window| *window > current + 1e-6)
+            .unwrap_or(0.0);
+        let mut config = self.config.clone();
+        config.set_overlap_window(next);
+        self.update_config(config, true);
+    }
+
+    /// Tolerated key overlap in seconds, 0.0 = strict
+    fn set_overlap_window(&mut self, overlap_window: f32) {
+        self.movement.set_overlap_window(overlap_window);
+        self.record(|recorder| recorder.record_overlap_window(overlap_window));
+    }
+
+    /// Current key bindings
+    pub fn keymap(&self) -> KeyMap {
+        self.event_listener.keymap()
+    }
+
+    /// Use new key bindings and persist them
+    pub fn apply_keymap(&mut self, keymap: KeyMap) {
+        if let Some(path) = &self.bindings_path
+            && let Err(e) = keymap.save(path)
+        {
+            log::error!("Failed to save key bindings: {}", e);
+        }
+        self.event_listener.set_keymap(keymap);
+    }
+
+    /// Action waiting for its new key, if any
+    pub fn rebinding(&self) -> Option<Action> {
+        self.rebinding
+    }
+
+    /// Bind the next key pressed to `action`
+    pub fn start_rebind(&mut self, action: Action) {
+        self.rebinding = Some(action);
+        self.event_listener.capture_next_key();
+    }
+
+    pub fn cancel_rebind(&mut self) {
+        self.rebinding = None;
+        self.event_listener.cancel_capture();
+    }
+
+    /// Whether the trainer's window is focused or hovered; clicks then operate the UI and do not fire
+    pub fn set_ui_has_pointer(&mut self, has_pointer: bool) {
+        self.event_listener.set_ui_has_pointer(has_pointer);
+    }
+
+    /// Persist a finished stop
+    fn record_history(&mut self, write: impl FnOnce(&mut History, Weapon) -> Result<(), Box<dyn std::error::Error>>) {
+        if let Some(history) = &mut self.history
+            && let Err(e) = write(history, self.weapon)
+        {
+            log::error!("Failed to save attempt to history: {}", e);
+        }
+    }
+
+    /// Record a finished stop; `time` is when it ended, the reference for shot timing
+    fn handle_completion(&mut self, result: StopResult, time: Instant) {
+        self.shots.on_stop(time);
+        match result {
+            StopResult::Single(mut result) => {
+                result.stop = self.simulation.take_stop_analysis(result.axis());
+                self.record(|recorder| recorder.record_result(&result, time));
+
+                // Record stats
+                self.stats_mut().record(Attempt::from(&result));
+                self.all_time_stats_mut().record(Attempt::from(&result));
+                // The stop lands when the counter key goes down; attempts without a counter hold have no beat
+                let mut beat_offset = None;
+                if let Some(drill) = &mut self.drill {
+                    drill.record(Attempt::from(&result));
+                    if let Some(counter_time) = result.counter_time {
+                        beat_offset = drill.record_stop(counter_time);
+                    }
+                }
+                let (reaction, latency) = self.score_reaction(result.counter_time, time);
+                self.record_history(|history, weapon| history.record(&result, weapon, latency));
+
+                // Add to feed
+                self.feed.add_result(&result, self.movement.timing());
+                if let Some(offset) = beat_offset {
+                    self.feed.add_beat(offset);
+                }
+                if let Some(outcome) = reaction {
+                    self.feed.add_reaction(outcome);
+                }
+            }
+            StopResult::Diagonal(mut diagonal) => {
+                for result in [&mut diagonal.horizontal, &mut diagonal.vertical].into_iter().flatten() {
+                    result.stop = self.simulation.take_stop_analysis(result.axis());
+                }
+                for result in [&diagonal.horizontal, &diagonal.vertical].into_iter().flatten() {
+                    self.record(|recorder| recorder.record_result(result, time));
+                }
+
+                let axes: Vec<Attempt> = [&diagonal.horizontal, &diagonal.vertical]
+                    .into_iter()
+                    .flatten()
+                    .map(Attempt::from)
+                    .collect();
+                // The first counter press on either axis lands the stop and answers the STOP cue
+                let counter_time = [&diagonal.horizontal, &diagonal.vertical]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|result| result.counter_time)
+                    .min();
+                let mut beat_offset = None;
+                if let Some(drill) = &mut self.drill {
+                    drill.record_diagonal(diagonal.quality, axes.iter().copied());
+                    if let Some(counter_time) = counter_time {
+                        beat_offset = drill.record_stop(counter_time);
+                    }
+                }
+                self.stats_mut().record_diagonal(diagonal.quality, axes.iter().copied());
+                self.all_time_stats_mut().record_diagonal(diagonal.quality, axes);
+
+                let (reaction, latency) = self.score_reaction(counter_time, time);
+                self.record_history(|history, weapon| history.record_diagonal(&diagonal, weapon, latency));
+                self.feed.add_diagonal(&diagonal);
+                if let Some(offset) = beat_offset {
+                    self.feed.add_beat(offset);
+                }
+                if let Some(outcome) = reaction {
+                    self.feed.add_reaction(outcome);
+                }
+            }
+        }
+    }
+
+    /// Score a stop in reaction mode and count its latencies. Stops without a counter press are not scored.
+    fn score_reaction(&mut self, counter_time: Option<Instant>, time: Instant) -> (Option<Outcome>, ReactionTimes) {
+        let Some((outcome, strafe)) = counter_time.and_then(|counter_time| {
+            self.reaction.as_mut().and_then(|reaction| reaction.on_stop(counter_time, time))
+        }) else {
+            return (None, ReactionTimes::default());
+        };
+        self.stats_mut().record_strafe_reaction(strafe);
+        self.all_time_stats_mut().record_strafe_reaction(strafe);
+        let stop = match outcome {
+            Outcome::Reaction(latency) => {
+                self.stats_mut().record_reaction(latency);
+                self.all_time_stats_mut().record_reaction(latency);
+                Some(latency)
+            }
+            _ => None,
+        };
+        (Some(outcome), ReactionTimes { strafe: Some(strafe), stop })
+    }
+}
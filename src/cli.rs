@@ -0,0 +1,635 @@
+use crate::config::Config;
+use crate::diagonal::{combine_quality, StopResult};
+use crate::diagnostics::{Report, Severity};
+use crate::drills::{self, Drill};
+use crate::history::{self, History, HistoryRecord};
+use crate::input::Backend;
+use crate::keymap::{Action, KeyMap};
+use crate::profiles::Weapon;
+use crate::recording::{RecordedResult, Recording};
+use crate::state::{evaluate_hold_time, ErrorKind, Quality, StrafeKey, TimingWindows};
+use crate::stats::{Attempt, Stats, TimingSummary};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// High-performance Counter-Strike 2 counter-strafe training tool
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Config file [default: <config dir>/cs2st/config.toml]
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Weapon profile to start with (or to filter/re-score with)
+    #[arg(long, global = true, value_enum)]
+    pub profile: Option<Weapon>,
+
+    /// Key bindings file [default: <config dir>/cs2st/keybindings.toml]
+    #[arg(long, global = true, value_name = "FILE")]
+    pub bindings: Option<PathBuf>,
+
+    /// Input backend [default: from the config, else auto]
+    #[arg(long, global = true, value_enum)]
+    pub input: Option<Backend>,
+
+    /// Read only this evdev device, e.g. /dev/input/event3 (see `devices`)
+    #[arg(long, global = true, value_name = "DEVICE")]
+    pub device: Option<PathBuf>,
+
+    /// Log messages at this level and above to stderr
+    #[arg(long, global = true, value_enum, default_value_t = LogLevel::Warn)]
+    pub log_level: LogLevel,
+
+    /// Same as the `tui` subcommand, kept for `cs2st --tui`
+    #[arg(long, hide = true)]
+    pub tui: bool,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Train with the GUI (default)
+    Train {
+        /// Record the raw input and results to this file
+        #[arg(long, value_name = "FILE")]
+        record: Option<PathBuf>,
+        /// Start with this drill (see `drills`)
+        #[arg(long, value_name = "NAME")]
+        drill: Option<String>,
+        /// Start in reaction mode: random strafe and STOP prompts
+        #[arg(long)]
+        reaction: bool,
+    },
+    /// Train in the terminal
+    Tui {
+        /// Record the raw input and results to this file
+        #[arg(long, value_name = "FILE")]
+        record: Option<PathBuf>,
+        /// Start with this drill (see `drills`)
+        #[arg(long, value_name = "NAME")]
+        drill: Option<String>,
+        /// Start in reaction mode: random strafe and STOP prompts
+        #[arg(long)]
+        reaction: bool,
+    },
+    /// Print summaries of the stored history
+    Stats,
+    /// Export the stored history, only attempts with --profile if given
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Output file [default: stdout]
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Re-score a history file or recording with the current timing windows
+    Replay {
+        file: PathBuf,
+        /// Play a recording back at its original pace in the trainer
+        #[arg(long)]
+        realtime: bool,
+        /// Play back in the terminal instead of the GUI
+        #[arg(long, requires = "realtime")]
+        tui: bool,
+    },
+    /// Show the effective configuration
+    Config,
+    /// List input devices and whether they can be read (Linux)
+    Devices,
+    /// Check why the keyboard can or cannot be read: devices, groups, udev, session
+    Doctor,
+    /// List the built-in and configured drills
+    Drills,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn filter(&self) -> log::LevelFilter {
+        match self {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    /// One JSON object per line, same as the history file
+    Jsonl,
+}
+
+impl Cli {
+    /// The subcommand to run: `train` if none was given, `tui` with `--tui`
+    pub fn command(&self) -> Command {
+        match self.command.clone().unwrap_or(Command::Train { record: None, drill: None, reaction: false }) {
+            Command::Train { record, drill, reaction } if self.tui => Command::Tui { record, drill, reaction },
+            command => command,
+        }
+    }
+
+    /// Config file from `--config`, or the default location
+    pub fn config_path(&self) -> Option<PathBuf> {
+        self.config.clone().or_else(Config::default_path)
+    }
+
+    /// Input backend and device from the flags, falling back to the config
+    pub fn input(&self, config: &Config) -> (Backend, Option<PathBuf>) {
+        let device = self.device.clone().or_else(|| config.input.device.clone());
+        (self.input.or(config.input.backend).unwrap_or_default(), device)
+    }
+
+    /// Key bindings file from `--bindings`, or the default location
+    pub fn bindings_path(&self) -> Option<PathBuf> {
+        self.bindings.clone().or_else(KeyMap::default_path)
+    }
+}
+
+/// Stored attempts, only those with `profile` if given
+fn history_records(profile: Option<Weapon>) -> Result<Vec<HistoryRecord>, Box<dyn std::error::Error>> {
+    let path = History::default_path().ok_or("No data directory found for the history file")?;
+    let mut records = history::load_records(&path)?;
+    records.retain(|record| profile.is_none_or(|weapon| record.weapon == weapon));
+    Ok(records)
+}
+
+/// `stats`: summary per weapon, optionally only for `profile`
+pub fn print_stats(profile: Option<Weapon>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let records = history_records(profile)?;
+    let by_weapon = history::stats_by_weapon(&records);
+
+    if by_weapon.is_empty() {
+        println!("No attempts recorded yet.");
+        return Ok(());
+    }
+
+    // A diagonal stop is stored as one record per axis, count attempts like the stats do
+    let attempts: u32 = by_weapon.values().map(|stats| stats.total_attempts).sum();
+    let sessions = records.iter().map(|record| record.session).collect::<std::collections::HashSet<_>>();
+    println!("{} attempts in {} sessions\n", attempts, sessions.len());
+
+    for weapon in Weapon::ALL {
+        if let Some(stats) = by_weapon.get(&weapon) {
+            print_weapon_stats(weapon, stats, &config.timing(weapon));
+        }
+    }
+    Ok(())
+}
+
+fn print_weapon_stats(weapon: Weapon, stats: &Stats, timing: &TimingWindows) {
+    println!(
+        "{} · target {:.0}ms: {} attempts · {:.0}% ★ · {:.0}% ● · {:.0}% ✕",
+        weapon.name(),
+        timing.optimal * 1000.0,
+        stats.total_attempts,
+        stats.perfect_percentage(),
+        stats.good_percentage(),
+        stats.failed_percentage()
+    );
+
+    for (label, summary) in [
+        ("Hold", stats.hold_time_summary()),
+        ("Gap", stats.gap_summary()),
+        ("Overlap", stats.overlap_summary()),
+        ("Reaction", stats.reaction_summary()),
+        ("Strafe", stats.strafe_reaction_summary()),
+    ] {
+        if let Some(summary) = summary {
+            println!("  {:<8}{}", label, format_summary(&summary));
+        }
+    }
+
+    for key in [StrafeKey::A, StrafeKey::D, StrafeKey::W, StrafeKey::S] {
+        if let Some(direction) = stats.direction(key) {
+            println!(
+                "  {:<8}{} attempts · {:.0}% ★ · mean {:.0}ms",
+                key.direction_label(),
+                direction.attempts,
+                direction.perfect_percentage(),
+                direction.mean_hold_time().unwrap_or(0.0) * 1000.0
+            );
+        }
+    }
+    println!();
+}
+
+fn format_summary(summary: &TimingSummary) -> String {
+    format!(
+        "mean {:.0}ms · median {:.0}ms · σ {:.0}ms · P10 {:.0}ms · P90 {:.0}ms",
+        summary.mean * 1000.0,
+        summary.median * 1000.0,
+        summary.std_dev * 1000.0,
+        summary.p10 * 1000.0,
+        summary.p90 * 1000.0
+    )
+}
+
+/// `export`: write the history as CSV or JSON lines, optionally only for `profile`
+pub fn export(
+    format: ExportFormat,
+    output: Option<&Path>,
+    profile: Option<Weapon>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let records = history_records(profile)?;
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    match format {
+        ExportFormat::Csv => {
+            writeln!(out, "{}", CSV_HEADER)?;
+            for record in &records {
+                writeln!(out, "{}", csv_row(record))?;
+            }
+        }
+        ExportFormat::Jsonl => {
+            for record in &records {
+                writeln!(out, "{}", serde_json::to_string(record)?)?;
+            }
+        }
+    }
+    out.flush()?;
+
+    if let Some(path) = output {
+        eprintln!("Exported {} attempts to {}", records.len(), path.display());
+    }
+    Ok(())
+}
+
+const CSV_HEADER: &str = "timestamp,session,weapon,original_key,counter_key,hold_time_ms,quality,error,diagonal,diagonal_quality,time_to_accurate_ms,gap_ms,overlap_ms,reaction_ms,strafe_reaction_ms";
+
+fn csv_row(record: &HistoryRecord) -> String {
+    let optional = |value: Option<f32>| value.map(|v| format!("{:.1}", v)).unwrap_or_default();
+    format!(
+        "{},{},{},{},{},{:.1},{:?},{},{},{},{},{},{},{},{}",
+        record.timestamp.to_rfc3339(),
+        record.session.to_rfc3339(),
+        record.weapon.name(),
+        record.original_key.as_char(),
+        record.counter_key.as_char(),
+        record.hold_time_ms,
+        record.quality,
+        record.error.map(|error| format!("{:?}", error)).unwrap_or_default(),
+        record.diagonal,
+        record.diagonal_quality.map(|quality| format!("{:?}", quality)).unwrap_or_default(),
+        optional(record.time_to_accurate_ms),
+        optional(record.gap_ms),
+        optional(record.overlap_ms),
+        optional(record.reaction_ms),
+        optional(record.strafe_reaction_ms)
+    )
+}
+
+/// `replay`: re-score every attempt in a history file with the timing windows of
+/// `profile` (or the weapon it was recorded with) and report changed verdicts
+pub fn replay(file: &Path, profile: Option<Weapon>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let records = history::load_records(file)?;
+    if records.is_empty() {
+        return Err(format!("No attempts found in {}", file.display()).into());
+    }
+
+    let mut stats = Stats::default();
+    let mut changed = 0;
+    // A diagonal stop is counted once, with the verdict combined from its axes
+    for stop in history::stops(&records) {
+        let first = &stop[0];
+        let weapon = profile.unwrap_or(first.weapon);
+        let timing = config.timing(weapon);
+        let attempts: Vec<Attempt> = stop
+            .iter()
+            .map(|record| Attempt {
+                quality: rescore(record, &timing),
+                ..record.attempt()
+            })
+            .collect();
+
+        for (record, attempt) in stop.iter().zip(&attempts) {
+            println!(
+                "{} {:<7} {} {:>4.0}ms {}{}",
+                record.timestamp.format("%Y-%m-%d %H:%M:%S%.3f"),
+                weapon.name(),
+                record.original_key.direction_label(),
+                record.hold_time_ms,
+                attempt.quality.symbol(),
+                was(attempt.quality, record.quality)
+            );
+        }
+
+        let (quality, recorded) = match first.diagonal_quality {
+            Some(recorded) => {
+                let quality = combine_quality(attempts.first().map(|a| a.quality), attempts.get(1).map(|a| a.quality));
+                println!(
+                    "{} {:<7} ↔↕ diagonal {}{}",
+                    first.timestamp.format("%Y-%m-%d %H:%M:%S%.3f"),
+                    weapon.name(),
+                    quality.symbol(),
+                    was(quality, recorded)
+                );
+                stats.record_diagonal(quality, attempts);
+                (quality, recorded)
+            }
+            None => {
+                stats.record(attempts[0]);
+                (attempts[0].quality, first.quality)
+            }
+        };
+        if quality != recorded {
+            changed += 1;
+        }
+    }
+
+    println!(
+        "\n{} attempts · {:.0}% ★ · {:.0}% ● · {:.0}% ✕ · {} verdicts changed",
+        stats.total_attempts,
+        stats.perfect_percentage(),
+        stats.good_percentage(),
+        stats.failed_percentage(),
+        changed
+    );
+    Ok(())
+}
+
+/// `replay` of a recording: run the raw input through the state machine with the timing
+/// windows of `profile` (or the weapons it was recorded with) and compare with the recorded results
+pub fn replay_recording(file: &Path, profile: Option<Weapon>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let recording = Recording::load(file)?;
+    let rescored = recording.rescore(|weapon| config.timing(profile.unwrap_or(weapon)));
+    if rescored.is_empty() {
+        return Err(format!("No attempts found in {}", file.display()).into());
+    }
+
+    let mut recorded: Vec<&RecordedResult> = recording.results().map(|(_, result)| result).collect();
+    let mut stats = Stats::default();
+    let mut changed = 0;
+    for (offset, stop) in &rescored {
+        // Results are recorded per axis; None if the axis has no recorded counterpart
+        let mut recorded_axes = Vec::new();
+        for result in stop.axes() {
+            let recorded = take_matching(&mut recorded, result.original_key, result.counter_key, result.hold_time)
+                .map(|recorded| recorded.quality);
+            recorded_axes.push(recorded);
+            println!(
+                "{:>8.3}s {} {:>4.0}ms {}{}",
+                offset.as_secs_f32(),
+                result.original_key.direction_label(),
+                result.hold_time * 1000.0,
+                result.quality.symbol(),
+                recorded.map(|recorded| was(result.quality, recorded)).unwrap_or_default()
+            );
+        }
+
+        let recorded_stop = match stop {
+            StopResult::Single(result) => {
+                stats.record(result.into());
+                recorded_axes[0]
+            }
+            StopResult::Diagonal(diagonal) => {
+                stats.record_diagonal(diagonal.quality, stop.axes().map(Attempt::from));
+                // An axis that was not countered fails the stop, whichever axis it is
+                let recorded = recorded_axes.iter().all(Option::is_some).then(|| {
+                    combine_quality(recorded_axes.first().copied().flatten(), recorded_axes.get(1).copied().flatten())
+                });
+                println!(
+                    "{:>8.3}s ↔↕ diagonal {}{}",
+                    offset.as_secs_f32(),
+                    diagonal.quality.symbol(),
+                    recorded.map(|recorded| was(diagonal.quality, recorded)).unwrap_or_default()
+                );
+                recorded
+            }
+        };
+        if recorded_stop.is_some_and(|recorded| recorded != stop.quality()) {
+            changed += 1;
+        }
+    }
+
+    println!(
+        "\n{} attempts · {:.0}% ★ · {:.0}% ● · {:.0}% ✕ · {} verdicts changed",
+        stats.total_attempts,
+        stats.perfect_percentage(),
+        stats.good_percentage(),
+        stats.failed_percentage(),
+        changed
+    );
+    Ok(())
+}
+
+/// Remove and return the first recorded result for the same keys and hold time.
+/// Offsets are not compared: a timeout is recorded at frame time, re-scored at input time.
+fn take_matching<'a>(
+    recorded: &mut Vec<&'a RecordedResult>,
+    original_key: StrafeKey,
+    counter_key: StrafeKey,
+    hold_time: f32,
+) -> Option<&'a RecordedResult> {
+    let index = recorded.iter().position(|result| {
+        result.original_key == original_key
+            && result.counter_key == counter_key
+            && (result.hold_time - hold_time).abs() < 0.001
+    })?;
+    Some(recorded.remove(index))
+}
+
+/// " (was ★)" when a verdict changed
+fn was(quality: Quality, recorded: Quality) -> String {
+    if quality != recorded { format!(" (was {})", recorded.symbol()) } else { String::new() }
+}
+
+/// Quality of a stored attempt under other timing windows. Both-keys failures
+/// have no meaningful hold time and stay failed.
+fn rescore(record: &HistoryRecord, timing: &TimingWindows) -> Quality {
+    if record.error == Some(ErrorKind::BothKeys) {
+        return Quality::Failed;
+    }
+    evaluate_hold_time(record.hold_time_ms / 1000.0, timing)
+}
+
+/// `devices`: every event device with its kind, or why it cannot be read
+#[cfg(target_os = "linux")]
+pub fn print_devices() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::evdev_source::{scan_devices, DeviceKind};
+
+    let devices = scan_devices();
+    if devices.is_empty() {
+        return Err("No input devices found in /dev/input".into());
+    }
+    for device in &devices {
+        let used = matches!(device.kind(), Some(DeviceKind::Keyboard | DeviceKind::Mouse));
+        let marker = if used { "*" } else { " " };
+        println!("{} {}", marker, device.describe());
+    }
+    println!("\n* read by the evdev backend; pick a single device with --device or input.device in the config");
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn print_devices() -> Result<(), Box<dyn std::error::Error>> {
+    Err("Listing input devices is only supported on Linux".into())
+}
+
+/// `doctor`: the diagnostics report; fails if keyboard access is broken
+pub fn print_doctor() -> Result<(), Box<dyn std::error::Error>> {
+    let report = Report::collect();
+    println!("{}", report.to_text());
+    if report.worst() == Severity::Error {
+        return Err("Keyboard access is not working, see the fixes above".into());
+    }
+    Ok(())
+}
+
+/// `drills`: name, limit and goal of every drill
+pub fn print_drills(config: &Config) {
+    let drills = config.drills();
+    let width = drills.iter().map(|drill| drill.name.len()).max().unwrap_or(0);
+    for drill in &drills {
+        println!("{:<width$}  {}", drill.name, drill.describe());
+    }
+    println!("\nStart one with --drill NAME, or press F5 in the trainer");
+}
+
+/// Drill called `name`, built-in or from the config
+pub fn find_drill(config: &Config, name: &str) -> Result<Drill, Box<dyn std::error::Error>> {
+    let drills = config.drills();
+    drills::find(&drills, name).cloned().ok_or_else(|| {
+        let names: Vec<&str> = drills.iter().map(|drill| drill.name.as_str()).collect();
+        format!("Unknown drill \"{}\", available: {}", name, names.join(", ")).into()
+    })
+}
+
+/// `config`: file locations, profile and key bindings in effect
+pub fn print_config(cli: &Cli, config: &Config, keymap: &KeyMap) {
+    let display = |path: Option<PathBuf>| {
+        path.map(|path| path.display().to_string())
+            .unwrap_or_else(|| "(no config directory)".to_string())
+    };
+    let weapon = cli.profile.unwrap_or_default();
+    let timing = config.timing(weapon);
+    let feed = config.feed();
+
+    println!("Config file:  {}", display(cli.config_path()));
+    println!("Key bindings: {}", display(cli.bindings_path()));
+    let (backend, device) = cli.input(config);
+    println!(
+        "Input:        {}{}",
+        backend.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default(),
+        device.map(|device| format!(" ({})", device.display())).unwrap_or_default()
+    );
+    println!("History:      {}", display(History::default_path()));
+    println!();
+    println!(
+        "Profile: {} · target {:.0}ms ±{:.0}ms ({:.0}-{:.0}ms) · timeout {:.0}ms",
+        weapon.name(),
+        timing.optimal * 1000.0,
+        timing.perfect_tolerance * 1000.0,
+        timing.min * 1000.0,
+        timing.max * 1000.0,
+        timing.timeout * 1000.0
+    );
+    println!(
+        "Transition: gap ≤{:.0}ms ★ ≤{:.0}ms ● · overlap ≤{:.0}ms ★ ≤{:.0}ms ● · tolerated overlap {:.0}ms",
+        timing.perfect_gap * 1000.0,
+        timing.max_gap * 1000.0,
+        timing.perfect_overlap * 1000.0,
+        timing.max_overlap * 1000.0,
+        config.overlap_window() * 1000.0
+    );
+    println!(
+        "Feed: {} entries · visible {:.1}s · fade {:.1}s",
+        feed.max_entries, feed.visible_duration, feed.fade_duration
+    );
+    println!();
+    for action in Action::ALL {
+        println!("{:<14}{}", action.label(), keymap.label_for(action));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn record(hold_time_ms: f32, quality: Quality, error: Option<ErrorKind>) -> HistoryRecord {
+        HistoryRecord {
+            timestamp: Local::now(),
+            session: Local::now(),
+            weapon: Weapon::Rifle,
+            original_key: StrafeKey::A,
+            counter_key: StrafeKey::D,
+            hold_time_ms,
+            quality,
+            error,
+            diagonal: false,
+            diagonal_quality: None,
+            time_to_accurate_ms: None,
+            gap_ms: Some(12.0),
+            overlap_ms: None,
+            reaction_ms: None,
+            strafe_reaction_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_subcommands_and_global_flags() {
+        let cli = Cli::try_parse_from(["cs2st", "replay", "session.jsonl", "--profile", "awp"]).unwrap();
+        assert!(
+            matches!(cli.command, Some(Command::Replay { ref file, realtime: false, .. }) if file == Path::new("session.jsonl"))
+        );
+        assert_eq!(cli.profile, Some(Weapon::Awp));
+        assert_eq!(cli.log_level, LogLevel::Warn);
+
+        let cli = Cli::try_parse_from(["cs2st", "--log-level", "debug"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.log_level, LogLevel::Debug);
+
+        assert!(Cli::try_parse_from(["cs2st", "--profile", "shotgun"]).is_err());
+
+        let cli = Cli::try_parse_from(["cs2st", "tui", "--record", "session.cs2r", "--drill", "Sprint"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Tui { record: Some(_), drill: Some(ref drill), reaction: false }) if drill == "Sprint"));
+        assert!(Cli::try_parse_from(["cs2st", "replay", "session.cs2r", "--tui"]).is_err());
+
+        let cli = Cli::try_parse_from(["cs2st", "--tui"]).unwrap();
+        assert!(matches!(cli.command(), Command::Tui { record: None, drill: None, reaction: false }));
+        let cli = Cli::try_parse_from(["cs2st", "--tui", "train", "--reaction"]).unwrap();
+        assert!(matches!(cli.command(), Command::Tui { reaction: true, .. }));
+        let cli = Cli::try_parse_from(["cs2st", "replay", "session.cs2r", "--realtime", "--tui"]).unwrap();
+        assert!(matches!(cli.command(), Command::Replay { realtime: true, tui: true, .. }));
+
+        let cli = Cli::try_parse_from(["cs2st", "train", "--reaction"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Train { drill: None, reaction: true, .. })));
+    }
+
+    #[test]
+    fn test_rescore_uses_weapon_timing() {
+        // 130ms is too slow for a rifle but fine for the AWP
+        let slow = record(130.0, Quality::Failed, Some(ErrorKind::TooSlow));
+        assert_eq!(rescore(&slow, &Weapon::Rifle.timing()), Quality::Failed);
+        assert_ne!(rescore(&slow, &Weapon::Awp.timing()), Quality::Failed);
+
+        let both = record(0.0, Quality::Failed, Some(ErrorKind::BothKeys));
+        assert_eq!(rescore(&both, &Weapon::Awp.timing()), Quality::Failed);
+    }
+
+    #[test]
+    fn test_csv_row_matches_header() {
+        let row = csv_row(&record(80.0, Quality::Perfect, None));
+        assert_eq!(row.split(',').count(), CSV_HEADER.split(',').count());
+        assert!(row.contains(",Rifle,A,D,80.0,Perfect,,false,,,12.0,"));
+    }
+}
@@ -0,0 +1,771 @@
+use crate::drills::Drill;
+use crate::events::NO_INPUT_AFTER;
+use crate::feedback::FeedSettings;
+use crate::input::Backend;
+use crate::metronome::{BPM_RANGE, DEFAULT_BPM};
+use crate::profiles::Weapon;
+use crate::state::TimingWindows;
+use crate::ui::Theme;
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+const CONFIG_DIR_NAME: &str = "cs2st";
+const CONFIG_FILE_NAME: &str = "config.toml";
+const RELOAD_INTERVAL: Duration = Duration::from_millis(500);  // How often the file is checked for changes
+
+// Limits for values that would break the UI
+pub const MAX_FEED_LENGTH: usize = 20;
+const FONT_SIZE_RANGE: (f32, f32) = (6.0, 120.0);
+pub const MIN_OPACITY: f32 = 0.2;  // Keep the window visible
+
+/// User overrides for the built-in constants. Everything is optional; values that are
+/// left out keep their defaults. Times are in milliseconds (feed durations in seconds).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip_serializing_if = "is_default")]
+    pub timing: TimingConfig,
+    #[serde(skip_serializing_if = "is_default")]
+    pub feed: FeedConfig,
+    #[serde(skip_serializing_if = "is_default")]
+    pub theme: ThemeConfig,
+    #[serde(skip_serializing_if = "is_default")]
+    pub input: InputConfig,
+    #[serde(skip_serializing_if = "is_default")]
+    pub metronome: MetronomeConfig,
+    /// `[[drills]]`: user-defined drills, offered after the built-in ones
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub drills: Vec<Drill>,
+}
+
+/// `[timing]`: limits shared by all weapons, plus hold-time windows per weapon
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimingConfig {
+    pub timeout_ms: Option<f32>,
+    pub perfect_gap_ms: Option<f32>,
+    pub max_gap_ms: Option<f32>,
+    pub perfect_overlap_ms: Option<f32>,
+    pub max_overlap_ms: Option<f32>,
+    /// Overlap tolerated before the attempt fails as "Both keys pressed", 0 = strict (default)
+    pub overlap_window_ms: Option<f32>,
+    #[serde(skip_serializing_if = "is_default")]
+    pub rifle: WindowConfig,
+    #[serde(skip_serializing_if = "is_default")]
+    pub awp: WindowConfig,
+    #[serde(skip_serializing_if = "is_default")]
+    pub pistol: WindowConfig,
+    #[serde(skip_serializing_if = "is_default")]
+    pub knife: WindowConfig,
+}
+
+/// `[timing.<weapon>]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub optimal_ms: Option<f32>,
+    pub min_ms: Option<f32>,
+    pub max_ms: Option<f32>,
+    pub perfect_tolerance_ms: Option<f32>,
+}
+
+/// `[feed]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedConfig {
+    pub max_entries: Option<usize>,
+    pub visible_seconds: Option<f32>,
+    pub fade_seconds: Option<f32>,
+}
+
+/// `[theme]`: colors as "#rrggbb" or "#rrggbbaa", font sizes in points
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub background: Option<String>,
+    pub card: Option<String>,
+    pub text: Option<String>,
+    pub accent: Option<String>,
+    pub good: Option<String>,
+    pub bad: Option<String>,
+    pub warning: Option<String>,
+    pub neutral: Option<String>,
+    pub huge_font: Option<f32>,
+    pub big_font: Option<f32>,
+    pub normal_font: Option<f32>,
+    pub small_font: Option<f32>,
+    /// Background opacity of the window, 0.2 - 1.0
+    pub opacity: Option<f32>,
+}
+
+/// `[input]`: backend and, for evdev, a single device to read instead of all keyboards
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputConfig {
+    pub backend: Option<Backend>,
+    pub device: Option<PathBuf>,
+    /// Report the backend as silent after this many seconds without input, 0 = never (default 10)
+    pub no_input_seconds: Option<f32>,
+}
+
+/// `[metronome]`: tempo of the built-in rhythm drill and the audible click
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetronomeConfig {
+    pub bpm: Option<f32>,
+    /// Click on every beat of rhythm drills (default true)
+    pub click: Option<bool>,
+}
+
+impl Config {
+    /// Default location: `<config dir>/cs2st/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    /// Load and validate a config file. A missing file yields the defaults.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        let config: Config = toml::from_str(&contents)
+            .map_err(|e| format!("Invalid config in {}: {}", path.display(), e))?;
+        config
+            .validate()
+            .map_err(|problems| format!("Invalid config in {}:\n  - {}", path.display(), problems.join("\n  - ")))?;
+        Ok(config)
+    }
+
+    /// Every problem found, described with the config key it comes from
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        for weapon in Weapon::ALL {
+            let timing = self.timing(weapon);
+            let section = format!("timing.{}", section_name(weapon));
+            let ms = |seconds: f32| seconds * 1000.0;
+            if timing.min <= 0.0 {
+                problems.push(format!("{}.min_ms must be positive", section));
+            }
+            if !(timing.min < timing.optimal && timing.optimal < timing.max) {
+                problems.push(format!(
+                    "{}: min_ms < optimal_ms < max_ms is required, got {:.0} / {:.0} / {:.0}",
+                    section,
+                    ms(timing.min),
+                    ms(timing.optimal),
+                    ms(timing.max)
+                ));
+            }
+            if timing.perfect_tolerance <= 0.0 {
+                problems.push(format!("{}.perfect_tolerance_ms must be positive", section));
+            }
+        }
+
+        let timing = self.timing(Weapon::default());
+        if timing.timeout <= 0.0 {
+            problems.push("timing.timeout_ms must be positive".to_string());
+        }
+        for (name, perfect, max) in [
+            ("gap", timing.perfect_gap, timing.max_gap),
+            ("overlap", timing.perfect_overlap, timing.max_overlap),
+        ] {
+            if perfect < 0.0 || perfect > max {
+                problems.push(format!(
+                    "timing: 0 <= perfect_{name}_ms <= max_{name}_ms is required, got {:.0} / {:.0}",
+                    perfect * 1000.0,
+                    max * 1000.0
+                ));
+            }
+        }
+        let overlap_window = self.overlap_window();
+        if overlap_window < 0.0 || overlap_window > timing.max_overlap {
+            problems.push(format!(
+                "timing: 0 <= overlap_window_ms <= max_overlap_ms is required, got {:.0} / {:.0}",
+                overlap_window * 1000.0,
+                timing.max_overlap * 1000.0
+            ));
+        }
+
+        let feed = self.feed();
+        if !(1..=MAX_FEED_LENGTH).contains(&feed.max_entries) {
+            problems.push(format!("feed.max_entries must be between 1 and {}", MAX_FEED_LENGTH));
+        }
+        if feed.visible_duration < 0.0 || feed.fade_duration < 0.0 {
+            problems.push("feed.visible_seconds and feed.fade_seconds must not be negative".to_string());
+        }
+
+        for (name, color) in self.theme.colors() {
+            if let Some(color) = color
+                && let Err(e) = parse_color(color)
+            {
+                problems.push(format!("theme.{}: {}", name, e));
+            }
+        }
+        for (name, size) in self.theme.font_sizes() {
+            if let Some(size) = size
+                && !(FONT_SIZE_RANGE.0..=FONT_SIZE_RANGE.1).contains(&size)
+            {
+                problems.push(format!(
+                    "theme.{} must be between {} and {}",
+                    name, FONT_SIZE_RANGE.0, FONT_SIZE_RANGE.1
+                ));
+            }
+        }
+
+        if let Some(opacity) = self.theme.opacity
+            && !(MIN_OPACITY..=1.0).contains(&opacity)
+        {
+            problems.push(format!("theme.opacity must be between {} and 1.0", MIN_OPACITY));
+        }
+
+        if self.input.no_input_seconds.is_some_and(|seconds| seconds < 0.0) {
+            problems.push("input.no_input_seconds must not be negative".to_string());
+        }
+        if self.input.backend == Some(Backend::Rdev) && self.input.device.is_some() {
+            problems.push("input.device requires backend = \"evdev\"".to_string());
+        }
+
+        if let Some(bpm) = self.metronome.bpm
+            && !(BPM_RANGE.0..=BPM_RANGE.1).contains(&bpm)
+        {
+            problems.push(format!("metronome.bpm must be between {} and {}", BPM_RANGE.0, BPM_RANGE.1));
+        }
+
+        for drill in &self.drills {
+            problems.extend(drill.validate());
+        }
+
+        if problems.is_empty() { Ok(()) } else { Err(problems) }
+    }
+
+    /// Write the config as TOML, creating the parent directory if needed.
+    /// Only overridden values are written. An existing file is updated in place:
+    /// keys that did not change keep their formatting and comments.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let new: DocumentMut = toml::to_string_pretty(self)?.parse()?;
+        let mut document: DocumentMut = match std::fs::read_to_string(path) {
+            Ok(contents) => contents.parse()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
+            Err(e) => return Err(e.into()),
+        };
+        merge_table(document.as_table_mut(), new.as_table());
+        std::fs::write(path, document.to_string())?;
+        Ok(())
+    }
+
+    fn window(&self, weapon: Weapon) -> &WindowConfig {
+        match weapon {
+            Weapon::Rifle => &self.timing.rifle,
+            Weapon::Awp => &self.timing.awp,
+            Weapon::Pistol => &self.timing.pistol,
+            Weapon::Knife => &self.timing.knife,
+        }
+    }
+
+    fn window_mut(&mut self, weapon: Weapon) -> &mut WindowConfig {
+        match weapon {
+            Weapon::Rifle => &mut self.timing.rifle,
+            Weapon::Awp => &mut self.timing.awp,
+            Weapon::Pistol => &mut self.timing.pistol,
+            Weapon::Knife => &mut self.timing.knife,
+        }
+    }
+
+    /// Timing windows of `weapon` with the overrides applied
+    pub fn timing(&self, weapon: Weapon) -> TimingWindows {
+        let defaults = weapon.timing();
+        let window = self.window(weapon);
+        let seconds = |ms: Option<f32>, default: f32| ms.map_or(default, |ms| ms / 1000.0);
+
+        TimingWindows {
+            optimal: seconds(window.optimal_ms, defaults.optimal),
+            min: seconds(window.min_ms, defaults.min),
+            max: seconds(window.max_ms, defaults.max),
+            perfect_tolerance: seconds(window.perfect_tolerance_ms, defaults.perfect_tolerance),
+            timeout: seconds(self.timing.timeout_ms, defaults.timeout),
+            perfect_gap: seconds(self.timing.perfect_gap_ms, defaults.perfect_gap),
+            max_gap: seconds(self.timing.max_gap_ms, defaults.max_gap),
+            perfect_overlap: seconds(self.timing.perfect_overlap_ms, defaults.perfect_overlap),
+            max_overlap: seconds(self.timing.max_overlap_ms, defaults.max_overlap),
+        }
+    }
+
+    /// Override `weapon`'s hold-time windows and the shared limits; values equal to
+    /// the built-in defaults are removed so the file only lists real changes
+    pub fn set_timing(&mut self, weapon: Weapon, timing: TimingWindows) {
+        let defaults = weapon.timing();
+        let window = self.window_mut(weapon);
+        window.optimal_ms = override_ms(timing.optimal, defaults.optimal);
+        window.min_ms = override_ms(timing.min, defaults.min);
+        window.max_ms = override_ms(timing.max, defaults.max);
+        window.perfect_tolerance_ms = override_ms(timing.perfect_tolerance, defaults.perfect_tolerance);
+
+        self.timing.timeout_ms = override_ms(timing.timeout, defaults.timeout);
+        self.timing.perfect_gap_ms = override_ms(timing.perfect_gap, defaults.perfect_gap);
+        self.timing.max_gap_ms = override_ms(timing.max_gap, defaults.max_gap);
+        self.timing.perfect_overlap_ms = override_ms(timing.perfect_overlap, defaults.perfect_overlap);
+        self.timing.max_overlap_ms = override_ms(timing.max_overlap, defaults.max_overlap);
+    }
+
+    /// Tolerated counter/movement key overlap in seconds, 0.0 = strict
+    pub fn overlap_window(&self) -> f32 {
+        self.timing.overlap_window_ms.map_or(0.0, |ms| ms / 1000.0)
+    }
+
+    pub fn set_overlap_window(&mut self, overlap_window: f32) {
+        self.timing.overlap_window_ms = override_ms(overlap_window, 0.0);
+    }
+
+    pub fn feed(&self) -> FeedSettings {
+        let defaults = FeedSettings::default();
+        FeedSettings {
+            max_entries: self.feed.max_entries.unwrap_or(defaults.max_entries),
+            visible_duration: self.feed.visible_seconds.unwrap_or(defaults.visible_duration),
+            fade_duration: self.feed.fade_seconds.unwrap_or(defaults.fade_duration),
+        }
+    }
+
+    pub fn set_feed(&mut self, feed: FeedSettings) {
+        let defaults = FeedSettings::default();
+        self.feed = FeedConfig {
+            max_entries: (feed.max_entries != defaults.max_entries).then_some(feed.max_entries),
+            visible_seconds: (feed.visible_duration != defaults.visible_duration).then_some(feed.visible_duration),
+            fade_seconds: (feed.fade_duration != defaults.fade_duration).then_some(feed.fade_duration),
+        };
+    }
+
+    /// Window background opacity, 1.0 = as defined by the theme colors
+    pub fn opacity(&self) -> f32 {
+        self.theme.opacity.unwrap_or(1.0)
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.theme.opacity = (opacity != 1.0).then_some(opacity);
+    }
+
+    /// Silence after which the input backend is reported, `None` = never
+    pub fn no_input_after(&self) -> Option<Duration> {
+        match self.input.no_input_seconds {
+            Some(seconds) if seconds <= 0.0 => None,
+            Some(seconds) => Some(Duration::from_secs_f32(seconds)),
+            None => Some(NO_INPUT_AFTER),
+        }
+    }
+
+    /// Built-in drills followed by the user-defined ones
+    pub fn drills(&self) -> Vec<Drill> {
+        let mut drills = Drill::builtin(self.metronome.bpm.unwrap_or(DEFAULT_BPM));
+        drills.extend(self.drills.iter().cloned());
+        drills
+    }
+
+    /// Whether rhythm drills click on every beat
+    pub fn metronome_click(&self) -> bool {
+        self.metronome.click.unwrap_or(true)
+    }
+
+    /// Theme with the overrides applied; invalid colors (rejected by `validate`) keep the default
+    pub fn theme(&self) -> Theme {
+        let defaults = Theme::default();
+        let color = |value: &Option<String>, default: Color32| {
+            value.as_deref().and_then(|value| parse_color(value).ok()).unwrap_or(default)
+        };
+        let theme = &self.theme;
+        let opacity = self.opacity();
+
+        Theme {
+            background: color(&theme.background, defaults.background).gamma_multiply(opacity),
+            card: color(&theme.card, defaults.card).gamma_multiply(opacity),
+            text: color(&theme.text, defaults.text),
+            accent: color(&theme.accent, defaults.accent),
+            good: color(&theme.good, defaults.good),
+            bad: color(&theme.bad, defaults.bad),
+            warning: color(&theme.warning, defaults.warning),
+            neutral: color(&theme.neutral, defaults.neutral),
+            huge_font: theme.huge_font.unwrap_or(defaults.huge_font),
+            big_font: theme.big_font.unwrap_or(defaults.big_font),
+            normal_font: theme.normal_font.unwrap_or(defaults.normal_font),
+            small_font: theme.small_font.unwrap_or(defaults.small_font),
+        }
+    }
+}
+
+impl ThemeConfig {
+    fn colors(&self) -> [(&'static str, Option<&str>); 8] {
+        [
+            ("background", self.background.as_deref()),
+            ("card", self.card.as_deref()),
+            ("text", self.text.as_deref()),
+            ("accent", self.accent.as_deref()),
+            ("good", self.good.as_deref()),
+            ("bad", self.bad.as_deref()),
+            ("warning", self.warning.as_deref()),
+            ("neutral", self.neutral.as_deref()),
+        ]
+    }
+
+    fn font_sizes(&self) -> [(&'static str, Option<f32>); 4] {
+        [
+            ("huge_font", self.huge_font),
+            ("big_font", self.big_font),
+            ("normal_font", self.normal_font),
+            ("small_font", self.small_font),
+        ]
+    }
+}
+
+/// Milliseconds to store for `value`, `None` if it matches the default
+fn override_ms(value: f32, default: f32) -> Option<f32> {
+    ((value - default).abs() > 1e-6).then(|| (value * 1000.0).round())
+}
+
+/// Make `old` hold the values of `new`, touching only the keys that differ
+fn merge_table(old: &mut Table, new: &Table) {
+    old.retain(|key, _| new.contains_key(key));
+    for (key, new_item) in new.iter() {
+        match (old.get_mut(key), new_item) {
+            (Some(Item::Table(old)), Item::Table(new)) => merge_table(old, new),
+            (Some(Item::ArrayOfTables(old)), Item::ArrayOfTables(new)) if old.len() == new.len() => {
+                for (old, new) in old.iter_mut().zip(new.iter()) {
+                    merge_table(old, new);
+                }
+            }
+            (Some(Item::Value(old)), Item::Value(new)) => {
+                if !same_value(old, new) {
+                    let decor = old.decor().clone();
+                    *old = new.clone();
+                    *old.decor_mut() = decor;
+                }
+            }
+            _ => {
+                old.insert(key, new_item.clone());
+            }
+        }
+    }
+}
+
+/// Equal values regardless of formatting; `80` and `80.0` are the same number
+fn same_value(a: &Value, b: &Value) -> bool {
+    let number = |value: &Value| value.as_float().or_else(|| value.as_integer().map(|i| i as f64));
+    match (a, b) {
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_value(a, b)),
+        (Value::InlineTable(a), Value::InlineTable(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| same_value(a, b)))
+        }
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Datetime(a), Value::Datetime(b)) => a.value() == b.value(),
+        _ => matches!((number(a), number(b)), (Some(a), Some(b)) if a == b),
+    }
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Name of the weapon's `[timing.<weapon>]` table
+fn section_name(weapon: Weapon) -> &'static str {
+    match weapon {
+        Weapon::Rifle => "rifle",
+        Weapon::Awp => "awp",
+        Weapon::Pistol => "pistol",
+        Weapon::Knife => "knife",
+    }
+}
+
+/// Parse "#rrggbb" or "#rrggbbaa"
+pub fn parse_color(value: &str) -> Result<Color32, String> {
+    let hex = value
+        .strip_prefix('#')
+        .filter(|hex| (hex.len() == 6 || hex.len() == 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| format!("\"{}\" is not a color, expected \"#rrggbb\" or \"#rrggbbaa\"", value))?;
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or_default();
+    let alpha = if hex.len() == 8 { channel(6) } else { 255 };
+    Ok(Color32::from_rgba_unmultiplied(channel(0), channel(2), channel(4), alpha))
+}
+
+/// Polls the config file's modification time so edits apply without a restart
+pub struct ConfigWatcher {
+    path: PathBuf,
+    /// Modification time and size when last loaded
+    stamp: Option<(SystemTime, u64)>,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            stamp: file_stamp(&path),
+            path,
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write `config` to the watched file, unless the file was changed since it was
+    /// last loaded: those edits have not been seen yet and would be overwritten
+    pub fn save(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        if file_stamp(&self.path) != self.stamp {
+            return Err(format!("{} changed since it was loaded, not overwriting it", self.path.display()).into());
+        }
+        config.save(&self.path)?;
+        self.stamp = file_stamp(&self.path);
+        Ok(())
+    }
+
+    /// The reloaded config if the file changed since the last load. Rechecks at most
+    /// every `RELOAD_INTERVAL`; an invalid file is reported once per change.
+    pub fn poll(&mut self, now: Instant) -> Option<Result<Config, Box<dyn std::error::Error>>> {
+        if now.saturating_duration_since(self.last_check) < RELOAD_INTERVAL {
+            return None;
+        }
+        self.last_check = now;
+
+        let stamp = file_stamp(&self.path);
+        if stamp == self.stamp {
+            return None;
+        }
+        self.stamp = stamp;
+        Some(Config::load(&self.path))
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("cs2st-config-{}-{}.toml", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_empty_config_keeps_defaults() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.timing(Weapon::Awp), Weapon::Awp.timing());
+        assert_eq!(config.feed(), FeedSettings::default());
+        assert_eq!(config.theme(), Theme::default());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let config: Config = toml::from_str(
+            r##"
+            [timing]
+            timeout_ms = 250
+            max_gap_ms = 40
+            overlap_window_ms = 20
+
+            [timing.rifle]
+            optimal_ms = 85
+            max_ms = 130
+
+            [feed]
+            max_entries = 8
+
+            [theme]
+            good = "#00ff00"
+            small_font = 16
+
+            [input]
+            backend = "evdev"
+            device = "/dev/input/event3"
+
+            [metronome]
+            bpm = 90
+            click = false
+
+            [[drills]]
+            name = "Long AWP"
+            seconds = 90
+            goal = { perfect_percent = 60 }
+            weapon = "awp"
+            "##,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+
+        let rifle = config.timing(Weapon::Rifle);
+        assert!((rifle.optimal - 0.085).abs() < 1e-6);
+        assert!((rifle.max - 0.130).abs() < 1e-6);
+        assert_eq!(rifle.min, TimingWindows::DEFAULT.min);
+        assert!((rifle.timeout - 0.250).abs() < 1e-6);
+
+        // Shared limits apply to every weapon, hold windows only to their own
+        let awp = config.timing(Weapon::Awp);
+        assert!((awp.max_gap - 0.040).abs() < 1e-6);
+        assert_eq!(awp.optimal, Weapon::Awp.timing().optimal);
+        assert!((config.overlap_window() - 0.020).abs() < 1e-6);
+
+        assert_eq!(config.feed().max_entries, 8);
+        assert_eq!(config.theme().good, Color32::from_rgb(0, 255, 0));
+        assert_eq!(config.theme().small_font, 16.0);
+        assert_eq!(config.input.backend, Some(Backend::Evdev));
+        assert_eq!(config.input.device.as_deref(), Some(Path::new("/dev/input/event3")));
+        assert_eq!(config.drills[0].goal, crate::drills::Goal::PerfectPercent(60.0));
+        assert_eq!(config.drills[0].weapon, Some(Weapon::Awp));
+        assert!(!config.metronome_click());
+        assert_eq!(config.drills().iter().find(|drill| drill.name == "Rhythm").unwrap().bpm, Some(90.0));
+    }
+
+    #[test]
+    fn test_validation_messages() {
+        let config: Config = toml::from_str(
+            r##"
+            [timing]
+            max_overlap_ms = 50
+            overlap_window_ms = 60
+
+            [timing.awp]
+            min_ms = 120
+
+            [feed]
+            max_entries = 0
+
+            [theme]
+            accent = "blue"
+
+            [input]
+            backend = "rdev"
+            device = "/dev/input/event3"
+            "##,
+        )
+        .unwrap();
+        let problems = config.validate().unwrap_err();
+
+        assert_eq!(problems.len(), 5);
+        assert!(problems[0].starts_with("timing.awp: min_ms < optimal_ms < max_ms"));
+        assert!(problems[1].starts_with("timing: 0 <= overlap_window_ms <= max_overlap_ms"));
+        assert!(problems[2].starts_with("feed.max_entries"));
+        assert!(problems[3].starts_with("theme.accent: \"blue\" is not a color"));
+        assert!(problems[4].starts_with("input.device requires"));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let path = temp_path("unknown");
+        std::fs::write(&path, "[timing]\noptimal = 80\n").unwrap();
+
+        let error = Config::load(&path).unwrap_err().to_string();
+        assert!(error.contains("Invalid config"));
+        assert!(error.contains("optimal"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_save_only_writes_overrides() {
+        let path = temp_path("save");
+        let mut config = Config::default();
+
+        let mut timing = config.timing(Weapon::Pistol);
+        timing.optimal = 0.070;
+        config.set_timing(Weapon::Pistol, timing);
+        config.set_feed(FeedSettings {
+            fade_duration: 2.0,
+            ..FeedSettings::default()
+        });
+        config.set_opacity(0.8);
+        config.save(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("[timing.pistol]"));
+        assert!(contents.contains("optimal_ms = 70"));
+        assert!(!contents.contains("rifle"));
+        assert!(!contents.contains("timeout_ms"));
+        assert!(!contents.contains("max_entries"));
+        assert_eq!(Config::load(&path).unwrap(), config);
+
+        // Back to the defaults: nothing left to write
+        config.set_timing(Weapon::Pistol, Weapon::Pistol.timing());
+        assert_eq!(config.timing, TimingConfig::default());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_save_keeps_comments_and_unchanged_keys() {
+        let path = temp_path("comments");
+        let original = "# My settings\n[timing]\n# coasting limit\nmax_gap_ms = 40 # tight\n\n[feed]\nmax_entries = 8\n";
+        std::fs::write(&path, original).unwrap();
+
+        let mut config = Config::load(&path).unwrap();
+        config.set_opacity(0.8);
+        config.feed.max_entries = None;
+        config.save(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# My settings\n[timing]\n# coasting limit\nmax_gap_ms = 40 # tight\n"));
+        assert!(!contents.contains("max_entries"));
+        assert!(contents.contains("opacity = 0.8"));
+        assert_eq!(Config::load(&path).unwrap(), config);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_watcher_does_not_overwrite_unseen_edits() {
+        let path = temp_path("unseen");
+        std::fs::write(&path, "[feed]\nmax_entries = 8\n").unwrap();
+        let mut watcher = ConfigWatcher::new(path.clone());
+        let mut config = Config::load(&path).unwrap();
+
+        config.set_opacity(0.8);
+        watcher.save(&config).unwrap();
+        assert!(Config::load(&path).unwrap().theme.opacity.is_some());
+
+        std::fs::write(&path, "[feed]\nmax_entries = 12\n").unwrap();
+        config.set_opacity(0.5);
+        assert!(watcher.save(&config).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[feed]\nmax_entries = 12\n");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#3498db").unwrap(), Color32::from_rgb(0x34, 0x98, 0xdb));
+        assert_eq!(
+            parse_color("#00000080").unwrap(),
+            Color32::from_rgba_unmultiplied(0, 0, 0, 0x80)
+        );
+        assert!(parse_color("3498db").is_err());
+        assert!(parse_color("#34zz").is_err());
+    }
+
+    #[test]
+    fn test_watcher_reloads_changes() {
+        let path = temp_path("watch");
+        std::fs::write(&path, "[feed]\nmax_entries = 3\n").unwrap();
+
+        let mut watcher = ConfigWatcher::new(path.clone());
+        let later = Instant::now() + RELOAD_INTERVAL;
+        assert!(watcher.poll(later).is_none());
+
+        std::fs::write(&path, "[feed]\nmax_entries = 12\n").unwrap();
+        let config = watcher.poll(later + RELOAD_INTERVAL).unwrap().unwrap();
+        assert_eq!(config.feed().max_entries, 12);
+
+        // Reported once per change
+        assert!(watcher.poll(later + RELOAD_INTERVAL * 2).is_none());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,536 @@
+#[cfg(target_os = "linux")]
+use crate::evdev_source::{self, DeviceKind, DeviceStatus, InputDevice};
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Ok,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Ok => " OK ",
+            Severity::Info => "INFO",
+            Severity::Warning => "WARN",
+            Severity::Error => "FAIL",
+        }
+    }
+}
+
+/// One aspect of the system that affects keyboard access
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub summary: String,
+    pub details: Vec<String>,
+    /// What to do about a warning or failure
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, severity: Severity, summary: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity,
+            summary: summary.into(),
+            details: Vec::new(),
+            fix: None,
+        }
+    }
+
+    fn details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Everything that decides whether the trainer can read the keyboard, for `doctor` and the GUI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Inspect the running system
+    pub fn collect() -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            Self::from_system(&SystemInfo::read())
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Self {
+                checks: vec![Check::new(
+                    "Platform",
+                    Severity::Info,
+                    "Input diagnostics are only available on Linux",
+                )],
+            }
+        }
+    }
+
+    /// Most severe result of all checks
+    pub fn worst(&self) -> Severity {
+        self.checks.iter().map(|check| check.severity).max().unwrap_or(Severity::Ok)
+    }
+
+    /// Plain text for the terminal
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        for check in &self.checks {
+            lines.push(format!("[{}] {}: {}", check.severity.label(), check.name, check.summary));
+            lines.extend(check.details.iter().map(|detail| format!("       {}", detail)));
+            if let Some(fix) = &check.fix {
+                lines.push(format!("       Fix: {}", fix));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Snapshot of the files and environment the checks look at, so they can be tested
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Default)]
+pub struct SystemInfo {
+    pub session_type: Option<String>,
+    pub wayland_display: Option<String>,
+    pub display: Option<String>,
+    pub devices: Vec<InputDevice>,
+    /// Owner groups of the keyboard device nodes
+    pub keyboard_gids: Vec<u32>,
+    /// Contents of /proc/self/status, /etc/group and /etc/passwd
+    pub proc_status: String,
+    pub etc_group: String,
+    pub etc_passwd: String,
+    /// udev rule lines that mention input devices: file, line
+    pub udev_rules: Vec<(PathBuf, String)>,
+    /// Mode and group of /dev/uinput, if it exists
+    pub uinput: Option<(u32, u32)>,
+}
+
+#[cfg(target_os = "linux")]
+const UDEV_RULE_DIRS: [&str; 3] = ["/etc/udev/rules.d", "/usr/lib/udev/rules.d", "/lib/udev/rules.d"];
+
+#[cfg(target_os = "linux")]
+const INPUT_GROUP: &str = "input";
+
+#[cfg(target_os = "linux")]
+impl SystemInfo {
+    pub fn read() -> Self {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let devices = evdev_source::scan_devices();
+        let mut keyboard_gids: Vec<u32> = devices
+            .iter()
+            .filter(|device| device.kind() == Some(DeviceKind::Keyboard) || evdev_source::has_keyboard_keys(&device.path))
+            .filter_map(|device| std::fs::metadata(&device.path).ok())
+            .map(|metadata| metadata.gid())
+            .collect();
+        keyboard_gids.sort_unstable();
+        keyboard_gids.dedup();
+
+        let read = |path: &str| std::fs::read_to_string(path).unwrap_or_default();
+        Self {
+            session_type: std::env::var("XDG_SESSION_TYPE").ok(),
+            wayland_display: std::env::var("WAYLAND_DISPLAY").ok(),
+            display: std::env::var("DISPLAY").ok(),
+            devices,
+            keyboard_gids,
+            proc_status: read("/proc/self/status"),
+            etc_group: read("/etc/group"),
+            etc_passwd: read("/etc/passwd"),
+            udev_rules: read_udev_rules(),
+            uinput: std::fs::metadata("/dev/uinput")
+                .ok()
+                .map(|metadata| (metadata.permissions().mode() & 0o777, metadata.gid())),
+        }
+    }
+}
+
+/// Rule lines about uinput or event devices, from all rule directories
+#[cfg(target_os = "linux")]
+fn read_udev_rules() -> Vec<(PathBuf, String)> {
+    let mut rules = Vec::new();
+    for dir in UDEV_RULE_DIRS {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+        paths.sort();
+        for path in paths {
+            let contents = std::fs::read_to_string(&path).unwrap_or_default();
+            rules.extend(
+                contents
+                    .lines()
+                    .filter(|line| is_input_rule(line))
+                    .map(|line| (path.clone(), line.trim().to_string())),
+            );
+        }
+    }
+    rules
+}
+
+#[cfg(target_os = "linux")]
+fn is_input_rule(line: &str) -> bool {
+    let line = line.trim();
+    !line.starts_with('#')
+        && (line.contains("uinput") || line.contains("event*"))
+        && (line.contains("GROUP") || line.contains("MODE") || line.contains("uaccess"))
+}
+
+#[cfg(target_os = "linux")]
+impl Report {
+    pub fn from_system(system: &SystemInfo) -> Self {
+        let keyboards = system
+            .devices
+            .iter()
+            .filter(|device| device.kind() == Some(DeviceKind::Keyboard))
+            .count();
+        let x11 = is_x11(system);
+        Self {
+            checks: vec![
+                session_check(system, keyboards > 0),
+                devices_check(system, keyboards, x11),
+                groups_check(system, x11),
+                udev_check(system),
+                capabilities_check(system),
+            ],
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn is_wayland(system: &SystemInfo) -> bool {
+    system.session_type.as_deref() == Some("wayland") || system.wayland_display.is_some()
+}
+
+/// An X11 session, where the rdev backend reads the keyboard without /dev/input access
+#[cfg(target_os = "linux")]
+fn is_x11(system: &SystemInfo) -> bool {
+    !is_wayland(system) && system.display.is_some()
+}
+
+#[cfg(target_os = "linux")]
+fn session_check(system: &SystemInfo, evdev_ready: bool) -> Check {
+    if is_wayland(system) {
+        let check = Check::new("Session", Severity::Warning, "Wayland: rdev cannot see input sent to other windows");
+        if evdev_ready {
+            Check { severity: Severity::Info, ..check }.details(vec!["The evdev backend is used instead".to_string()])
+        } else {
+            check.fix("Make a keyboard readable (see below) so the evdev backend can be used")
+        }
+    } else if let Some(display) = &system.display {
+        Check::new("Session", Severity::Ok, format!("X11 (DISPLAY={})", display))
+    } else {
+        Check::new("Session", Severity::Info, "No graphical session: use the evdev backend and `tui`")
+    }
+}
+
+/// Unreadable devices only break keyboard access when rdev cannot take over
+#[cfg(target_os = "linux")]
+fn devices_check(system: &SystemInfo, keyboards: usize, x11: bool) -> Check {
+    let unreadable = system
+        .devices
+        .iter()
+        .filter(|device| matches!(device.status, DeviceStatus::Unreadable(_)))
+        .count();
+    let details = system.devices.iter().map(InputDevice::describe).collect();
+
+    if system.devices.is_empty() {
+        Check::new("Devices", Severity::Warning, "No devices in /dev/input")
+            .fix("Check that /dev/input is available, e.g. in a container pass it through")
+    } else if keyboards > 0 {
+        Check::new(
+            "Devices",
+            Severity::Ok,
+            format!("{} readable keyboard(s), {} unreadable device(s)", keyboards, unreadable),
+        )
+        .details(details)
+    } else if x11 {
+        Check::new(
+            "Devices",
+            Severity::Warning,
+            format!("No readable keyboard, {} device(s) unreadable: evdev is unavailable, rdev (X11) is used", unreadable),
+        )
+        .details(details)
+        .fix("Only needed for evdev: see the group check")
+    } else {
+        Check::new("Devices", Severity::Error, format!("No readable keyboard, {} device(s) unreadable", unreadable))
+            .details(details)
+            .fix("See the group check; or pick a device with --device")
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn groups_check(system: &SystemInfo, x11: bool) -> Check {
+    let uid = status_ids(&system.proc_status, "Uid:").get(1).copied();
+    let user = uid
+        .and_then(|uid| user_name(&system.etc_passwd, uid))
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "$USER".to_string());
+    let active = status_ids(&system.proc_status, "Groups:");
+    let primary = status_ids(&system.proc_status, "Gid:").get(1).copied();
+
+    // The group owning the keyboard nodes, usually `input`
+    let gid = system.keyboard_gids.first().copied().or_else(|| group_id(&system.etc_group, INPUT_GROUP));
+    let Some(gid) = gid else {
+        return Check::new("Groups", Severity::Info, "No input group found");
+    };
+    if gid == 0 {
+        return Check::new("Groups", Severity::Info, "The keyboard devices belong to the root group, no group grants access")
+            .fix("See the udev check; or run the trainer with sudo");
+    }
+    let name = group_name(&system.etc_group, gid).map_or_else(|| gid.to_string(), str::to_string);
+
+    if active.contains(&gid) || primary == Some(gid) {
+        Check::new("Groups", Severity::Ok, format!("{} is in the {} group", user, name))
+    } else if group_members(&system.etc_group, gid).contains(&user.as_str()) {
+        Check::new(
+            "Groups",
+            Severity::Warning,
+            format!("{} was added to {}, but this session started before that", user, name),
+        )
+        .fix("Log out and back in (or reboot)")
+    } else {
+        let severity = if x11 { Severity::Warning } else { Severity::Error };
+        let check = Check::new("Groups", severity, format!("{} is not in the {} group", user, name))
+            .fix(format!("sudo usermod -a -G {} {}, then log out and back in", name, user));
+        if x11 {
+            check.details(vec!["Only the evdev backend needs it, rdev reads the keyboard through X11".to_string()])
+        } else {
+            check
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn udev_check(system: &SystemInfo) -> Check {
+    let details: Vec<String> = system
+        .udev_rules
+        .iter()
+        .map(|(path, line)| format!("{}: {}", path.display(), line))
+        .collect();
+    let uinput = match system.uinput {
+        Some((mode, gid)) => format!(
+            "/dev/uinput mode {:o}, group {}",
+            mode,
+            group_name(&system.etc_group, gid).map_or_else(|| gid.to_string(), str::to_string)
+        ),
+        None => "no /dev/uinput".to_string(),
+    };
+
+    if details.is_empty() {
+        Check::new("udev", Severity::Info, format!("No custom rules for input devices; {}", uinput))
+    } else {
+        Check::new("udev", Severity::Ok, format!("{} rule(s) for input devices; {}", details.len(), uinput)).details(details)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn capabilities_check(system: &SystemInfo) -> Check {
+    const CAP_DAC_OVERRIDE: u32 = 1;
+    const CAP_DAC_READ_SEARCH: u32 = 2;
+
+    let euid = status_ids(&system.proc_status, "Uid:").get(1).copied();
+    let effective = system
+        .proc_status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|caps| u64::from_str_radix(caps.trim(), 16).ok())
+        .unwrap_or(0);
+    let has = |cap: u32| effective & (1 << cap) != 0;
+
+    if euid == Some(0) {
+        Check::new("Capabilities", Severity::Info, "Running as root: every device is readable")
+            .details(vec!["Files written now (config, history) will be owned by root".to_string()])
+    } else if has(CAP_DAC_OVERRIDE) || has(CAP_DAC_READ_SEARCH) {
+        Check::new("Capabilities", Severity::Info, format!("Effective capabilities {:x} bypass file permissions", effective))
+    } else {
+        Check::new("Capabilities", Severity::Ok, "No extra capabilities, device permissions apply")
+    }
+}
+
+/// Numbers of a `/proc/self/status` line such as `Uid:  1000  1000  1000  1000`
+#[cfg(target_os = "linux")]
+fn status_ids(status: &str, key: &str) -> Vec<u32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(key))
+        .map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// `/etc/group` entries as (name, gid, members)
+#[cfg(target_os = "linux")]
+fn groups(etc_group: &str) -> impl Iterator<Item = (&str, u32, Vec<&str>)> {
+    etc_group.lines().filter_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        let gid = fields.nth(1)?.parse().ok()?;
+        let members = fields.next().unwrap_or_default().split(',').filter(|m| !m.is_empty()).collect();
+        Some((name, gid, members))
+    })
+}
+
+/// Name of group `gid` in the contents of an /etc/group file
+#[cfg(target_os = "linux")]
+pub fn group_name(etc_group: &str, gid: u32) -> Option<&str> {
+    groups(etc_group).find(|(_, id, _)| *id == gid).map(|(name, _, _)| name)
+}
+
+#[cfg(target_os = "linux")]
+fn group_id(etc_group: &str, name: &str) -> Option<u32> {
+    groups(etc_group).find(|(group, _, _)| *group == name).map(|(_, gid, _)| gid)
+}
+
+#[cfg(target_os = "linux")]
+fn group_members(etc_group: &str, gid: u32) -> Vec<&str> {
+    groups(etc_group).find(|(_, id, _)| *id == gid).map(|(_, _, members)| members).unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn user_name(etc_passwd: &str, uid: u32) -> Option<String> {
+    etc_passwd.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        (fields.nth(1)?.parse::<u32>().ok()? == uid).then(|| name.to_string())
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    fn device(path: &str, name: &str, status: DeviceStatus) -> InputDevice {
+        InputDevice {
+            path: PathBuf::from(path),
+            name: name.to_string(),
+            status,
+        }
+    }
+
+    const ETC_GROUP: &str = "root:x:0:\ninput:x:104:alice\nalice:x:1000:\n";
+    const ETC_PASSWD: &str = "root:x:0:0::/root:/bin/sh\nalice:x:1000:1000::/home/alice:/bin/sh\n";
+
+    fn status(groups: &str) -> String {
+        format!("Uid:\t1000\t1000\t1000\t1000\nGid:\t1000\t1000\t1000\t1000\nGroups:\t{}\nCapEff:\t0000000000000000\n", groups)
+    }
+
+    fn wayland_without_access() -> SystemInfo {
+        SystemInfo {
+            session_type: Some("wayland".to_string()),
+            wayland_display: Some("wayland-0".to_string()),
+            devices: vec![device(
+                "/dev/input/event3",
+                "AT Translated Set 2 keyboard",
+                DeviceStatus::Unreadable("permission denied (mode 660, group input)".to_string()),
+            )],
+            keyboard_gids: vec![104],
+            proc_status: status("1000"),
+            etc_group: "root:x:0:\ninput:x:104:\n".to_string(),
+            etc_passwd: ETC_PASSWD.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn check<'a>(report: &'a Report, name: &str) -> &'a Check {
+        report.checks.iter().find(|check| check.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_wayland_without_access() {
+        let report = Report::from_system(&wayland_without_access());
+        assert_eq!(report.worst(), Severity::Error);
+        assert_eq!(check(&report, "Session").severity, Severity::Warning);
+        assert_eq!(check(&report, "Devices").severity, Severity::Error);
+
+        let groups = check(&report, "Groups");
+        assert_eq!(groups.summary, "alice is not in the input group");
+        assert_eq!(groups.fix.as_deref(), Some("sudo usermod -a -G input alice, then log out and back in"));
+
+        let text = report.to_text();
+        assert!(text.contains("[FAIL] Devices: No readable keyboard, 1 device(s) unreadable"));
+        assert!(text.contains("       /dev/input/event3 \"AT Translated Set 2 keyboard\" - unreadable"));
+    }
+
+    #[test]
+    fn test_group_added_but_not_active() {
+        let system = SystemInfo {
+            etc_group: ETC_GROUP.to_string(),
+            ..wayland_without_access()
+        };
+        let groups = check(&Report::from_system(&system), "Groups").clone();
+        assert_eq!(groups.severity, Severity::Warning);
+        assert!(groups.fix.unwrap().starts_with("Log out"));
+
+        let system = SystemInfo {
+            etc_group: ETC_GROUP.to_string(),
+            proc_status: status("104 1000"),
+            devices: vec![device("/dev/input/event3", "Keyboard", DeviceStatus::Readable(DeviceKind::Keyboard))],
+            ..wayland_without_access()
+        };
+        let report = Report::from_system(&system);
+        assert_eq!(check(&report, "Groups").severity, Severity::Ok);
+        assert_eq!(check(&report, "Session").severity, Severity::Info);
+        assert_eq!(report.worst(), Severity::Info);
+    }
+
+    #[test]
+    fn test_groups_follow_the_keyboard_nodes() {
+        // Keyboards owned by a group other than `input`
+        let system = SystemInfo {
+            keyboard_gids: vec![46],
+            etc_group: "input:x:104:alice\nplugdev:x:46:\n".to_string(),
+            ..wayland_without_access()
+        };
+        assert_eq!(check(&Report::from_system(&system), "Groups").summary, "alice is not in the plugdev group");
+
+        let system = SystemInfo {
+            keyboard_gids: vec![0],
+            ..wayland_without_access()
+        };
+        assert_eq!(check(&Report::from_system(&system), "Groups").severity, Severity::Info);
+        assert_eq!(group_name(ETC_GROUP, 104), Some("input"));
+    }
+
+    #[test]
+    fn test_capabilities_and_udev() {
+        let system = SystemInfo {
+            display: Some(":0".to_string()),
+            session_type: Some("x11".to_string()),
+            wayland_display: None,
+            proc_status: "Uid:\t0\t0\t0\t0\nCapEff:\t000001ffffffffff\n".to_string(),
+            udev_rules: vec![(
+                PathBuf::from("/etc/udev/rules.d/99-input.rules"),
+                r#"KERNEL=="uinput", GROUP="input", MODE="0660""#.to_string(),
+            )],
+            uinput: Some((0o660, 104)),
+            ..wayland_without_access()
+        };
+        let report = Report::from_system(&system);
+        assert_eq!(check(&report, "Session").summary, "X11 (DISPLAY=:0)");
+        // rdev works on X11, so unreadable devices only make evdev unavailable
+        assert_eq!(check(&report, "Devices").severity, Severity::Warning);
+        assert_eq!(check(&report, "Groups").severity, Severity::Warning);
+        assert_eq!(report.worst(), Severity::Warning);
+        assert!(check(&report, "Capabilities").summary.starts_with("Running as root"));
+
+        let udev = check(&report, "udev");
+        assert_eq!(udev.summary, "1 rule(s) for input devices; /dev/uinput mode 660, group input");
+        assert!(udev.details[0].starts_with("/etc/udev/rules.d/99-input.rules: KERNEL"));
+
+        assert!(is_input_rule(r#"KERNEL=="event*", SUBSYSTEM=="input", TAG+="uaccess""#));
+        assert!(!is_input_rule(r#"# KERNEL=="uinput", MODE="0660""#));
+    }
+}
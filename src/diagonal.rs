@@ -0,0 +1,287 @@
+use crate::state::{Axis, CompletionResult, CounterStrafeState, Quality, StrafeKey, TimingWindows};
+use std::time::Instant;
+
+/// A finished stop, either on one axis or a diagonal stop countered on both
+pub enum StopResult {
+    Single(CompletionResult),
+    Diagonal(DiagonalResult),
+}
+
+impl StopResult {
+    /// Verdict of the whole stop
+    pub fn quality(&self) -> Quality {
+        match self {
+            StopResult::Single(result) => result.quality,
+            StopResult::Diagonal(diagonal) => diagonal.quality,
+        }
+    }
+
+    /// Results of the countered axes, horizontal first
+    pub fn axes(&self) -> impl Iterator<Item = &CompletionResult> {
+        let (single, horizontal, vertical) = match self {
+            StopResult::Single(result) => (Some(result), None, None),
+            StopResult::Diagonal(diagonal) => (None, diagonal.horizontal.as_ref(), diagonal.vertical.as_ref()),
+        };
+        single.into_iter().chain(horizontal).chain(vertical)
+    }
+}
+
+/// Result of stopping diagonal movement (e.g. W+A). Each axis is evaluated on its own;
+/// `None` means that axis was released without being countered.
+pub struct DiagonalResult {
+    pub horizontal: Option<CompletionResult>,
+    pub vertical: Option<CompletionResult>,
+    pub quality: Quality,
+}
+
+impl DiagonalResult {
+    fn new(horizontal: Option<CompletionResult>, vertical: Option<CompletionResult>) -> Self {
+        let quality = combine_quality(
+            horizontal.as_ref().map(|r| r.quality),
+            vertical.as_ref().map(|r| r.quality),
+        );
+        Self {
+            horizontal,
+            vertical,
+            quality,
+        }
+    }
+
+    pub fn axis_result(&self, axis: Axis) -> Option<&CompletionResult> {
+        match axis {
+            Axis::Horizontal => self.horizontal.as_ref(),
+            Axis::Vertical => self.vertical.as_ref(),
+        }
+    }
+}
+
+/// Combined diagonal quality: the worse of both axes, failed if an axis was not countered
+pub fn combine_quality(horizontal: Option<Quality>, vertical: Option<Quality>) -> Quality {
+    match (horizontal, vertical) {
+        (Some(Quality::Perfect), Some(Quality::Perfect)) => Quality::Perfect,
+        (Some(Quality::Failed), _) | (_, Some(Quality::Failed)) => Quality::Failed,
+        (Some(_), Some(_)) => Quality::Good,
+        _ => Quality::Failed,
+    }
+}
+
+/// Counter results collected while a diagonal stop is in progress
+#[derive(Default)]
+struct PendingDiagonal {
+    horizontal: Option<CompletionResult>,
+    vertical: Option<CompletionResult>,
+}
+
+/// Two-axis state machine: one `CounterStrafeState` per axis, plus diagonal tracking
+/// whenever both axes were moving at the same time.
+pub struct MovementState {
+    horizontal: CounterStrafeState,
+    vertical: CounterStrafeState,
+    diagonal: Option<PendingDiagonal>,
+    timing: TimingWindows,
+    /// Tolerated counter/movement key overlap in seconds, 0.0 = strict
+    overlap_window: f32,
+}
+
+impl MovementState {
+    pub fn new() -> Self {
+        Self {
+            horizontal: CounterStrafeState::new(),
+            vertical: CounterStrafeState::new(),
+            diagonal: None,
+            timing: TimingWindows::default(),
+            overlap_window: 0.0,
+        }
+    }
+
+    /// Timing windows used to evaluate counter-strafes on both axes
+    pub fn timing(&self) -> &TimingWindows {
+        &self.timing
+    }
+
+    pub fn set_timing(&mut self, timing: TimingWindows) {
+        self.timing = timing;
+    }
+
+    pub fn overlap_window(&self) -> f32 {
+        self.overlap_window
+    }
+
+    pub fn set_overlap_window(&mut self, overlap_window: f32) {
+        self.overlap_window = overlap_window;
+    }
+
+    pub fn axis(&self, axis: Axis) -> &CounterStrafeState {
+        match axis {
+            Axis::Horizontal => &self.horizontal,
+            Axis::Vertical => &self.vertical,
+        }
+    }
+
+    fn axis_mut(&mut self, axis: Axis) -> &mut CounterStrafeState {
+        match axis {
+            Axis::Horizontal => &mut self.horizontal,
+            Axis::Vertical => &mut self.vertical,
+        }
+    }
+
+    /// Handle key press event
+    pub fn on_key_press(&mut self, key: StrafeKey, now: Instant) -> Option<StopResult> {
+        let result = self.axis_mut(key.axis()).on_key_press(key, now);
+        self.detect_diagonal();
+        result.and_then(|result| self.resolve(result))
+    }
+
+    /// Handle key release event
+    pub fn on_key_release(&mut self, key: StrafeKey, now: Instant) -> Option<StopResult> {
+        let (timing, overlap_window) = (self.timing, self.overlap_window);
+        let result = self.axis_mut(key.axis()).on_key_release(key, now, &timing, overlap_window);
+        result.and_then(|result| self.resolve(result))
+    }
+
+    /// Check both axes for timeout; a diagonal stop resolves once neither axis can still be countered
+    pub fn check_timeout(&mut self, now: Instant) -> Option<StopResult> {
+        let mut timed_out = false;
+        for axis in Axis::ALL {
+            let timing = self.timing;
+            timed_out |= self.axis_mut(axis).check_timeout(now, &timing);
+        }
+        if timed_out {
+            self.finish_diagonal()
+        } else {
+            None
+        }
+    }
+
+    /// State shown in the main display: the axis with an attempt in progress, horizontal otherwise
+    pub fn display_state(&self) -> &CounterStrafeState {
+        if self.vertical.is_active() && !self.horizontal.is_active() {
+            &self.vertical
+        } else {
+            &self.horizontal
+        }
+    }
+
+    /// Start tracking a diagonal stop once both axes are held at the same time
+    fn detect_diagonal(&mut self) {
+        if self.diagonal.is_none() && self.horizontal.is_strafing() && self.vertical.is_strafing() {
+            self.diagonal = Some(PendingDiagonal::default());
+        }
+    }
+
+    fn resolve(&mut self, result: CompletionResult) -> Option<StopResult> {
+        match &mut self.diagonal {
+            Some(pending) => {
+                match result.axis() {
+                    Axis::Horizontal => pending.horizontal = Some(result),
+                    Axis::Vertical => pending.vertical = Some(result),
+                }
+                self.finish_diagonal()
+            }
+            None => Some(StopResult::Single(result)),
+        }
+    }
+
+    /// Emit the diagonal result when both axes are countered or can no longer be
+    fn finish_diagonal(&mut self) -> Option<StopResult> {
+        let pending = self.diagonal.as_ref()?;
+        let settled = |axis: Axis, result: &Option<CompletionResult>| {
+            result.is_some() || !self.axis(axis).is_active()
+        };
+        if !settled(Axis::Horizontal, &pending.horizontal) || !settled(Axis::Vertical, &pending.vertical) {
+            return None;
+        }
+
+        let pending = self.diagonal.take()?;
+        if pending.horizontal.is_none() && pending.vertical.is_none() {
+            // Neither axis was countered: not a stop attempt, same as a single-axis timeout
+            return None;
+        }
+        Some(StopResult::Diagonal(DiagonalResult::new(pending.horizontal, pending.vertical)))
+    }
+}
+
+impl Default for MovementState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::TIMEOUT_NO_COUNTER;
+    use std::time::Duration;
+
+    fn at(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_single_axis_stop() {
+        let mut movement = MovementState::new();
+        let start = Instant::now();
+
+        movement.on_key_press(StrafeKey::A, at(start, 0));
+        movement.on_key_release(StrafeKey::A, at(start, 300));
+        movement.on_key_press(StrafeKey::D, at(start, 305));
+        let result = movement.on_key_release(StrafeKey::D, at(start, 385));
+
+        assert!(matches!(result, Some(StopResult::Single(r)) if r.quality == Quality::Perfect));
+    }
+
+    #[test]
+    fn test_diagonal_stop_evaluates_both_axes() {
+        let mut movement = MovementState::new();
+        let start = Instant::now();
+
+        movement.on_key_press(StrafeKey::W, at(start, 0));
+        movement.on_key_press(StrafeKey::A, at(start, 10));
+        movement.on_key_release(StrafeKey::W, at(start, 300));
+        movement.on_key_release(StrafeKey::A, at(start, 302));
+        movement.on_key_press(StrafeKey::S, at(start, 305));
+        movement.on_key_press(StrafeKey::D, at(start, 306));
+
+        // First counter released: still waiting for the other axis
+        assert!(movement.on_key_release(StrafeKey::D, at(start, 386)).is_none());
+
+        match movement.on_key_release(StrafeKey::S, at(start, 405)) {
+            Some(StopResult::Diagonal(result)) => {
+                assert_eq!(result.axis_result(Axis::Horizontal).unwrap().quality, Quality::Perfect);
+                assert_eq!(result.axis_result(Axis::Vertical).unwrap().quality, Quality::Good);
+                assert_eq!(result.quality, Quality::Good);
+            }
+            _ => panic!("expected a diagonal result"),
+        }
+    }
+
+    #[test]
+    fn test_diagonal_missing_counter_fails() {
+        let mut movement = MovementState::new();
+        let start = Instant::now();
+
+        movement.on_key_press(StrafeKey::W, at(start, 0));
+        movement.on_key_press(StrafeKey::D, at(start, 0));
+        movement.on_key_release(StrafeKey::W, at(start, 300));
+        movement.on_key_release(StrafeKey::D, at(start, 300));
+        movement.on_key_press(StrafeKey::A, at(start, 305));
+        assert!(movement.on_key_release(StrafeKey::A, at(start, 385)).is_none());
+
+        let timeout_ms = 300 + (TIMEOUT_NO_COUNTER * 1000.0) as u64 + 1;
+        match movement.check_timeout(at(start, timeout_ms)) {
+            Some(StopResult::Diagonal(result)) => {
+                assert!(result.vertical.is_none());
+                assert_eq!(result.quality, Quality::Failed);
+            }
+            _ => panic!("expected a failed diagonal result"),
+        }
+    }
+
+    #[test]
+    fn test_combine_quality() {
+        assert_eq!(combine_quality(Some(Quality::Perfect), Some(Quality::Perfect)), Quality::Perfect);
+        assert_eq!(combine_quality(Some(Quality::Perfect), Some(Quality::Good)), Quality::Good);
+        assert_eq!(combine_quality(Some(Quality::Good), Some(Quality::Failed)), Quality::Failed);
+        assert_eq!(combine_quality(Some(Quality::Perfect), None), Quality::Failed);
+    }
+}
@@ -0,0 +1,383 @@
+use crate::diagonal::MovementState;
+use crate::metronome::{Metronome, BPM_RANGE};
+use crate::profiles::Weapon;
+use crate::state::{Axis, Quality};
+use crate::stats::{Attempt, Stats};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// A structured exercise: a limit on attempts and/or time, and a goal to reach within it.
+/// User-defined drills are `[[drills]]` entries in the config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Drill {
+    pub name: String,
+    /// Ends after this many attempts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+    /// Ends after this many seconds, counted from the first key press
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds: Option<u32>,
+    pub goal: Goal,
+    /// Weapon profile to train with, the active one if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weapon: Option<Weapon>,
+    /// Cue strafes and stops on a metronome at this tempo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bpm: Option<f32>,
+}
+
+/// What it takes to pass a drill
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    /// At least this many perfect attempts; the drill ends as soon as they are reached
+    Perfects(u32),
+    /// Share of perfect attempts, in percent
+    PerfectPercent(f32),
+    /// Share of Good or Perfect attempts, in percent
+    GoodPercent(f32),
+    /// Share of stops on the metronome beat, in percent
+    OnBeatPercent(f32),
+}
+
+impl Goal {
+    pub fn is_met(&self, stats: &Stats) -> bool {
+        match *self {
+            Goal::Perfects(count) => stats.perfect_count >= count,
+            Goal::PerfectPercent(percent) => stats.total_attempts > 0 && stats.perfect_percentage() >= percent,
+            Goal::GoodPercent(percent) => stats.total_attempts > 0 && good_or_better(stats) >= percent,
+            Goal::OnBeatPercent(percent) => !stats.beat_offsets.is_empty() && stats.on_beat_percentage() >= percent,
+        }
+    }
+
+    /// E.g. "25 perfects", "80% Good or better"
+    pub fn describe(&self) -> String {
+        match self {
+            Goal::Perfects(count) => format!("{} perfects", count),
+            Goal::PerfectPercent(percent) => format!("{:.0}% perfect", percent),
+            Goal::GoodPercent(percent) => format!("{:.0}% Good or better", percent),
+            Goal::OnBeatPercent(percent) => format!("{:.0}% on the beat", percent),
+        }
+    }
+
+    /// Where `stats` stand against the goal, e.g. "12/25 perfects"
+    pub fn progress(&self, stats: &Stats) -> String {
+        match self {
+            Goal::Perfects(count) => format!("{}/{} perfects", stats.perfect_count, count),
+            Goal::PerfectPercent(percent) => format!("{:.0}% perfect (goal {:.0}%)", stats.perfect_percentage(), percent),
+            Goal::GoodPercent(percent) => format!("{:.0}% Good or better (goal {:.0}%)", good_or_better(stats), percent),
+            Goal::OnBeatPercent(percent) => format!("{:.0}% on the beat (goal {:.0}%)", stats.on_beat_percentage(), percent),
+        }
+    }
+}
+
+/// Share of Good or Perfect attempts in percent
+fn good_or_better(stats: &Stats) -> f32 {
+    if stats.total_attempts == 0 {
+        0.0
+    } else {
+        (stats.perfect_count + stats.good_count) as f32 / stats.total_attempts as f32 * 100.0
+    }
+}
+
+impl Drill {
+    fn new(name: &str, attempts: Option<u32>, seconds: Option<u32>, goal: Goal) -> Self {
+        Self {
+            name: name.to_string(),
+            attempts,
+            seconds,
+            goal,
+            weapon: None,
+            bpm: None,
+        }
+    }
+
+    /// Drills available without any configuration; the rhythm drill runs at `bpm`
+    pub fn builtin(bpm: f32) -> Vec<Drill> {
+        vec![
+            Drill::new("Warm-up", Some(20), None, Goal::GoodPercent(70.0)),
+            Drill::new("Perfect 25", None, Some(120), Goal::Perfects(25)),
+            Drill::new("Consistency", Some(50), None, Goal::GoodPercent(80.0)),
+            Drill::new("Sprint", None, Some(60), Goal::PerfectPercent(50.0)),
+            Drill {
+                weapon: Some(Weapon::Awp),
+                ..Drill::new("AWP stops", Some(30), None, Goal::PerfectPercent(40.0))
+            },
+            Drill {
+                bpm: Some(bpm),
+                ..Drill::new("Rhythm", Some(30), None, Goal::OnBeatPercent(50.0))
+            },
+        ]
+    }
+
+    /// Limit and goal, e.g. "25 perfects within 120s"
+    pub fn describe(&self) -> String {
+        let limit = match (self.attempts, self.seconds) {
+            (Some(attempts), Some(seconds)) => format!("in {} attempts or {}s", attempts, seconds),
+            (Some(attempts), None) => format!("over {} attempts", attempts),
+            (None, Some(seconds)) => format!("within {}s", seconds),
+            (None, None) => "without a limit".to_string(),
+        };
+        let weapon = self.weapon.map(|weapon| format!(" ({})", weapon.name())).unwrap_or_default();
+        let bpm = self.bpm.map(|bpm| format!(" at {:.0} BPM", bpm)).unwrap_or_default();
+        format!("{} {}{}{}", self.goal.describe(), limit, bpm, weapon)
+    }
+
+    /// Problems with a user-defined drill, for config validation
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let name = if self.name.trim().is_empty() {
+            problems.push("drills: every drill needs a name".to_string());
+            "<unnamed>"
+        } else {
+            &self.name
+        };
+        match (self.attempts, self.seconds) {
+            (None, None) => problems.push(format!("drills.{}: set attempts, seconds or both", name)),
+            (Some(0), _) | (_, Some(0)) => problems.push(format!("drills.{}: attempts and seconds must be positive", name)),
+            _ => {}
+        }
+        match self.goal {
+            Goal::Perfects(0) => problems.push(format!("drills.{}: goal perfects must be positive", name)),
+            Goal::Perfects(count) if self.attempts.is_some_and(|attempts| count > attempts) => {
+                problems.push(format!("drills.{}: {} perfects cannot be reached in {} attempts", name, count, self.attempts.unwrap_or(0)))
+            }
+            Goal::PerfectPercent(percent) | Goal::GoodPercent(percent) | Goal::OnBeatPercent(percent)
+                if !(percent > 0.0 && percent <= 100.0) =>
+            {
+                problems.push(format!("drills.{}: goal percentage must be between 0 and 100", name))
+            }
+            Goal::OnBeatPercent(_) if self.bpm.is_none() => {
+                problems.push(format!("drills.{}: an on_beat_percent goal needs a bpm", name))
+            }
+            _ => {}
+        }
+        if let Some(bpm) = self.bpm
+            && !(BPM_RANGE.0..=BPM_RANGE.1).contains(&bpm)
+        {
+            problems.push(format!("drills.{}: bpm must be between {} and {}", name, BPM_RANGE.0, BPM_RANGE.1));
+        }
+        problems
+    }
+}
+
+/// A drill in progress. Attempts are counted in their own `Stats`, separate from the session.
+#[derive(Debug, Clone)]
+pub struct DrillRun {
+    drill: Drill,
+    stats: Stats,
+    /// First key press; the time limit counts from here
+    started: Option<Instant>,
+    /// Beat clock of a rhythm drill, running from the start of the drill
+    metronome: Option<Metronome>,
+}
+
+impl DrillRun {
+    pub fn new(drill: Drill, now: Instant) -> Self {
+        Self {
+            metronome: drill.bpm.map(|bpm| Metronome::new(bpm, now)),
+            drill,
+            stats: Stats::default(),
+            started: None,
+        }
+    }
+
+    pub fn metronome(&self) -> Option<&Metronome> {
+        self.metronome.as_ref()
+    }
+
+    pub fn drill(&self) -> &Drill {
+        &self.drill
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn record(&mut self, attempt: Attempt) {
+        self.stats.record(attempt);
+    }
+
+    /// Record when the counter key of a stop went down, scored against the metronome.
+    /// Returns the offset from the beat; `None` without a metronome.
+    pub fn record_stop(&mut self, time: Instant) -> Option<f32> {
+        let offset = self.metronome?.stop_offset(time);
+        self.stats.record_beat_offset(offset);
+        Some(offset)
+    }
+
+    pub fn record_diagonal(&mut self, quality: Quality, axes: impl IntoIterator<Item = Attempt>) {
+        self.stats.record_diagonal(quality, axes);
+    }
+
+    /// Time left of a time-limited drill; the full time until the first key press
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        let limit = Duration::from_secs(self.drill.seconds?.into());
+        Some(limit.saturating_sub(self.elapsed(now)))
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        self.started.map_or(Duration::ZERO, |started| now.saturating_duration_since(started))
+    }
+
+    /// One line of progress, e.g. "12/20 attempts · 8/25 perfects · 43s left"
+    pub fn progress(&self, now: Instant) -> String {
+        let mut parts = Vec::new();
+        match self.drill.attempts {
+            Some(attempts) => parts.push(format!("{}/{} attempts", self.stats.total_attempts, attempts)),
+            None => parts.push(format!("{} attempts", self.stats.total_attempts)),
+        }
+        parts.push(self.drill.goal.progress(&self.stats));
+        if let Some(remaining) = self.remaining(now) {
+            parts.push(format!("{:.0}s left", remaining.as_secs_f32().ceil()));
+        }
+        parts.join(" · ")
+    }
+
+    /// Start the clock on the first key press and end the drill once a limit is reached.
+    /// When time runs out during an attempt, that attempt still counts.
+    pub fn update(&mut self, movement: &MovementState, now: Instant) -> Option<DrillSummary> {
+        let in_attempt = Axis::ALL.iter().any(|axis| movement.axis(*axis).is_active());
+        if self.started.is_none() && in_attempt {
+            self.started = Some(now);
+        }
+
+        let attempts_done = self.drill.attempts.is_some_and(|attempts| self.stats.total_attempts >= attempts);
+        let time_up = self.remaining(now) == Some(Duration::ZERO) && self.started.is_some() && !in_attempt;
+        let reached = matches!(self.drill.goal, Goal::Perfects(_)) && self.drill.goal.is_met(&self.stats);
+        (attempts_done || time_up || reached).then(|| DrillSummary {
+            passed: self.drill.goal.is_met(&self.stats),
+            elapsed: self.elapsed(now),
+            drill: self.drill.clone(),
+            stats: self.stats.clone(),
+        })
+    }
+}
+
+/// Result of a finished drill, for the summary screen
+#[derive(Debug, Clone)]
+pub struct DrillSummary {
+    pub drill: Drill,
+    pub passed: bool,
+    pub stats: Stats,
+    /// From the first key press to the end
+    pub elapsed: Duration,
+}
+
+impl DrillSummary {
+    /// E.g. "PASSED - 25/25 perfects"
+    pub fn headline(&self) -> String {
+        let verdict = if self.passed { "PASSED" } else { "FAILED" };
+        format!("{} - {}", verdict, self.drill.goal.progress(&self.stats))
+    }
+}
+
+/// Drill called `name`, ignoring case
+pub fn find<'a>(drills: &'a [Drill], name: &str) -> Option<&'a Drill> {
+    drills.iter().find(|drill| drill.name.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::StrafeKey;
+
+    fn attempt(quality: Quality) -> Attempt {
+        Attempt {
+            key: StrafeKey::A,
+            quality,
+            hold_time: 0.080,
+            transition: None,
+        }
+    }
+
+    #[test]
+    fn test_attempt_limit_and_goal() {
+        let drill = Drill::new("Test", Some(4), None, Goal::GoodPercent(75.0));
+        let mut run = DrillRun::new(drill, Instant::now());
+        let movement = MovementState::new();
+        let now = Instant::now();
+
+        for quality in [Quality::Perfect, Quality::Good, Quality::Failed] {
+            run.record(attempt(quality));
+            assert!(run.update(&movement, now).is_none());
+        }
+        assert_eq!(run.progress(now), "3/4 attempts · 67% Good or better (goal 75%)");
+
+        run.record(attempt(Quality::Good));
+        let summary = run.update(&movement, now).unwrap();
+        assert!(summary.passed);
+        assert_eq!(summary.headline(), "PASSED - 75% Good or better (goal 75%)");
+    }
+
+    #[test]
+    fn test_time_limit_starts_with_first_press() {
+        let drill = Drill::new("Test", None, Some(10), Goal::Perfects(3));
+        let mut run = DrillRun::new(drill, Instant::now());
+        let mut movement = MovementState::new();
+        let start = Instant::now();
+
+        // The clock does not run before the first key press
+        assert!(run.update(&movement, start + Duration::from_secs(60)).is_none());
+        assert_eq!(run.remaining(start), Some(Duration::from_secs(10)));
+
+        movement.on_key_press(StrafeKey::A, start);
+        assert!(run.update(&movement, start).is_none());
+        run.record(attempt(Quality::Perfect));
+
+        // Time is up, but the attempt in progress still counts
+        let late = start + Duration::from_secs(11);
+        assert!(run.update(&movement, late).is_none());
+        movement.on_key_release(StrafeKey::A, late);
+        movement.check_timeout(late + Duration::from_secs(1));
+
+        let summary = run.update(&movement, late + Duration::from_secs(1)).unwrap();
+        assert!(!summary.passed);
+        assert_eq!(summary.headline(), "FAILED - 1/3 perfects");
+    }
+
+    #[test]
+    fn test_perfects_goal_ends_early() {
+        let mut run = DrillRun::new(Drill::new("Test", None, Some(60), Goal::Perfects(2)), Instant::now());
+        run.record(attempt(Quality::Perfect));
+        run.record(attempt(Quality::Perfect));
+        assert!(run.update(&MovementState::new(), Instant::now()).unwrap().passed);
+    }
+
+    #[test]
+    fn test_rhythm_drill_scores_beat_offsets() {
+        let start = Instant::now();
+        let mut run = DrillRun::new(Drill::new("Test", Some(2), None, Goal::OnBeatPercent(50.0)), start);
+        assert_eq!(run.record_stop(start), None);
+
+        let drill = Drill {
+            bpm: Some(120.0),
+            ..run.drill().clone()
+        };
+        let mut run = DrillRun::new(drill, start);
+        let offset = run.record_stop(start + Duration::from_millis(510)).unwrap();
+        assert!((offset - 0.010).abs() < 1e-3);
+        run.record_stop(start + Duration::from_millis(1400));
+        assert!(run.drill().goal.is_met(run.stats()));
+        assert_eq!(run.progress(start), "0/2 attempts · 50% on the beat (goal 50%)");
+    }
+
+    #[test]
+    fn test_validation_and_config_format() {
+        let drill: Drill = toml::from_str("name = \"Mine\"\nattempts = 10\ngoal = { perfects = 12 }\nweapon = \"awp\"\n").unwrap();
+        assert_eq!(drill.weapon, Some(Weapon::Awp));
+        assert_eq!(drill.describe(), "12 perfects over 10 attempts (AWP)");
+        assert_eq!(drill.validate(), vec!["drills.Mine: 12 perfects cannot be reached in 10 attempts"]);
+
+        let drill = Drill::new("", None, None, Goal::GoodPercent(120.0));
+        assert_eq!(drill.validate().len(), 3);
+        assert!(Drill::builtin(90.0).iter().all(|drill| drill.validate().is_empty()));
+        assert!(find(&Drill::builtin(90.0), "warm-up").is_some());
+
+        let drill = Drill::new("Beat", Some(10), None, Goal::OnBeatPercent(50.0));
+        assert_eq!(drill.validate(), vec!["drills.Beat: an on_beat_percent goal needs a bpm"]);
+        let drill = Drill { bpm: Some(400.0), ..drill };
+        assert_eq!(drill.validate(), vec!["drills.Beat: bpm must be between 30 and 240"]);
+    }
+}
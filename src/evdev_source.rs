@@ -0,0 +1,406 @@
+use crate::diagnostics;
+use crate::input::{InputSink, InputSource, RawInput};
+use evdev::{Device, EventType, KeyCode};
+use rdev::{Button, Key};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const INPUT_DIR: &str = "/dev/input";
+
+// Evdev key values
+const RELEASED: i32 = 0;
+const PRESSED: i32 = 1;
+/// _IOW('E', 0xa0, int): select the clock of the device's event timestamps
+const EVIOCSCLOCKID: u32 = 0x4004_45a0;
+
+/// What a readable device can be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    Keyboard,
+    /// Has a left button, used for firing
+    Mouse,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceStatus {
+    Readable(DeviceKind),
+    /// Why the device could not be opened
+    Unreadable(String),
+}
+
+/// An event device under /dev/input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputDevice {
+    pub path: PathBuf,
+    /// Name reported by the kernel, also available for unreadable devices
+    pub name: String,
+    pub status: DeviceStatus,
+}
+
+impl InputDevice {
+    fn probe(path: PathBuf) -> Self {
+        match Device::open(&path) {
+            Ok(device) => Self {
+                name: device.name().unwrap_or_default().to_string(),
+                status: DeviceStatus::Readable(device_kind(&device)),
+                path,
+            },
+            Err(e) => Self {
+                name: sysfs_name(&path).unwrap_or_default(),
+                status: DeviceStatus::Unreadable(open_error(&path, &e)),
+                path,
+            },
+        }
+    }
+
+    pub fn kind(&self) -> Option<DeviceKind> {
+        match self.status {
+            DeviceStatus::Readable(kind) => Some(kind),
+            DeviceStatus::Unreadable(_) => None,
+        }
+    }
+
+    /// One line for device lists and diagnostics
+    pub fn describe(&self) -> String {
+        let status = match &self.status {
+            DeviceStatus::Readable(DeviceKind::Keyboard) => "keyboard".to_string(),
+            DeviceStatus::Readable(DeviceKind::Mouse) => "mouse".to_string(),
+            DeviceStatus::Readable(DeviceKind::Other) => "not a keyboard or mouse".to_string(),
+            DeviceStatus::Unreadable(reason) => format!("unreadable: {}", reason),
+        };
+        format!("{} \"{}\" - {}", self.path.display(), self.name, status)
+    }
+}
+
+/// All event devices, in device number order
+pub fn scan_devices() -> Vec<InputDevice> {
+    let Ok(entries) = std::fs::read_dir(INPUT_DIR) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| event_number(path).is_some())
+        .collect();
+    paths.sort_by_key(|path| event_number(path));
+    paths.into_iter().map(InputDevice::probe).collect()
+}
+
+fn event_number(path: &Path) -> Option<u32> {
+    path.file_name()?.to_str()?.strip_prefix("event")?.parse().ok()
+}
+
+fn device_kind(device: &Device) -> DeviceKind {
+    let Some(keys) = device.supported_keys() else {
+        return DeviceKind::Other;
+    };
+    // Power buttons and media remotes report some keys too, require the strafe keys
+    if [KeyCode::KEY_A, KeyCode::KEY_D, KeyCode::KEY_W, KeyCode::KEY_S].iter().all(|key| keys.contains(*key)) {
+        DeviceKind::Keyboard
+    } else if keys.contains(KeyCode::BTN_LEFT) {
+        DeviceKind::Mouse
+    } else {
+        DeviceKind::Other
+    }
+}
+
+/// Whether the device reports the strafe keys, from sysfs so it also works for unreadable nodes
+pub fn has_keyboard_keys(path: &Path) -> bool {
+    let Some(node) = path.file_name().and_then(|node| node.to_str()) else {
+        return false;
+    };
+    std::fs::read_to_string(format!("/sys/class/input/{}/device/capabilities/key", node))
+        .is_ok_and(|bitmap| [KeyCode::KEY_A, KeyCode::KEY_D, KeyCode::KEY_W, KeyCode::KEY_S].iter().all(|key| has_key(&bitmap, *key)))
+}
+
+/// Whether `key` is set in a sysfs key bitmap: hex words of a `long` each, most significant first
+fn has_key(bitmap: &str, key: KeyCode) -> bool {
+    let bits = usize::BITS as usize;
+    let code = key.code() as usize;
+    bitmap
+        .split_whitespace()
+        .rev()
+        .nth(code / bits)
+        .and_then(|word| u64::from_str_radix(word, 16).ok())
+        .is_some_and(|word| word & (1 << (code % bits)) != 0)
+}
+
+/// Device name from sysfs, which is world readable unlike the device node
+fn sysfs_name(path: &Path) -> Option<String> {
+    let node = path.file_name()?.to_str()?;
+    let name = std::fs::read_to_string(format!("/sys/class/input/{}/device/name", node)).ok()?;
+    Some(name.trim().to_string())
+}
+
+/// Explain a failed open, with the node's owner group and mode for permission errors
+fn open_error(path: &Path, error: &std::io::Error) -> String {
+    if error.kind() != std::io::ErrorKind::PermissionDenied {
+        return error.to_string();
+    }
+    match std::fs::metadata(path) {
+        Ok(metadata) => format!(
+            "permission denied (mode {:o}, group {})",
+            metadata.permissions().mode() & 0o777,
+            diagnostics::group_name(&std::fs::read_to_string("/etc/group").unwrap_or_default(), metadata.gid())
+                .map_or_else(|| metadata.gid().to_string(), str::to_string)
+        ),
+        Err(_) => "permission denied".to_string(),
+    }
+}
+
+/// Reads /dev/input event devices directly: works without X11 (Wayland, TTY) and
+/// uses the kernel's event timestamps instead of the time the event reached us
+pub struct EvdevSource {
+    devices: Vec<PathBuf>,
+}
+
+impl EvdevSource {
+    /// All readable keyboards and mice. Fails with a device list if there is no keyboard.
+    pub fn auto() -> Result<Self, Box<dyn std::error::Error>> {
+        let devices = scan_devices();
+        if !devices.iter().any(|device| device.kind() == Some(DeviceKind::Keyboard)) {
+            return Err(no_keyboard_error(&devices).into());
+        }
+        Ok(Self {
+            devices: devices
+                .into_iter()
+                .filter(|device| matches!(device.kind(), Some(DeviceKind::Keyboard | DeviceKind::Mouse)))
+                .map(|device| device.path)
+                .collect(),
+        })
+    }
+
+    /// A single device picked by the user
+    pub fn device(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let device = InputDevice::probe(path.to_path_buf());
+        match device.status {
+            DeviceStatus::Readable(_) => Ok(Self {
+                devices: vec![device.path],
+            }),
+            DeviceStatus::Unreadable(_) => Err(format!("Cannot read input device {}", device.describe()).into()),
+        }
+    }
+}
+
+fn no_keyboard_error(devices: &[InputDevice]) -> String {
+    if devices.is_empty() {
+        return format!("No input devices found in {}", INPUT_DIR);
+    }
+    let lines: Vec<String> = devices.iter().map(InputDevice::describe).collect();
+    format!("No readable keyboard in {}:\n  {}", INPUT_DIR, lines.join("\n  "))
+}
+
+impl InputSource for EvdevSource {
+    fn run(self: Box<Self>, sink: InputSink) -> Result<(), Box<dyn std::error::Error>> {
+        let mut readers = Vec::new();
+        for path in self.devices {
+            let mut device = match Device::open(&path) {
+                Ok(device) => device,
+                Err(e) => {
+                    log::warn!("Skipping {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            log::info!("Reading {} \"{}\"", path.display(), device.name().unwrap_or_default());
+            let clock = EventClock::for_device(&device);
+
+            // One blocking reader per device; repeat filtering is per device
+            let mut sink = sink.clone();
+            readers.push(thread::spawn(move || {
+                loop {
+                    let events = match device.fetch_events() {
+                        Ok(events) => events,
+                        Err(e) => {
+                            log::error!("Stopped reading {}: {}", path.display(), e);
+                            return;
+                        }
+                    };
+                    for event in events.filter(|event| event.event_type() == EventType::KEY) {
+                        let Some(input) = raw_input(event.code(), event.value()) else {
+                            continue;
+                        };
+                        if !sink.send(input, clock.instant_at(event.timestamp())) {
+                            return;
+                        }
+                    }
+                }
+            }));
+        }
+
+        if readers.is_empty() {
+            return Err("No input device could be opened".into());
+        }
+        for reader in readers {
+            let _ = reader.join();
+        }
+        Ok(())
+    }
+}
+
+/// Press or release of an evdev key code; auto-repeat (value 2) is dropped
+fn raw_input(code: u16, value: i32) -> Option<RawInput> {
+    if code == KeyCode::BTN_LEFT.code() {
+        return match value {
+            PRESSED => Some(RawInput::ButtonPress(Button::Left)),
+            RELEASED => Some(RawInput::ButtonRelease(Button::Left)),
+            _ => None,
+        };
+    }
+    let key = key_from_code(code);
+    match value {
+        PRESSED => Some(RawInput::KeyPress(key)),
+        RELEASED => Some(RawInput::KeyRelease(key)),
+        _ => None,
+    }
+}
+
+/// Converts kernel event timestamps to `Instant`s, so hold times use the kernel's timing
+/// rather than when the reader thread got to run
+struct EventClock {
+    /// Reading of the device's clock, taken together with `instant`
+    reading: Duration,
+    instant: Instant,
+}
+
+impl EventClock {
+    /// Switch `device` to CLOCK_MONOTONIC, the clock `Instant` uses, so the offset taken here
+    /// stays exact. Kernels that refuse keep wall clock timestamps, converted with the same offset.
+    fn for_device(device: &Device) -> Self {
+        let mut clock = libc::CLOCK_MONOTONIC;
+        // SAFETY: the descriptor is open for the lifetime of `device`, the argument is a valid int
+        if unsafe { libc::ioctl(device.as_raw_fd(), EVIOCSCLOCKID as _, &clock as *const libc::clockid_t) } < 0 {
+            log::debug!("Keeping wall clock event timestamps: {}", std::io::Error::last_os_error());
+            clock = libc::CLOCK_REALTIME;
+        }
+        Self {
+            reading: clock_reading(clock),
+            instant: Instant::now(),
+        }
+    }
+
+    /// `Instant` of an event timestamp, which evdev reports as time since the epoch of the clock
+    fn instant_at(&self, timestamp: SystemTime) -> Instant {
+        let reading = timestamp.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        match reading.checked_sub(self.reading) {
+            Some(after) => self.instant + after,
+            None => self.instant.checked_sub(self.reading - reading).unwrap_or(self.instant),
+        }
+    }
+}
+
+fn clock_reading(clock: libc::clockid_t) -> Duration {
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: `time` is a valid timespec to write to
+    unsafe { libc::clock_gettime(clock, &mut time) };
+    Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+/// The rdev key for an evdev key code, so bindings work with both backends.
+/// Codes rdev has no name for become `Key::Unknown` with the X11 keycode (evdev + 8), as rdev reports them.
+fn key_from_code(code: u16) -> Key {
+    #[rustfmt::skip]
+    let key = match code {
+        1 => Key::Escape, 2 => Key::Num1, 3 => Key::Num2, 4 => Key::Num3, 5 => Key::Num4,
+        6 => Key::Num5, 7 => Key::Num6, 8 => Key::Num7, 9 => Key::Num8, 10 => Key::Num9,
+        11 => Key::Num0, 12 => Key::Minus, 13 => Key::Equal, 14 => Key::Backspace, 15 => Key::Tab,
+        16 => Key::KeyQ, 17 => Key::KeyW, 18 => Key::KeyE, 19 => Key::KeyR, 20 => Key::KeyT,
+        21 => Key::KeyY, 22 => Key::KeyU, 23 => Key::KeyI, 24 => Key::KeyO, 25 => Key::KeyP,
+        26 => Key::LeftBracket, 27 => Key::RightBracket, 28 => Key::Return, 29 => Key::ControlLeft,
+        30 => Key::KeyA, 31 => Key::KeyS, 32 => Key::KeyD, 33 => Key::KeyF, 34 => Key::KeyG,
+        35 => Key::KeyH, 36 => Key::KeyJ, 37 => Key::KeyK, 38 => Key::KeyL, 39 => Key::SemiColon,
+        40 => Key::Quote, 41 => Key::BackQuote, 42 => Key::ShiftLeft, 43 => Key::BackSlash,
+        44 => Key::KeyZ, 45 => Key::KeyX, 46 => Key::KeyC, 47 => Key::KeyV, 48 => Key::KeyB,
+        49 => Key::KeyN, 50 => Key::KeyM, 51 => Key::Comma, 52 => Key::Dot, 53 => Key::Slash,
+        54 => Key::ShiftRight, 55 => Key::KpMultiply, 56 => Key::Alt, 57 => Key::Space,
+        58 => Key::CapsLock, 59 => Key::F1, 60 => Key::F2, 61 => Key::F3, 62 => Key::F4,
+        63 => Key::F5, 64 => Key::F6, 65 => Key::F7, 66 => Key::F8, 67 => Key::F9, 68 => Key::F10,
+        69 => Key::NumLock, 70 => Key::ScrollLock, 71 => Key::Kp7, 72 => Key::Kp8, 73 => Key::Kp9,
+        74 => Key::KpMinus, 75 => Key::Kp4, 76 => Key::Kp5, 77 => Key::Kp6, 78 => Key::KpPlus,
+        79 => Key::Kp1, 80 => Key::Kp2, 81 => Key::Kp3, 82 => Key::Kp0, 83 => Key::KpDelete,
+        86 => Key::IntlBackslash, 87 => Key::F11, 88 => Key::F12, 96 => Key::KpReturn,
+        97 => Key::ControlRight, 98 => Key::KpDivide, 99 => Key::PrintScreen, 100 => Key::AltGr,
+        102 => Key::Home, 103 => Key::UpArrow, 104 => Key::PageUp, 105 => Key::LeftArrow,
+        106 => Key::RightArrow, 107 => Key::End, 108 => Key::DownArrow, 109 => Key::PageDown,
+        110 => Key::Insert, 111 => Key::Delete, 119 => Key::Pause, 125 => Key::MetaLeft,
+        code => Key::Unknown(code as u32 + 8),
+    };
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_codes_match_rdev() {
+        assert_eq!(key_from_code(KeyCode::KEY_A.code()), Key::KeyA);
+        assert_eq!(key_from_code(KeyCode::KEY_D.code()), Key::KeyD);
+        assert_eq!(key_from_code(KeyCode::KEY_ESC.code()), Key::Escape);
+        assert_eq!(key_from_code(KeyCode::KEY_LEFT.code()), Key::LeftArrow);
+        // Unnamed keys use the X11 keycode like rdev
+        assert_eq!(key_from_code(KeyCode::KEY_F13.code()), Key::Unknown(191));
+    }
+
+    #[test]
+    fn test_raw_input_drops_repeats() {
+        let a = KeyCode::KEY_A.code();
+        assert_eq!(raw_input(a, 1), Some(RawInput::KeyPress(Key::KeyA)));
+        assert_eq!(raw_input(a, 0), Some(RawInput::KeyRelease(Key::KeyA)));
+        assert_eq!(raw_input(a, 2), None);
+        assert_eq!(raw_input(KeyCode::BTN_LEFT.code(), 1), Some(RawInput::ButtonPress(Button::Left)));
+    }
+
+    #[test]
+    fn test_sysfs_key_bitmap() {
+        // Words of a typical keyboard; the last one holds codes 0-63
+        let keyboard = "1000000000007 ff800000000007ff febeffdfffefffff fffffffffffffffe";
+        let power_button = "10000000000000 0";
+        if usize::BITS == 64 {
+            assert!(has_key(keyboard, KeyCode::KEY_A) && has_key(keyboard, KeyCode::KEY_W));
+            assert!(!has_key(keyboard, KeyCode::KEY_RESERVED));
+            assert!(!has_key(power_button, KeyCode::KEY_A));
+            assert!(has_key(power_button, KeyCode::KEY_POWER));
+        }
+    }
+
+    #[test]
+    fn test_kernel_timestamp_becomes_instant() {
+        let clock = EventClock {
+            reading: clock_reading(libc::CLOCK_MONOTONIC),
+            instant: Instant::now(),
+        };
+        let age = Duration::from_millis(40);
+        let timestamp = SystemTime::UNIX_EPOCH + clock_reading(libc::CLOCK_MONOTONIC) - age;
+        let measured = Instant::now() - clock.instant_at(timestamp);
+        assert!(measured >= age && measured < age + Duration::from_millis(20));
+
+        // Events before the offset was taken
+        let earlier = SystemTime::UNIX_EPOCH + clock.reading - Duration::from_millis(5);
+        assert_eq!(clock.instant - clock.instant_at(earlier), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_no_keyboard_error_lists_devices() {
+        let devices = [
+            InputDevice {
+                path: PathBuf::from("/dev/input/event3"),
+                name: "AT Translated Set 2 keyboard".to_string(),
+                status: DeviceStatus::Unreadable("permission denied (mode 660, group input)".to_string()),
+            },
+            InputDevice {
+                path: PathBuf::from("/dev/input/event0"),
+                name: "Power Button".to_string(),
+                status: DeviceStatus::Readable(DeviceKind::Other),
+            },
+        ];
+        let error = no_keyboard_error(&devices);
+        assert!(error.contains(
+            "/dev/input/event3 \"AT Translated Set 2 keyboard\" - unreadable: permission denied (mode 660, group input)"
+        ));
+        assert!(error.contains("event0 \"Power Button\" - not a keyboard or mouse"));
+        assert_eq!(no_keyboard_error(&[]), "No input devices found in /dev/input");
+    }
+}
@@ -1,111 +1,384 @@
-use rdev::{Event, EventType, Key};
-use std::sync::mpsc::{channel, Receiver, Sender};
+use crate::input::{self, Backend, InputSink, InputSource};
+use crate::keymap::{Action, KeyMap};
+use crate::profiles::Weapon;
+use rdev::Key;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, RwLock};
 use std::thread;
+use std::time::{Duration, Instant};
+
+/// A running backend that has not delivered an event for this long is reported as silent,
+/// unless configured otherwise
+pub const NO_INPUT_AFTER: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum GameEvent {
-    KeyAPress,
-    KeyARelease,
-    KeyDPress,
-    KeyDRelease,
-    SpacePress,
-    EscapePress,
+pub enum GameEventKind {
+    Press(Action),
+    Release(Action),
+    /// Next key press after `EventListener::capture_next_key`, delivered instead of its action
+    KeyCaptured(Key),
+    /// Setting change from a recording, applied in order with the input around it
+    Control(Control),
 }
 
-pub struct EventListener {
-    receiver: Receiver<GameEvent>,
+/// Trainer setting changed while a recording was made
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    SelectWeapon(Weapon),
+    /// Tolerated key overlap
+    SetOverlapWindow(Duration),
 }
 
-impl EventListener {
-    /// Start listening for keyboard events in a background thread
-    pub fn start() -> Result<Self, Box<dyn std::error::Error>> {
-        let (tx, rx) = channel();
+/// A keyboard event stamped with the instant it was captured by the listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameEvent {
+    pub kind: GameEventKind,
+    pub time: Instant,
+}
 
-        // Spawn background thread for rdev event listening
-        thread::spawn(move || {
-            if let Err(e) = listen_events(tx) {
-                eprintln!("Error in event listener: {}", e);
-            }
-        });
+/// Health of the input backend, reported by the listener thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenerStatus {
+    Starting,
+    Running,
+    /// Running, but no event arrived for this long
+    NoInput(Duration),
+    /// The backend may not read the keyboard
+    PermissionDenied(String),
+    Failed(String),
+    /// The source ended, e.g. a replay finished
+    Stopped,
+}
+
+impl ListenerStatus {
+    /// Failures and silence need the user's attention; the others are normal operation
+    pub fn is_problem(&self) -> bool {
+        matches!(self, Self::NoInput(_) | Self::PermissionDenied(_) | Self::Failed(_))
+    }
 
-        Ok(Self { receiver: rx })
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Starting => "Starting input",
+            Self::Running => "Input running",
+            Self::NoInput(_) => "No input received",
+            Self::PermissionDenied(_) => "No permission to read the keyboard",
+            Self::Failed(_) => "Input backend failed",
+            Self::Stopped => "Input ended",
+        }
     }
 
-    /// Try to receive next event (non-blocking)
-    pub fn try_recv(&self) -> Option<GameEvent> {
-        self.receiver.try_recv().ok()
+    /// Error or explanation to show under the title
+    pub fn detail(&self) -> String {
+        match self {
+            Self::NoInput(silent) => format!(
+                "The input backend is running but has not seen a key press or click in {:.0} seconds.",
+                silent.as_secs_f32()
+            ),
+            Self::PermissionDenied(error) | Self::Failed(error) => error.clone(),
+            _ => String::new(),
+        }
     }
 
-    /// Get all pending events (non-blocking)
-    pub fn drain_events(&self) -> Vec<GameEvent> {
-        let mut events = Vec::new();
-        while let Ok(event) = self.receiver.try_recv() {
-            events.push(event);
+    /// Steps the user can take, most likely fix first
+    pub fn remediation(&self) -> Vec<&'static str> {
+        let mut steps = Vec::new();
+        if cfg!(target_os = "linux") {
+            match self {
+                Self::PermissionDenied(_) => steps.extend([
+                    "Add yourself to the input group: sudo usermod -a -G input $USER, then log out and back in",
+                    "Or run the trainer with sudo",
+                    "Run `cs2-counter-strafe-trainer doctor` (or open Diagnostics) to see what blocks access",
+                ]),
+                Self::Failed(_) | Self::NoInput(_) => steps.extend([
+                    "Under Wayland the rdev backend sees no global input: use --input evdev",
+                    "Run `cs2-counter-strafe-trainer devices` to check that your keyboard is detected and readable",
+                    "Pick the keyboard explicitly with --device /dev/input/eventN",
+                ]),
+                _ => {}
+            }
+        } else if cfg!(target_os = "macos") && self.is_problem() {
+            steps.push("Allow the trainer under System Settings > Privacy & Security > Input Monitoring");
         }
-        events
+        if self.is_problem() {
+            steps.push("Check the log with --log-level debug for details");
+        }
+        steps
     }
 }
 
-/// Background event listening function
-fn listen_events(tx: Sender<GameEvent>) -> Result<(), Box<dyn std::error::Error>> {
-    // Track key states to filter key repeats
-    let mut a_pressed = false;
-    let mut d_pressed = false;
-
-    rdev::listen(move |event: Event| {
-        let game_event = match event.event_type {
-            EventType::KeyPress(Key::KeyA) | EventType::KeyPress(Key::Alt) => {
-                if !a_pressed {
-                    a_pressed = true;
-                    Some(GameEvent::KeyAPress)
-                } else {
-                    None // Filter key repeat
+/// What the listener thread sends: input, or a change of the backend's health
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenerMessage {
+    Event(GameEvent),
+    Status(ListenerStatus),
+}
+
+pub struct EventListener {
+    receiver: Receiver<ListenerMessage>,
+    keymap: Arc<RwLock<KeyMap>>,
+    capture_next: Arc<AtomicBool>,
+    ui_has_pointer: Arc<AtomicBool>,
+    status: ListenerStatus,
+    /// Start of the listener, then the arrival of the latest event
+    last_input: Instant,
+    /// Silence after which the backend is reported, `None` = never
+    no_input_after: Option<Duration>,
+    /// The source is a global hook still owned by the listener thread until it ends
+    global_hook: bool,
+}
+
+impl EventListener {
+    /// Open `backend` and listen in a background thread. A backend that cannot be
+    /// opened yields a listener reporting the error instead of failing.
+    pub fn open(backend: Backend, device: Option<&Path>, keymap: KeyMap) -> Self {
+        match input::open_source(backend, device) {
+            Ok(source) => Self::with_source(source, keymap),
+            Err(e) => {
+                let listener = Self::with_status(ListenerStatus::Starting, keymap);
+                Self {
+                    status: failure_status(e.as_ref()),
+                    ..listener
                 }
             }
-            EventType::KeyRelease(Key::KeyA) | EventType::KeyRelease(Key::Alt) => {
-                if a_pressed {
-                    a_pressed = false;
-                    Some(GameEvent::KeyARelease)
-                } else {
-                    None
+        }
+    }
+
+    /// Run `source` in a background thread and deliver its input through the key bindings
+    pub fn with_source(source: Box<dyn InputSource>, keymap: KeyMap) -> Self {
+        let (tx, rx) = channel();
+        let mut listener = Self::with_status(ListenerStatus::Starting, keymap);
+        listener.receiver = rx;
+        listener.global_hook = source.is_global_hook();
+
+        let sink = InputSink::new(
+            tx.clone(),
+            Arc::clone(&listener.keymap),
+            Arc::clone(&listener.capture_next),
+            Arc::clone(&listener.ui_has_pointer),
+        );
+        thread::spawn(move || {
+            let _ = tx.send(ListenerMessage::Status(ListenerStatus::Running));
+            let status = match source.run(sink) {
+                Ok(()) => ListenerStatus::Stopped,
+                Err(e) => {
+                    log::error!("Error in event listener: {}", e);
+                    failure_status(e.as_ref())
                 }
-            }
-            EventType::KeyPress(Key::KeyD) => {
-                if !d_pressed {
-                    d_pressed = true;
-                    Some(GameEvent::KeyDPress)
-                } else {
-                    None // Filter key repeat
+            };
+            let _ = tx.send(ListenerMessage::Status(status));
+        });
+
+        listener
+    }
+
+    /// Listener without a thread
+    fn with_status(status: ListenerStatus, keymap: KeyMap) -> Self {
+        Self {
+            receiver: channel().1,
+            keymap: Arc::new(RwLock::new(keymap)),
+            capture_next: Arc::new(AtomicBool::new(false)),
+            ui_has_pointer: Arc::new(AtomicBool::new(false)),
+            status,
+            last_input: Instant::now(),
+            no_input_after: Some(NO_INPUT_AFTER),
+            global_hook: false,
+        }
+    }
+
+    /// Get all pending events (non-blocking) and pick up status reports
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                ListenerMessage::Event(event) => {
+                    self.last_input = Instant::now();
+                    events.push(event);
                 }
-            }
-            EventType::KeyRelease(Key::KeyD) => {
-                if d_pressed {
-                    d_pressed = false;
-                    Some(GameEvent::KeyDRelease)
-                } else {
-                    None
+                ListenerMessage::Status(status) => {
+                    log::info!("Input listener: {:?}", status);
+                    self.status = status;
                 }
             }
-            EventType::KeyPress(Key::Space) => Some(GameEvent::SpacePress),
-            EventType::KeyPress(Key::Escape) => Some(GameEvent::EscapePress),
-            _ => None,
-        };
+        }
+        events
+    }
 
-        if let Some(evt) = game_event {
-            // Send event through channel (non-blocking)
-            let _ = tx.send(evt);
+    /// Health of the backend as of `now`
+    pub fn status(&self, now: Instant) -> ListenerStatus {
+        let silent = now.saturating_duration_since(self.last_input);
+        if self.status == ListenerStatus::Running && self.no_input_after.is_some_and(|after| silent >= after) {
+            ListenerStatus::NoInput(silent)
+        } else {
+            self.status.clone()
         }
-    })
-    .map_err(|e| format!("Event listening error: {:?}", e).into())
+    }
+
+    /// Silence after which a running backend is reported as `NoInput`, `None` = never
+    pub fn set_no_input_after(&mut self, no_input_after: Option<Duration>) {
+        self.no_input_after = no_input_after;
+    }
+
+    /// Whether another listener may be opened: a global hook only once its thread has ended
+    pub fn can_reopen(&self) -> bool {
+        !self.global_hook
+            || matches!(
+                self.status,
+                ListenerStatus::Failed(_) | ListenerStatus::PermissionDenied(_) | ListenerStatus::Stopped
+            )
+    }
+
+    /// Current key bindings
+    pub fn keymap(&self) -> KeyMap {
+        self.keymap.read().expect("keymap lock poisoned").clone()
+    }
+
+    /// Replace the key bindings used by the listener thread
+    pub fn set_keymap(&self, keymap: KeyMap) {
+        *self.keymap.write().expect("keymap lock poisoned") = keymap;
+    }
+
+    /// Deliver the next key press as `GameEventKind::KeyCaptured` instead of its action
+    pub fn capture_next_key(&self) {
+        self.capture_next.store(true, Ordering::SeqCst);
+    }
+
+    /// Stop waiting for a key to capture
+    pub fn cancel_capture(&self) {
+        self.capture_next.store(false, Ordering::SeqCst);
+    }
+
+    /// Drop mouse buttons while the trainer's window is focused or hovered
+    pub fn set_ui_has_pointer(&self, has_pointer: bool) {
+        self.ui_has_pointer.store(has_pointer, Ordering::SeqCst);
+    }
+}
+
+/// Classify a backend error; permission problems get their own remediation
+fn failure_status(error: &(dyn std::error::Error + 'static)) -> ListenerStatus {
+    let message = error.to_string();
+    let permission = error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied)
+        || message.to_lowercase().contains("permission denied");
+    if permission {
+        ListenerStatus::PermissionDenied(message)
+    } else {
+        ListenerStatus::Failed(message)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::ScriptedSource;
+
+    struct FailingSource(std::io::ErrorKind);
+
+    impl InputSource for FailingSource {
+        fn run(self: Box<Self>, _sink: InputSink) -> Result<(), Box<dyn std::error::Error>> {
+            Err(std::io::Error::new(self.0, "cannot open /dev/input/event3").into())
+        }
+    }
+
+    /// Global hook that runs until the test lets it end
+    struct HookSource(Receiver<()>);
+
+    impl InputSource for HookSource {
+        fn run(self: Box<Self>, _sink: InputSink) -> Result<(), Box<dyn std::error::Error>> {
+            let _ = self.0.recv();
+            Ok(())
+        }
+
+        fn is_global_hook(&self) -> bool {
+            true
+        }
+    }
+
+    /// Drain until the listener reports something other than starting/running
+    fn wait_for_end(listener: &mut EventListener) -> ListenerStatus {
+        let start = Instant::now();
+        loop {
+            listener.drain_events();
+            let status = listener.status(Instant::now());
+            if !matches!(status, ListenerStatus::Starting | ListenerStatus::Running) {
+                return status;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5), "listener never finished");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_backend_errors_are_reported() {
+        let mut listener =
+            EventListener::with_source(Box::new(FailingSource(std::io::ErrorKind::PermissionDenied)), KeyMap::default());
+        let status = wait_for_end(&mut listener);
+        assert!(matches!(status, ListenerStatus::PermissionDenied(ref e) if e.contains("event3")));
+        assert!(status.is_problem());
+        assert!(!status.remediation().is_empty());
+
+        let mut listener = EventListener::with_source(Box::new(FailingSource(std::io::ErrorKind::Other)), KeyMap::default());
+        assert!(matches!(wait_for_end(&mut listener), ListenerStatus::Failed(_)));
+    }
+
+    #[test]
+    fn test_running_global_hook_cannot_be_reopened() {
+        let (stop, stopped) = channel();
+        let mut listener = EventListener::with_source(Box::new(HookSource(stopped)), KeyMap::default());
+        listener.drain_events();
+        assert!(!listener.can_reopen());
+
+        drop(stop);
+        assert_eq!(wait_for_end(&mut listener), ListenerStatus::Stopped);
+        assert!(listener.can_reopen());
+
+        let listener = EventListener::with_source(Box::new(ScriptedSource::new()), KeyMap::default());
+        assert!(listener.can_reopen());
+    }
+
+    #[test]
+    fn test_finished_source_is_stopped() {
+        let mut listener = EventListener::with_source(Box::new(ScriptedSource::new()), KeyMap::default());
+        assert_eq!(wait_for_end(&mut listener), ListenerStatus::Stopped);
+    }
+
+    #[test]
+    fn test_silent_backend() {
+        let mut listener = EventListener::with_status(ListenerStatus::Running, KeyMap::default());
+        let start = listener.last_input;
+        assert_eq!(listener.status(start), ListenerStatus::Running);
+        assert_eq!(listener.status(start + NO_INPUT_AFTER), ListenerStatus::NoInput(NO_INPUT_AFTER));
+
+        // Silence is measured from the latest event, not from the start
+        let (tx, rx) = channel();
+        listener.receiver = rx;
+        let event = GameEvent {
+            kind: GameEventKind::Press(Action::StrafeLeft),
+            time: Instant::now(),
+        };
+        tx.send(ListenerMessage::Event(event)).unwrap();
+        listener.drain_events();
+        let last_input = listener.last_input;
+        assert_eq!(listener.status(last_input + NO_INPUT_AFTER / 2), ListenerStatus::Running);
+        assert!(matches!(listener.status(last_input + NO_INPUT_AFTER), ListenerStatus::NoInput(_)));
+
+        listener.set_no_input_after(Some(Duration::from_secs(30)));
+        assert_eq!(listener.status(last_input + NO_INPUT_AFTER), ListenerStatus::Running);
+        listener.set_no_input_after(None);
+        assert_eq!(listener.status(last_input + Duration::from_secs(3600)), ListenerStatus::Running);
+    }
 
     #[test]
     fn test_game_event_equality() {
-        assert_eq!(GameEvent::KeyAPress, GameEvent::KeyAPress);
-        assert_ne!(GameEvent::KeyAPress, GameEvent::KeyDPress);
+        assert_eq!(
+            GameEventKind::Press(Action::StrafeLeft),
+            GameEventKind::Press(Action::StrafeLeft)
+        );
+        assert_ne!(
+            GameEventKind::Press(Action::StrafeLeft),
+            GameEventKind::Press(Action::StrafeRight)
+        );
     }
 }
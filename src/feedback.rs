@@ -1,9 +1,34 @@
 use std::time::Instant;
-use crate::state::Quality;
+use crate::diagonal::DiagonalResult;
+use crate::metronome::rate_offset;
+use crate::reaction::{rate_reaction, Outcome};
+use crate::shots::{ShotResult, ShotTiming};
+use crate::simulation::StopAnalysis;
+use crate::state::{Axis, CompletionResult, Quality, TimingWindows};
 
-const MAX_FEED_ENTRIES: usize = 5;
-const VISIBLE_DURATION: f32 = 3.0;  // 3 seconds visible
-const FADE_DURATION: f32 = 1.0;     // 1 second fade
+pub const MAX_FEED_ENTRIES: usize = 5;
+pub const VISIBLE_DURATION: f32 = 3.0;  // 3 seconds visible
+pub const FADE_DURATION: f32 = 1.0;     // 1 second fade
+
+/// Feed length and timing, defaults from the constants above
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeedSettings {
+    pub max_entries: usize,
+    /// Seconds an entry stays fully visible
+    pub visible_duration: f32,
+    /// Seconds it then takes to fade out
+    pub fade_duration: f32,
+}
+
+impl Default for FeedSettings {
+    fn default() -> Self {
+        Self {
+            max_entries: MAX_FEED_ENTRIES,
+            visible_duration: VISIBLE_DURATION,
+            fade_duration: FADE_DURATION,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct FeedEntry {
@@ -24,14 +49,14 @@ impl FeedEntry {
     }
 
     /// Get opacity for fading effect (0.0 to 1.0)
-    pub fn get_opacity(&self, now: Instant) -> f32 {
+    pub fn get_opacity(&self, now: Instant, settings: &FeedSettings) -> f32 {
         let elapsed = now.duration_since(self.timestamp).as_secs_f32();
 
-        if elapsed < VISIBLE_DURATION {
+        if elapsed < settings.visible_duration {
             1.0
-        } else if elapsed < VISIBLE_DURATION + FADE_DURATION {
+        } else if elapsed < settings.visible_duration + settings.fade_duration {
             // Fade out linearly
-            let fade_progress = (elapsed - VISIBLE_DURATION) / FADE_DURATION;
+            let fade_progress = (elapsed - settings.visible_duration) / settings.fade_duration;
             1.0 - fade_progress
         } else {
             0.0
@@ -39,24 +64,36 @@ impl FeedEntry {
     }
 
     /// Check if entry should be removed
-    pub fn is_expired(&self, now: Instant) -> bool {
+    pub fn is_expired(&self, now: Instant, settings: &FeedSettings) -> bool {
         let elapsed = now.duration_since(self.timestamp).as_secs_f32();
-        elapsed >= VISIBLE_DURATION + FADE_DURATION
+        elapsed >= settings.visible_duration + settings.fade_duration
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct FeedSystem {
     entries: Vec<FeedEntry>,
+    settings: FeedSettings,
 }
 
 impl FeedSystem {
     pub fn new() -> Self {
+        Self::with_settings(FeedSettings::default())
+    }
+
+    pub fn with_settings(settings: FeedSettings) -> Self {
         Self {
-            entries: Vec::with_capacity(MAX_FEED_ENTRIES),
+            entries: Vec::with_capacity(settings.max_entries),
+            settings,
         }
     }
 
+    /// Apply new settings; a shorter feed drops the oldest entries
+    pub fn set_settings(&mut self, settings: FeedSettings) {
+        self.settings = settings;
+        self.entries.truncate(settings.max_entries);
+    }
+
     /// Add a new feed entry
     pub fn add(&mut self, message: String, quality: Quality) {
         let entry = FeedEntry::new(message, quality);
@@ -64,39 +101,106 @@ impl FeedSystem {
         // Insert at beginning (newest first)
         self.entries.insert(0, entry);
 
-        // Limit to MAX_FEED_ENTRIES
-        if self.entries.len() > MAX_FEED_ENTRIES {
-            self.entries.truncate(MAX_FEED_ENTRIES);
+        // Limit to the configured length
+        self.entries.truncate(self.settings.max_entries);
+    }
+
+    /// Add a completed attempt on one axis; `timing` rates the key transition
+    pub fn add_result(&mut self, result: &CompletionResult, timing: &TimingWindows) {
+        let axis = result.axis().symbol();
+        let hold_ms = result.hold_time * 1000.0;
+        let mut message = match (&result.error_message, result.quality) {
+            (Some(error), _) => format!("{} {}", axis, error),
+            (None, Quality::Perfect) => format!("{} PERFECT {:.0}ms", axis, hold_ms),
+            (None, Quality::Good) => format!("{} Good {:.0}ms", axis, hold_ms),
+            (None, Quality::Failed) => format!("{} Failed {:.0}ms", axis, hold_ms),
+        };
+        if let Some(transition) = result.transition {
+            message.push_str(&format!(" · {} {}", transition.label(), transition.quality(timing).symbol()));
         }
+        message.push_str(&stop_summary(result.stop.as_ref()));
+        self.add(message, result.quality);
     }
 
-    /// Add a perfect attempt
-    pub fn add_perfect(&mut self, hold_time: f32) {
-        let message = format!("PERFECT {:.0}ms", hold_time * 1000.0);
-        self.add(message, Quality::Perfect);
+    /// Add a diagonal stop with the outcome of each axis
+    pub fn add_diagonal(&mut self, result: &DiagonalResult) {
+        let label = match result.quality {
+            Quality::Perfect => "PERFECT",
+            Quality::Good => "Good",
+            Quality::Failed => "Failed",
+        };
+        let axes: Vec<String> = Axis::ALL
+            .iter()
+            .map(|axis| match result.axis_result(*axis) {
+                Some(r) if r.error_message.is_some() => {
+                    format!("{}{}", axis.symbol(), r.error_message.as_deref().unwrap_or_default())
+                }
+                Some(r) => format!("{}{:.0}ms", axis.symbol(), r.hold_time * 1000.0),
+                None => format!("{}no counter", axis.symbol()),
+            })
+            .collect();
+        let mut message = format!("↔↕ {} {}", label, axes.join(" "));
+
+        // The slower axis decides when the player is accurate again
+        let slowest_stop = Axis::ALL
+            .iter()
+            .filter_map(|axis| result.axis_result(*axis).and_then(|r| r.stop))
+            .max_by(|a, b| {
+                let a = a.time_to_accurate.unwrap_or(f32::MAX);
+                let b = b.time_to_accurate.unwrap_or(f32::MAX);
+                a.total_cmp(&b)
+            });
+        message.push_str(&stop_summary(slowest_stop.as_ref()));
+        self.add(message, result.quality);
     }
 
-    /// Add a good attempt
-    pub fn add_good(&mut self, hold_time: f32) {
-        let message = format!("Good {:.0}ms", hold_time * 1000.0);
-        self.add(message, Quality::Good);
+    /// Add an evaluated shot
+    pub fn add_shot(&mut self, shot: &ShotResult) {
+        let since_stop = shot
+            .since_stop
+            .map(|since| format!(" +{:.0}ms", since * 1000.0))
+            .unwrap_or_default();
+        let message = match shot.timing {
+            ShotTiming::OnTime => format!("⌖ On time{}", since_stop),
+            ShotTiming::Late => format!("⌖ Late{}", since_stop),
+            ShotTiming::TooEarly => format!("⌖ Too early · moving {:.0}u/s", shot.speed),
+        };
+        self.add(message, shot.timing.quality());
     }
 
-    /// Add a failed attempt with error message
-    pub fn add_failed(&mut self, error_message: &str) {
-        self.add(error_message.to_string(), Quality::Failed);
+    /// Add how far a stop landed from the metronome beat (seconds, negative = early)
+    pub fn add_beat(&mut self, offset: f32) {
+        let quality = rate_offset(offset);
+        let label = match quality {
+            Quality::Perfect => "On the beat",
+            _ if offset < 0.0 => "Early",
+            _ => "Late",
+        };
+        self.add(format!("♩ {} {:+.0}ms", label, offset * 1000.0), quality);
+    }
+
+    /// Add how a reaction prompt's STOP cue was answered
+    pub fn add_reaction(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Reaction(latency) => {
+                self.add(format!("⚑ Reaction {:.0}ms", latency * 1000.0), rate_reaction(latency))
+            }
+            Outcome::Early => self.add("⚑ Jumped the cue - wait for STOP".to_string(), Quality::Failed),
+            Outcome::Missed => self.add("⚑ Missed the STOP cue".to_string(), Quality::Failed),
+        }
     }
 
     /// Clean up expired entries
     pub fn cleanup(&mut self, now: Instant) {
-        self.entries.retain(|entry| !entry.is_expired(now));
+        let settings = self.settings;
+        self.entries.retain(|entry| !entry.is_expired(now, &settings));
     }
 
     /// Get all visible entries (newest first)
     pub fn get_visible_entries(&self, now: Instant) -> Vec<&FeedEntry> {
         self.entries
             .iter()
-            .filter(|entry| !entry.is_expired(now))
+            .filter(|entry| !entry.is_expired(now, &self.settings))
             .collect()
     }
 
@@ -104,8 +208,8 @@ impl FeedSystem {
     pub fn get_entries_with_opacity(&self, now: Instant) -> Vec<(&FeedEntry, f32)> {
         self.entries
             .iter()
-            .filter(|entry| !entry.is_expired(now))
-            .map(|entry| (entry, entry.get_opacity(now)))
+            .filter(|entry| !entry.is_expired(now, &self.settings))
+            .map(|entry| (entry, entry.get_opacity(now, &self.settings)))
             .collect()
     }
 
@@ -115,6 +219,21 @@ impl FeedSystem {
     }
 }
 
+/// Feed suffix for a simulated stop: time until accurate and any reverse overshoot
+fn stop_summary(stop: Option<&StopAnalysis>) -> String {
+    let Some(stop) = stop else {
+        return String::new();
+    };
+    let mut summary = match stop.time_to_accurate {
+        Some(time) => format!(" · acc {:.0}ms", time * 1000.0),
+        None => " · never accurate".to_string(),
+    };
+    if stop.overshoot >= 1.0 {
+        summary.push_str(&format!(" ↩{:.0}u/s", stop.overshoot));
+    }
+    summary
+}
+
 impl Default for FeedSystem {
     fn default() -> Self {
         Self::new()
@@ -124,27 +243,90 @@ impl Default for FeedSystem {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::thread::sleep;
-    use std::time::Duration;
+    use crate::state::{StrafeKey, Transition};
+
+    fn result(key: StrafeKey, hold_time: f32, quality: Quality, error: Option<&str>) -> CompletionResult {
+        CompletionResult {
+            original_key: key,
+            counter_key: key.opposite(),
+            hold_time,
+            quality,
+            error_kind: None,
+            error_message: error.map(str::to_string),
+            transition: None,
+            stop: None,
+            counter_time: None,
+        }
+    }
 
     #[test]
     fn test_add_entries() {
         let mut feed = FeedSystem::new();
-        feed.add_perfect(0.080);
-        feed.add_good(0.100);
-        feed.add_failed("Too fast 50ms");
+        feed.add_result(&result(StrafeKey::A, 0.080, Quality::Perfect, None), &TimingWindows::DEFAULT);
+        feed.add_result(&result(StrafeKey::W, 0.100, Quality::Good, None), &TimingWindows::DEFAULT);
+        feed.add_result(&result(StrafeKey::A, 0.050, Quality::Failed, Some("Too fast 50ms")), &TimingWindows::DEFAULT);
 
         assert_eq!(feed.entries.len(), 3);
+        assert_eq!(feed.entries[0].message, "↔ Too fast 50ms");
+        assert_eq!(feed.entries[1].message, "↕ Good 100ms");
+    }
+
+    #[test]
+    fn test_stop_summary() {
+        let mut attempt = result(StrafeKey::A, 0.080, Quality::Perfect, None);
+        attempt.stop = Some(StopAnalysis {
+            time_to_accurate: Some(0.092),
+            overshoot: 12.4,
+        });
+
+        let mut feed = FeedSystem::new();
+        feed.add_result(&attempt, &TimingWindows::DEFAULT);
+        assert_eq!(feed.entries[0].message, "↔ PERFECT 80ms · acc 92ms ↩12u/s");
+    }
+
+    #[test]
+    fn test_transition_text() {
+        let mut attempt = result(StrafeKey::D, 0.080, Quality::Perfect, None);
+        attempt.transition = Some(Transition::Gap(0.008));
+
+        let mut feed = FeedSystem::new();
+        feed.add_result(&attempt, &TimingWindows::DEFAULT);
+        assert_eq!(feed.entries[0].message, "↔ PERFECT 80ms · gap 8ms ★");
+    }
+
+    #[test]
+    fn test_shot_text() {
+        let mut feed = FeedSystem::new();
+        feed.add_shot(&ShotResult {
+            timing: ShotTiming::TooEarly,
+            since_stop: Some(0.010),
+            speed: 120.4,
+        });
+        feed.add_shot(&ShotResult {
+            timing: ShotTiming::OnTime,
+            since_stop: Some(0.045),
+            speed: 5.0,
+        });
+
+        assert_eq!(feed.entries[0].message, "⌖ On time +45ms");
+        assert_eq!(feed.entries[0].quality, Quality::Perfect);
+        assert_eq!(feed.entries[1].message, "⌖ Too early · moving 120u/s");
     }
 
     #[test]
     fn test_max_entries() {
         let mut feed = FeedSystem::new();
-        for i in 0..10 {
-            feed.add_perfect(0.080);
+        for _ in 0..10 {
+            feed.add_result(&result(StrafeKey::A, 0.080, Quality::Perfect, None), &TimingWindows::DEFAULT);
         }
 
         assert_eq!(feed.entries.len(), MAX_FEED_ENTRIES);
+
+        feed.set_settings(FeedSettings {
+            max_entries: 2,
+            ..FeedSettings::default()
+        });
+        assert_eq!(feed.entries.len(), 2);
     }
 
     #[test]
@@ -152,14 +334,21 @@ mod tests {
         let entry = FeedEntry::new("Test".to_string(), Quality::Perfect);
         let now = Instant::now();
 
+        let settings = FeedSettings::default();
+
         // Should be fully visible immediately
-        assert_eq!(entry.get_opacity(now), 1.0);
+        assert_eq!(entry.get_opacity(now, &settings), 1.0);
+
+        // Halfway through the fade
+        let later = now + std::time::Duration::from_secs_f32(settings.visible_duration + settings.fade_duration / 2.0);
+        assert!((entry.get_opacity(later, &settings) - 0.5).abs() < 0.01);
+        assert!(entry.is_expired(later + std::time::Duration::from_secs(1), &settings));
     }
 
     #[test]
     fn test_cleanup() {
         let mut feed = FeedSystem::new();
-        feed.add_perfect(0.080);
+        feed.add_result(&result(StrafeKey::A, 0.080, Quality::Perfect, None), &TimingWindows::DEFAULT);
 
         let now = Instant::now();
         feed.cleanup(now);
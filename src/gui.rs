@@ -0,0 +1,221 @@
+use crate::app::Trainer;
+use crate::diagnostics::Report;
+use crate::keymap::KeyMap;
+use crate::ui::{self, BindingsAction, DiagnosticsAction, DrillSummaryAction, DrillsAction, ListenerAction, SettingsAction, TrainerAction, View};
+use eframe::egui;
+use std::time::Instant;
+
+const WINDOW_TITLE: &str = "CS2 Counter-Strafe Trainer";
+
+/// egui frontend: the trainer plus the screen currently shown
+pub struct CS2TrainerApp {
+    trainer: Trainer,
+    view: View,
+    /// Collected when the diagnostics screen opens
+    diagnostics: Option<Report>,
+}
+
+impl CS2TrainerApp {
+    pub fn new(trainer: Trainer) -> Self {
+        Self {
+            trainer,
+            view: View::Trainer,
+            diagnostics: None,
+        }
+    }
+
+    pub fn trainer(&self) -> &Trainer {
+        &self.trainer
+    }
+
+    pub fn trainer_mut(&mut self) -> &mut Trainer {
+        &mut self.trainer
+    }
+
+    /// Screen currently shown
+    pub fn view(&self) -> View {
+        self.view
+    }
+
+    fn handle_bindings_action(&mut self, action: BindingsAction) {
+        match action {
+            BindingsAction::Rebind(target) => self.trainer.start_rebind(target),
+            BindingsAction::CancelRebind => self.trainer.cancel_rebind(),
+            BindingsAction::ToggleClickFire => {
+                let mut keymap = self.trainer.keymap();
+                keymap.set_click_fires(!keymap.click_fires());
+                self.trainer.apply_keymap(keymap);
+            }
+            BindingsAction::ResetDefaults => self.trainer.apply_keymap(KeyMap::default()),
+            BindingsAction::Close => self.toggle_view(View::Bindings),
+        }
+    }
+
+    fn handle_settings_action(&mut self, action: SettingsAction) {
+        match action {
+            SettingsAction::Apply { config, save } => self.trainer.update_config(*config, save),
+            SettingsAction::SelectWeapon(weapon) => self.trainer.select_weapon(weapon),
+            SettingsAction::SelectKeyMap(keymap) => self.trainer.apply_keymap(keymap),
+            SettingsAction::EditBindings => self.toggle_view(View::Bindings),
+            SettingsAction::Close => self.toggle_view(View::Settings),
+        }
+    }
+
+    /// Open `view`, or go back to the trainer if it is already open
+    fn toggle_view(&mut self, view: View) {
+        self.view = if self.view == view { View::Trainer } else { view };
+        self.trainer.cancel_rebind();
+        if self.view == View::Diagnostics {
+            self.diagnostics = Some(Report::collect());
+        }
+    }
+
+    /// Report shown on the diagnostics screen
+    pub fn diagnostics(&self) -> Option<&Report> {
+        self.diagnostics.as_ref()
+    }
+
+    /// One frame: process input and render the current view. Needs no window,
+    /// so it can be driven by a headless `egui::Context`.
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        // Clicks on the trainer's own window are not shots
+        self.trainer.set_ui_has_pointer(ctx.input(|i| i.focused || i.pointer.has_pointer()));
+        // Process keyboard events
+        self.trainer.process_events();
+        ui::set_theme(ctx, self.trainer.theme());
+
+        if ctx.input(|i| i.key_pressed(egui::Key::F1)) {
+            self.toggle_view(View::Bindings);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F2)) {
+            self.toggle_view(View::Charts);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F3)) {
+            self.toggle_view(View::Settings);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F4)) {
+            self.toggle_view(View::Diagnostics);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F5)) {
+            self.toggle_view(View::Drills);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F6)) {
+            self.trainer.toggle_reaction();
+        }
+        // A replay has no global quit key, the window has focus instead
+        if self.trainer.is_replaying() && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+
+        // Render UI
+        let trainer = &self.trainer;
+        let keymap = trainer.keymap();
+        match self.view {
+            View::Trainer if let Some(problem) = trainer.listener_problem() => {
+                match ui::render_listener_screen(ctx, &problem, trainer.can_restart_input()) {
+                    Some(ListenerAction::Retry) => self.trainer.restart_input(),
+                    Some(ListenerAction::Dismiss) => self.trainer.dismiss_listener_problem(),
+                    Some(ListenerAction::Diagnostics) => self.toggle_view(View::Diagnostics),
+                    Some(ListenerAction::Quit) => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+                    None => {}
+                }
+            }
+            View::Trainer if let Some(summary) = trainer.drill_summary() => {
+                match ui::render_drill_summary(ctx, summary) {
+                    Some(DrillSummaryAction::Again) => self.trainer.repeat_drill(),
+                    Some(DrillSummaryAction::Close) => self.trainer.close_drill_summary(),
+                    None => {}
+                }
+            }
+            View::Trainer => {
+                let action = ui::render_ui(
+                    ctx,
+                    trainer.movement(),
+                    trainer.simulation(),
+                    trainer.weapon(),
+                    trainer.feed(),
+                    trainer.stats(),
+                    trainer.all_time_stats(),
+                    &keymap,
+                    trainer.drill(),
+                    trainer.cue(Instant::now()),
+                );
+                match action {
+                    Some(TrainerAction::SelectWeapon(weapon)) => self.trainer.select_weapon(weapon),
+                    Some(TrainerAction::CycleOverlapWindow) => self.trainer.cycle_overlap_window(),
+                    Some(TrainerAction::StopDrill) => self.trainer.stop_drill(),
+                    None => {}
+                }
+            }
+            View::Bindings => {
+                if let Some(action) = ui::render_bindings_screen(ctx, &keymap, trainer.rebinding()) {
+                    self.handle_bindings_action(action);
+                }
+            }
+            View::Charts => {
+                if ui::render_charts_screen(ctx, trainer.stats(), trainer.movement().timing()) {
+                    self.toggle_view(View::Charts);
+                }
+            }
+            View::Settings => {
+                if let Some(action) = ui::render_settings_screen(ctx, trainer.config(), trainer.weapon(), &keymap) {
+                    self.handle_settings_action(action);
+                }
+            }
+            View::Drills => {
+                match ui::render_drills_screen(ctx, &trainer.drills(), trainer.drill()) {
+                    Some(DrillsAction::Start(drill)) => {
+                        self.trainer.start_drill(drill);
+                        self.toggle_view(View::Drills);
+                    }
+                    Some(DrillsAction::Stop) => self.trainer.stop_drill(),
+                    Some(DrillsAction::Close) => self.toggle_view(View::Drills),
+                    None => {}
+                }
+            }
+            View::Diagnostics => {
+                let report = self.diagnostics.get_or_insert_with(Report::collect);
+                match ui::render_diagnostics_screen(ctx, report) {
+                    Some(DiagnosticsAction::Refresh) => self.diagnostics = Some(Report::collect()),
+                    Some(DiagnosticsAction::Close) => self.toggle_view(View::Diagnostics),
+                    None => {}
+                }
+            }
+        }
+
+        // Handle quit
+        if self.trainer.should_quit() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+
+        // Request continuous repaint for smooth animations and timer updates
+        ctx.request_repaint();
+    }
+}
+
+impl eframe::App for CS2TrainerApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.ui(ctx);
+    }
+}
+
+/// Open the trainer window and run until it is closed
+pub fn run(trainer: Trainer) -> Result<(), Box<dyn std::error::Error>> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([ui::WINDOW_WIDTH, ui::WINDOW_HEIGHT])
+            .with_min_inner_size([ui::WINDOW_WIDTH, ui::WINDOW_HEIGHT])
+            .with_max_inner_size([ui::WINDOW_WIDTH, ui::WINDOW_HEIGHT])
+            .with_decorations(false)
+            .with_resizable(false)
+            .with_transparent(true),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        WINDOW_TITLE,
+        options,
+        Box::new(|_cc| Ok(Box::new(CS2TrainerApp::new(trainer)))),
+    )?;
+    Ok(())
+}
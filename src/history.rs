@@ -0,0 +1,395 @@
+use crate::diagonal::DiagonalResult;
+use crate::profiles::Weapon;
+use crate::reaction::ReactionTimes;
+use crate::state::{CompletionResult, ErrorKind, Quality, StrafeKey, Transition};
+use crate::stats::{Attempt, Stats};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+const DATA_DIR_NAME: &str = "cs2st";
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+/// One attempt as stored on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub timestamp: DateTime<Local>,
+    /// Start of the session the attempt belongs to
+    pub session: DateTime<Local>,
+    pub weapon: Weapon,
+    pub original_key: StrafeKey,
+    pub counter_key: StrafeKey,
+    pub hold_time_ms: f32,
+    pub quality: Quality,
+    pub error: Option<ErrorKind>,
+    /// Part of a diagonal stop (one record per countered axis)
+    #[serde(default)]
+    pub diagonal: bool,
+    /// Verdict of the whole diagonal stop, set on its first record only.
+    /// Older files lack it, their diagonal records count one by one.
+    #[serde(default)]
+    pub diagonal_quality: Option<Quality>,
+    #[serde(default)]
+    pub time_to_accurate_ms: Option<f32>,
+    /// Release -> counter-press gap
+    #[serde(default)]
+    pub gap_ms: Option<f32>,
+    /// Time both keys were held
+    #[serde(default)]
+    pub overlap_ms: Option<f32>,
+    /// STOP cue -> counter key latency in reaction mode
+    #[serde(default)]
+    pub reaction_ms: Option<f32>,
+    /// Strafe cue -> first press of the cued key in reaction mode
+    #[serde(default)]
+    pub strafe_reaction_ms: Option<f32>,
+}
+
+impl HistoryRecord {
+    /// The attempt as counted by `Stats`
+    pub fn attempt(&self) -> Attempt {
+        let transition = match (self.gap_ms, self.overlap_ms) {
+            (Some(gap), _) => Some(Transition::Gap(gap / 1000.0)),
+            (None, Some(overlap)) => Some(Transition::Overlap(overlap / 1000.0)),
+            (None, None) => None,
+        };
+        Attempt {
+            key: self.original_key,
+            quality: self.quality,
+            hold_time: self.hold_time_ms / 1000.0,
+            transition,
+        }
+    }
+
+    /// Count the attempt, and its reaction times if any, towards `stats`
+    pub fn count(&self, stats: &mut Stats) {
+        stats.record(self.attempt());
+        self.count_reaction(stats);
+    }
+
+    fn count_reaction(&self, stats: &mut Stats) {
+        if let Some(reaction) = self.reaction_ms {
+            stats.record_reaction(reaction / 1000.0);
+        }
+        if let Some(reaction) = self.strafe_reaction_ms {
+            stats.record_strafe_reaction(reaction / 1000.0);
+        }
+    }
+
+    /// Whether this record is a further axis of the diagonal stop started by `first`
+    fn continues(&self, first: &HistoryRecord) -> bool {
+        self.diagonal && self.diagonal_quality.is_none() && self.session == first.session
+    }
+}
+
+/// Split records into stops: a diagonal stop is its records for both countered axes,
+/// every other record is a stop of its own
+pub fn stops(mut records: &[HistoryRecord]) -> impl Iterator<Item = &[HistoryRecord]> {
+    std::iter::from_fn(move || {
+        let first = records.first()?;
+        let len = match first.diagonal_quality {
+            Some(_) => 1 + records[1..].iter().take(1).take_while(|next| next.continues(first)).count(),
+            None => 1,
+        };
+        let (stop, rest) = records.split_at(len);
+        records = rest;
+        Some(stop)
+    })
+}
+
+/// Stats per weapon, counting a diagonal stop once like the session stats do,
+/// although it is stored as one record per countered axis
+pub fn stats_by_weapon(records: &[HistoryRecord]) -> HashMap<Weapon, Stats> {
+    let mut stats: HashMap<Weapon, Stats> = HashMap::new();
+    for stop in stops(records) {
+        let first = &stop[0];
+        let weapon_stats = stats.entry(first.weapon).or_default();
+        let Some(quality) = first.diagonal_quality else {
+            first.count(weapon_stats);
+            continue;
+        };
+
+        weapon_stats.record_diagonal(quality, stop.iter().map(HistoryRecord::attempt));
+        // The reaction times are stored on the first record of the stop
+        first.count_reaction(weapon_stats);
+    }
+    stats
+}
+
+/// Append-only attempt history, stored as one JSON record per line
+pub struct History {
+    file: File,
+    session: DateTime<Local>,
+    records: Vec<HistoryRecord>,
+}
+
+impl History {
+    /// Default location: `<data dir>/cs2st/history.jsonl`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(DATA_DIR_NAME).join(HISTORY_FILE_NAME))
+    }
+
+    /// Open the history file for appending and load all previous sessions
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let records = load_records(path)?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file,
+            session: Local::now(),
+            records,
+        })
+    }
+
+    /// Append a completed attempt to disk and to the in-memory history.
+    /// `reaction` holds the reaction mode latencies, if the stop answered a prompt.
+    pub fn record(
+        &mut self,
+        result: &CompletionResult,
+        weapon: Weapon,
+        reaction: ReactionTimes,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let record = self.new_record(result, weapon, reaction);
+        self.append(record)
+    }
+
+    /// Append a diagonal stop as one record per countered axis. The first record
+    /// carries the stop's verdict and the reaction mode latencies.
+    pub fn record_diagonal(
+        &mut self,
+        diagonal: &DiagonalResult,
+        weapon: Weapon,
+        reaction: ReactionTimes,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut diagonal_quality = Some(diagonal.quality);
+        let mut reaction = Some(reaction);
+        for result in [&diagonal.horizontal, &diagonal.vertical].into_iter().flatten() {
+            let mut record = self.new_record(result, weapon, reaction.take().unwrap_or_default());
+            record.diagonal = true;
+            record.diagonal_quality = diagonal_quality.take();
+            self.append(record)?;
+        }
+        Ok(())
+    }
+
+    fn new_record(&self, result: &CompletionResult, weapon: Weapon, reaction: ReactionTimes) -> HistoryRecord {
+        HistoryRecord {
+            timestamp: Local::now(),
+            session: self.session,
+            weapon,
+            original_key: result.original_key,
+            counter_key: result.counter_key,
+            hold_time_ms: result.hold_time * 1000.0,
+            quality: result.quality,
+            error: result.error_kind,
+            diagonal: false,
+            diagonal_quality: None,
+            time_to_accurate_ms: result
+                .stop
+                .and_then(|stop| stop.time_to_accurate)
+                .map(|time| time * 1000.0),
+            gap_ms: match result.transition {
+                Some(Transition::Gap(gap)) => Some(gap * 1000.0),
+                _ => None,
+            },
+            overlap_ms: match result.transition {
+                Some(Transition::Overlap(overlap)) => Some(overlap * 1000.0),
+                _ => None,
+            },
+            reaction_ms: reaction.stop.map(|reaction| reaction * 1000.0),
+            strafe_reaction_ms: reaction.strafe.map(|reaction| reaction * 1000.0),
+        }
+    }
+
+    fn append(&mut self, record: HistoryRecord) -> Result<(), Box<dyn std::error::Error>> {
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+
+        self.records.push(record);
+        Ok(())
+    }
+
+    /// All attempts, oldest first
+    pub fn records(&self) -> &[HistoryRecord] {
+        &self.records
+    }
+
+    /// All-time stats per weapon
+    pub fn stats_by_weapon(&self) -> HashMap<Weapon, Stats> {
+        stats_by_weapon(&self.records)
+    }
+}
+
+/// Read all records from a history file. A missing file is an empty history;
+/// lines that fail to parse are skipped so one bad write never loses the rest.
+pub fn load_records(path: &Path) -> Result<Vec<HistoryRecord>, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    let mut skipped = 0;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(_) => skipped += 1,
+        }
+    }
+
+    if skipped > 0 {
+        log::warn!("Skipped {} unreadable history entries in {}", skipped, path.display());
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::StopAnalysis;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("cs2st-{}-{}.jsonl", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn result(quality: Quality) -> CompletionResult {
+        CompletionResult {
+            original_key: StrafeKey::A,
+            counter_key: StrafeKey::D,
+            hold_time: 0.082,
+            quality,
+            error_kind: None,
+            error_message: None,
+            transition: Some(Transition::Gap(0.010)),
+            stop: Some(StopAnalysis {
+                time_to_accurate: Some(0.090),
+                overshoot: 0.0,
+            }),
+            counter_time: None,
+        }
+    }
+
+    #[test]
+    fn test_records_survive_reopen() {
+        let path = temp_path("reopen");
+
+        let mut history = History::open(&path).unwrap();
+        history.record(&result(Quality::Perfect), Weapon::Rifle, ReactionTimes::default()).unwrap();
+        let reaction = ReactionTimes { strafe: Some(0.310), stop: Some(0.240) };
+        history.record(&result(Quality::Good), Weapon::Awp, reaction).unwrap();
+        drop(history);
+
+        let history = History::open(&path).unwrap();
+        assert_eq!(history.records().len(), 2);
+        assert_eq!(history.records()[0].counter_key, StrafeKey::D);
+        assert_eq!(history.records()[0].attempt().transition, Some(Transition::Gap(0.010)));
+        assert_eq!(history.records()[1].weapon, Weapon::Awp);
+        assert_eq!(history.records()[0].session, history.records()[1].session);
+
+        let stats = history.stats_by_weapon();
+        assert_eq!(stats[&Weapon::Rifle].perfect_count, 1);
+        assert_eq!(stats[&Weapon::Awp].good_count, 1);
+        assert!(stats[&Weapon::Rifle].reaction_times.is_empty());
+        assert_eq!(stats[&Weapon::Awp].reaction_times.len(), 1);
+        assert!((stats[&Weapon::Awp].reaction_times[0] - 0.240).abs() < 1e-6);
+        assert!((stats[&Weapon::Awp].strafe_reaction_times[0] - 0.310).abs() < 1e-6);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_diagonal_stop_counts_once() {
+        let path = temp_path("diagonal");
+
+        let mut vertical = result(Quality::Good);
+        vertical.original_key = StrafeKey::W;
+        vertical.counter_key = StrafeKey::S;
+        let mut history = History::open(&path).unwrap();
+        history
+            .record_diagonal(
+                &DiagonalResult {
+                    horizontal: Some(result(Quality::Perfect)),
+                    vertical: Some(vertical),
+                    quality: Quality::Good,
+                },
+                Weapon::Rifle,
+                ReactionTimes::default(),
+            )
+            .unwrap();
+        // Diagonal stop with only the horizontal axis countered
+        history
+            .record_diagonal(
+                &DiagonalResult {
+                    horizontal: Some(result(Quality::Perfect)),
+                    vertical: None,
+                    quality: Quality::Failed,
+                },
+                Weapon::Rifle,
+                ReactionTimes::default(),
+            )
+            .unwrap();
+        history.record(&result(Quality::Perfect), Weapon::Rifle, ReactionTimes::default()).unwrap();
+        drop(history);
+
+        let history = History::open(&path).unwrap();
+        assert_eq!(history.records()[0].diagonal_quality, Some(Quality::Good));
+        assert_eq!(history.records()[1].diagonal_quality, None);
+        let stats = &history.stats_by_weapon()[&Weapon::Rifle];
+        assert_eq!(stats.total_attempts, 3);
+        assert_eq!(stats.diagonal.attempts, 2);
+        assert_eq!(stats.good_count, 1);
+        assert_eq!(stats.failed_count, 1);
+        assert_eq!(stats.perfect_count, 1);
+        assert_eq!(stats.hold_times.len(), 4);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_old_diagonal_records_count_one_by_one() {
+        let line = r#"{"timestamp":"2025-01-01T12:00:00+01:00","session":"2025-01-01T12:00:00+01:00","weapon":"Rifle","original_key":"A","counter_key":"D","hold_time_ms":82.0,"quality":"Good","error":null,"diagonal":true}"#;
+        let record: HistoryRecord = serde_json::from_str(line).unwrap();
+        assert_eq!(record.diagonal_quality, None);
+
+        let records = vec![record.clone(), record];
+        assert_eq!(stops(&records).count(), 2);
+        let stats = &stats_by_weapon(&records)[&Weapon::Rifle];
+        assert_eq!(stats.total_attempts, 2);
+        assert_eq!(stats.diagonal.attempts, 0);
+    }
+
+    #[test]
+    fn test_corrupt_lines_are_skipped() {
+        let path = temp_path("corrupt");
+
+        let mut history = History::open(&path).unwrap();
+        history.record(&result(Quality::Perfect), Weapon::Rifle, ReactionTimes::default()).unwrap();
+        drop(history);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{not json\n").unwrap();
+
+        assert_eq!(load_records(&path).unwrap().len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let path = temp_path("missing");
+        assert!(load_records(&path).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,407 @@
+use crate::events::{Control, GameEvent, GameEventKind, ListenerMessage};
+use crate::keymap::{Action, KeyMap};
+use crate::recording::{EntryKind, Recording};
+use rdev::{Button, Event, EventType, Key};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Which input backend to train with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// evdev if a keyboard is readable (Linux), rdev otherwise
+    #[default]
+    Auto,
+    /// Global hook via rdev (X11 on Linux)
+    Rdev,
+    /// /dev/input devices with kernel timestamps (Linux only)
+    Evdev,
+}
+
+/// Create the input source for `backend`; `device` picks a single evdev device
+pub fn open_source(backend: Backend, device: Option<&Path>) -> Result<Box<dyn InputSource>, Box<dyn std::error::Error>> {
+    match (backend, device) {
+        (Backend::Rdev, None) => Ok(Box::new(RdevSource)),
+        (Backend::Rdev, Some(_)) => Err("Picking an input device requires the evdev backend".into()),
+        #[cfg(target_os = "linux")]
+        (Backend::Evdev | Backend::Auto, Some(device)) => Ok(Box::new(crate::evdev_source::EvdevSource::device(device)?)),
+        #[cfg(target_os = "linux")]
+        (Backend::Evdev, None) => Ok(Box::new(crate::evdev_source::EvdevSource::auto()?)),
+        #[cfg(target_os = "linux")]
+        (Backend::Auto, None) => match crate::evdev_source::EvdevSource::auto() {
+            Ok(source) => Ok(Box::new(source)),
+            Err(e) => {
+                log::info!("Using rdev, evdev is not available: {}", e);
+                Ok(Box::new(RdevSource))
+            }
+        },
+        #[cfg(not(target_os = "linux"))]
+        (Backend::Evdev, _) | (Backend::Auto, Some(_)) => Err("The evdev backend is only available on Linux".into()),
+        #[cfg(not(target_os = "linux"))]
+        (Backend::Auto, None) => Ok(Box::new(RdevSource)),
+    }
+}
+
+/// Input as a backend sees it, before key bindings are applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RawInput {
+    KeyPress(Key),
+    KeyRelease(Key),
+    ButtonPress(Button),
+    ButtonRelease(Button),
+    /// Input that is already mapped to an action, e.g. from a recording
+    Mapped(GameEventKind),
+}
+
+/// Where input comes from. `run` is called once on the listener thread and delivers
+/// input to the sink until the source is exhausted or the sink is closed.
+pub trait InputSource: Send {
+    fn run(self: Box<Self>, sink: InputSink) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Whether the source installs a process-wide hook that must not run twice at once.
+    /// Such a source can only be opened again after `run` has returned.
+    fn is_global_hook(&self) -> bool {
+        false
+    }
+}
+
+/// Turns raw input into game events: filters key repeats, applies the key bindings
+/// and delivers captured keys while rebinding. Mouse buttons are dropped while the
+/// pointer is on the trainer's own window. Shared by all sources; a source reading
+/// several devices uses one clone per device.
+#[derive(Clone)]
+pub struct InputSink {
+    tx: Sender<ListenerMessage>,
+    keymap: Arc<RwLock<KeyMap>>,
+    capture_next: Arc<AtomicBool>,
+    ui_has_pointer: Arc<AtomicBool>,
+    held_keys: HashSet<Key>,
+    held_buttons: HashSet<Button>,
+}
+
+impl InputSink {
+    pub(crate) fn new(
+        tx: Sender<ListenerMessage>,
+        keymap: Arc<RwLock<KeyMap>>,
+        capture_next: Arc<AtomicBool>,
+        ui_has_pointer: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            tx,
+            keymap,
+            capture_next,
+            ui_has_pointer,
+            held_keys: HashSet::new(),
+            held_buttons: HashSet::new(),
+        }
+    }
+
+    /// Deliver input captured at `time`. Returns false once the listener is gone.
+    pub fn send(&mut self, input: RawInput, time: Instant) -> bool {
+        let kind = match input {
+            RawInput::KeyPress(key) => {
+                if !self.held_keys.insert(key) {
+                    None // Filter key repeat
+                } else if self.capture_next.swap(false, Ordering::SeqCst) {
+                    Some(GameEventKind::KeyCaptured(key))
+                } else {
+                    self.lookup(key).map(GameEventKind::Press)
+                }
+            }
+            RawInput::KeyRelease(key) => {
+                if self.held_keys.remove(&key) {
+                    self.lookup(key).map(GameEventKind::Release)
+                } else {
+                    None
+                }
+            }
+            // Clicks that operate the trainer's window are not shots
+            RawInput::ButtonPress(button) => {
+                if self.ui_has_pointer.load(Ordering::SeqCst) || !self.held_buttons.insert(button) {
+                    None
+                } else {
+                    self.lookup_button(button).map(GameEventKind::Press)
+                }
+            }
+            RawInput::ButtonRelease(button) => {
+                if self.held_buttons.remove(&button) {
+                    self.lookup_button(button).map(GameEventKind::Release)
+                } else {
+                    None
+                }
+            }
+            RawInput::Mapped(kind) => Some(kind),
+        };
+
+        match kind {
+            Some(kind) => self.tx.send(ListenerMessage::Event(GameEvent { kind, time })).is_ok(),
+            None => true,
+        }
+    }
+
+    fn lookup(&self, key: Key) -> Option<Action> {
+        self.keymap.read().ok().and_then(|keymap| keymap.action_for(key))
+    }
+
+    fn lookup_button(&self, button: Button) -> Option<Action> {
+        self.keymap.read().ok().and_then(|keymap| keymap.action_for_button(button))
+    }
+}
+
+/// Global keyboard and mouse hook via rdev (X11 on Linux)
+pub struct RdevSource;
+
+impl InputSource for RdevSource {
+    fn run(self: Box<Self>, mut sink: InputSink) -> Result<(), Box<dyn std::error::Error>> {
+        rdev::listen(move |event: Event| {
+            // Stamp before any filtering so the measured hold times are independent of the UI frame rate
+            let time = Instant::now();
+
+            let input = match event.event_type {
+                EventType::KeyPress(key) => RawInput::KeyPress(key),
+                EventType::KeyRelease(key) => RawInput::KeyRelease(key),
+                EventType::ButtonPress(button) => RawInput::ButtonPress(button),
+                EventType::ButtonRelease(button) => RawInput::ButtonRelease(button),
+                _ => return,
+            };
+            sink.send(input, time);
+        })
+        .map_err(|e| format!("Event listening error: {:?}", e).into())
+    }
+
+    /// `rdev::listen` never returns while it works, and a second call replaces its global callback
+    fn is_global_hook(&self) -> bool {
+        true
+    }
+}
+
+/// Plays a timed sequence of input, for tests and demos. Events are stamped with
+/// the scripted time, so hold times are exact regardless of scheduling.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedSource {
+    /// Offset from the start of the script and the input sent then
+    steps: Vec<(Duration, RawInput)>,
+}
+
+impl ScriptedSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `input` at `at` after the start; steps are sorted by time when run
+    pub fn at(mut self, at: Duration, input: RawInput) -> Self {
+        self.steps.push((at, input));
+        self
+    }
+
+    pub fn press(self, key: Key, at: Duration) -> Self {
+        self.at(at, RawInput::KeyPress(key))
+    }
+
+    pub fn release(self, key: Key, at: Duration) -> Self {
+        self.at(at, RawInput::KeyRelease(key))
+    }
+
+    /// Press `key` at `at` and hold it for `hold`
+    pub fn tap(self, key: Key, at: Duration, hold: Duration) -> Self {
+        self.press(key, at).release(key, at + hold)
+    }
+
+    /// Time of the last step
+    pub fn duration(&self) -> Duration {
+        self.steps.iter().map(|(at, _)| *at).max().unwrap_or_default()
+    }
+}
+
+impl InputSource for ScriptedSource {
+    fn run(mut self: Box<Self>, mut sink: InputSink) -> Result<(), Box<dyn std::error::Error>> {
+        self.steps.sort_by_key(|(at, _)| *at);
+        play(&self.steps, &mut sink);
+        Ok(())
+    }
+}
+
+/// Input of a recording, played back at its original pace, with the weapon and
+/// overlap window changes made while recording
+pub struct ReplaySource {
+    inputs: Vec<(Duration, RawInput)>,
+}
+
+impl ReplaySource {
+    pub fn new(recording: &Recording) -> Self {
+        let inputs = recording
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let kind = match entry.kind {
+                    EntryKind::Input(kind) => kind,
+                    EntryKind::Weapon(weapon) => GameEventKind::Control(Control::SelectWeapon(weapon)),
+                    EntryKind::OverlapWindow(window) => {
+                        GameEventKind::Control(Control::SetOverlapWindow(Duration::from_secs_f32(window)))
+                    }
+                    EntryKind::Result(_) => return None,
+                };
+                Some((entry.offset, RawInput::Mapped(kind)))
+            })
+            .collect();
+        Self { inputs }
+    }
+}
+
+impl InputSource for ReplaySource {
+    fn run(self: Box<Self>, mut sink: InputSink) -> Result<(), Box<dyn std::error::Error>> {
+        play(&self.inputs, &mut sink);
+        log::info!("Replay finished");
+        Ok(())
+    }
+}
+
+/// Send timed input, sleeping until each offset so timing does not drift over a long sequence
+fn play(steps: &[(Duration, RawInput)], sink: &mut InputSink) {
+    let start = Instant::now();
+    for (offset, input) in steps {
+        let time = start + *offset;
+        thread::sleep(time.saturating_duration_since(Instant::now()));
+        if !sink.send(*input, time) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    fn sink() -> (InputSink, std::sync::mpsc::Receiver<ListenerMessage>, Arc<AtomicBool>) {
+        let (sink, rx, capture, _) = sink_with_pointer();
+        (sink, rx, capture)
+    }
+
+    fn sink_with_pointer() -> (InputSink, std::sync::mpsc::Receiver<ListenerMessage>, Arc<AtomicBool>, Arc<AtomicBool>) {
+        let (tx, rx) = channel();
+        let capture = Arc::new(AtomicBool::new(false));
+        let pointer = Arc::new(AtomicBool::new(false));
+        let sink = InputSink::new(
+            tx,
+            Arc::new(RwLock::new(KeyMap::default())),
+            Arc::clone(&capture),
+            Arc::clone(&pointer),
+        );
+        (sink, rx, capture, pointer)
+    }
+
+    fn events(rx: &std::sync::mpsc::Receiver<ListenerMessage>) -> Vec<GameEvent> {
+        rx.try_iter()
+            .filter_map(|message| match message {
+                ListenerMessage::Event(event) => Some(event),
+                ListenerMessage::Status(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sink_maps_keys_and_filters_repeats() {
+        let (mut sink, rx, _) = sink();
+        let now = Instant::now();
+        sink.send(RawInput::KeyPress(Key::KeyA), now);
+        sink.send(RawInput::KeyPress(Key::KeyA), now); // Key repeat
+        sink.send(RawInput::KeyPress(Key::KeyZ), now); // Unbound
+        sink.send(RawInput::KeyRelease(Key::KeyA), now);
+        sink.send(RawInput::ButtonPress(Button::Left), now);
+
+        let kinds: Vec<GameEventKind> = events(&rx).iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                GameEventKind::Press(Action::StrafeLeft),
+                GameEventKind::Release(Action::StrafeLeft),
+                GameEventKind::Press(Action::Fire),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sink_drops_clicks_on_the_trainer_window() {
+        let (mut sink, rx, _, pointer) = sink_with_pointer();
+        let now = Instant::now();
+        pointer.store(true, Ordering::SeqCst);
+        sink.send(RawInput::ButtonPress(Button::Left), now);
+        pointer.store(false, Ordering::SeqCst);
+        sink.send(RawInput::ButtonRelease(Button::Left), now);
+        sink.send(RawInput::ButtonPress(Button::Right), now); // Unbound
+
+        sink.keymap.write().unwrap().set_click_fires(false);
+        sink.send(RawInput::ButtonPress(Button::Left), now);
+        sink.send(RawInput::ButtonRelease(Button::Left), now);
+
+        assert!(events(&rx).is_empty());
+    }
+
+    #[test]
+    fn test_sink_captures_next_key() {
+        let (mut sink, rx, capture) = sink();
+        capture.store(true, Ordering::SeqCst);
+        sink.send(RawInput::KeyPress(Key::KeyJ), Instant::now());
+        sink.send(RawInput::KeyPress(Key::KeyD), Instant::now());
+
+        let kinds: Vec<GameEventKind> = events(&rx).iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![GameEventKind::KeyCaptured(Key::KeyJ), GameEventKind::Press(Action::StrafeRight)]
+        );
+    }
+
+    #[test]
+    fn test_replay_delivers_setting_changes() {
+        use crate::profiles::Weapon;
+        use crate::recording::Entry;
+
+        let entry = |offset: u64, kind: EntryKind| Entry {
+            offset: Duration::from_millis(offset),
+            kind,
+        };
+        let recording = Recording {
+            weapon: Weapon::Rifle,
+            overlap_window: 0.0,
+            entries: vec![
+                entry(0, EntryKind::Input(GameEventKind::Press(Action::StrafeLeft))),
+                entry(1, EntryKind::Weapon(Weapon::Awp)),
+                entry(2, EntryKind::OverlapWindow(0.020)),
+            ],
+        };
+        let (sink, rx, _) = sink();
+        Box::new(ReplaySource::new(&recording)).run(sink).unwrap();
+
+        let kinds: Vec<GameEventKind> = events(&rx).iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                GameEventKind::Press(Action::StrafeLeft),
+                GameEventKind::Control(Control::SelectWeapon(Weapon::Awp)),
+                GameEventKind::Control(Control::SetOverlapWindow(Duration::from_millis(20))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scripted_source_stamps_script_time() {
+        let (sink, rx, _) = sink();
+        let script = ScriptedSource::new()
+            .tap(Key::KeyD, Duration::from_millis(5), Duration::from_millis(10))
+            .press(Key::KeyA, Duration::ZERO);
+        assert_eq!(script.duration(), Duration::from_millis(15));
+        Box::new(script).run(sink).unwrap();
+
+        let events = events(&rx);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].kind, GameEventKind::Press(Action::StrafeLeft));
+        assert_eq!(events[2].time - events[1].time, Duration::from_millis(10));
+    }
+}
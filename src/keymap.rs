@@ -0,0 +1,299 @@
+use crate::state::StrafeKey;
+use rdev::{Button, Key};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const CONFIG_DIR_NAME: &str = "cs2st";
+const BINDINGS_FILE_NAME: &str = "keybindings.toml";
+
+/// Logical actions the trainer reacts to, independent of the physical key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    StrafeLeft,
+    StrafeRight,
+    StrafeForward,
+    StrafeBack,
+    Fire,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::StrafeForward,
+        Action::StrafeBack,
+        Action::Fire,
+        Action::Quit,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::StrafeLeft => "Strafe left",
+            Action::StrafeRight => "Strafe right",
+            Action::StrafeForward => "Forward",
+            Action::StrafeBack => "Back",
+            Action::Fire => "Fire",
+            Action::Quit => "Quit",
+        }
+    }
+
+    /// Strafe key this action drives, if it is a movement action
+    pub fn strafe_key(&self) -> Option<StrafeKey> {
+        match self {
+            Action::StrafeLeft => Some(StrafeKey::A),
+            Action::StrafeRight => Some(StrafeKey::D),
+            Action::StrafeForward => Some(StrafeKey::W),
+            Action::StrafeBack => Some(StrafeKey::S),
+            Action::Fire | Action::Quit => None,
+        }
+    }
+}
+
+/// Maps physical keys to logical actions. Serialized as one key list per action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyMap {
+    pub strafe_left: Vec<Key>,
+    pub strafe_right: Vec<Key>,
+    pub strafe_forward: Vec<Key>,
+    pub strafe_back: Vec<Key>,
+    pub fire: Vec<Key>,
+    /// Mouse buttons that fire, in addition to the fire keys
+    pub fire_buttons: Vec<Button>,
+    pub quit: Vec<Key>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            strafe_left: vec![Key::KeyA, Key::Alt],
+            strafe_right: vec![Key::KeyD],
+            strafe_forward: vec![Key::KeyW],
+            strafe_back: vec![Key::KeyS],
+            fire: vec![Key::Space],
+            fire_buttons: vec![Button::Left],
+            quit: vec![Key::Escape],
+        }
+    }
+}
+
+impl KeyMap {
+    /// Arrow keys for movement, for players who do not use WASD
+    pub fn arrows() -> Self {
+        Self {
+            strafe_left: vec![Key::LeftArrow],
+            strafe_right: vec![Key::RightArrow],
+            strafe_forward: vec![Key::UpArrow],
+            strafe_back: vec![Key::DownArrow],
+            ..Self::default()
+        }
+    }
+
+    /// Named presets offered in the settings
+    pub fn presets() -> [(&'static str, KeyMap); 2] {
+        [("WASD", Self::default()), ("Arrows", Self::arrows())]
+    }
+
+    /// Find the action bound to a key
+    pub fn action_for(&self, key: Key) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| self.keys_for(*action).contains(&key))
+    }
+
+    /// Find the action bound to a mouse button
+    pub fn action_for_button(&self, button: Button) -> Option<Action> {
+        self.fire_buttons.contains(&button).then_some(Action::Fire)
+    }
+
+    /// Whether a left click fires
+    pub fn click_fires(&self) -> bool {
+        self.fire_buttons.contains(&Button::Left)
+    }
+
+    /// Let a left click fire, or stop it from firing
+    pub fn set_click_fires(&mut self, enabled: bool) {
+        self.fire_buttons.retain(|button| *button != Button::Left);
+        if enabled {
+            self.fire_buttons.push(Button::Left);
+        }
+    }
+
+    /// Keys bound to an action
+    pub fn keys_for(&self, action: Action) -> &[Key] {
+        match action {
+            Action::StrafeLeft => &self.strafe_left,
+            Action::StrafeRight => &self.strafe_right,
+            Action::StrafeForward => &self.strafe_forward,
+            Action::StrafeBack => &self.strafe_back,
+            Action::Fire => &self.fire,
+            Action::Quit => &self.quit,
+        }
+    }
+
+    fn keys_for_mut(&mut self, action: Action) -> &mut Vec<Key> {
+        match action {
+            Action::StrafeLeft => &mut self.strafe_left,
+            Action::StrafeRight => &mut self.strafe_right,
+            Action::StrafeForward => &mut self.strafe_forward,
+            Action::StrafeBack => &mut self.strafe_back,
+            Action::Fire => &mut self.fire,
+            Action::Quit => &mut self.quit,
+        }
+    }
+
+    /// Bind a key to an action, replacing the action's previous keys.
+    /// The key is removed from any other action so one key never triggers two actions.
+    pub fn bind(&mut self, action: Action, key: Key) {
+        for other in Action::ALL {
+            self.keys_for_mut(other).retain(|k| *k != key);
+        }
+        *self.keys_for_mut(action) = vec![key];
+    }
+
+    /// Human readable label of the keys and buttons bound to an action, e.g. "A/Alt"
+    pub fn label_for(&self, action: Action) -> String {
+        let mut names: Vec<String> = self.keys_for(action).iter().map(|k| key_name(*k)).collect();
+        if action == Action::Fire {
+            names.extend(self.fire_buttons.iter().map(|b| button_name(*b)));
+        }
+        if names.is_empty() {
+            "unbound".to_string()
+        } else {
+            names.join("/")
+        }
+    }
+
+    /// Label of the primary key driving a strafe direction
+    pub fn strafe_label(&self, key: StrafeKey) -> String {
+        let action = Action::ALL
+            .into_iter()
+            .find(|action| action.strafe_key() == Some(key))
+            .expect("every strafe key has an action");
+        self.keys_for(action)
+            .first()
+            .map(|k| key_name(*k))
+            .unwrap_or_else(|| key.as_char().to_string())
+    }
+
+    /// Default location: `<config dir>/cs2st/keybindings.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(BINDINGS_FILE_NAME))
+    }
+
+    /// Load bindings from a TOML file. A missing file yields the default bindings.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        let keymap = toml::from_str(&contents)
+            .map_err(|e| format!("Invalid key bindings in {}: {}", path.display(), e))?;
+        Ok(keymap)
+    }
+
+    /// Write bindings to a TOML file, creating the parent directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Short display name for a key ("KeyA" -> "A", "Num1" -> "1")
+pub fn key_name(key: Key) -> String {
+    let name = format!("{:?}", key);
+    match name.strip_prefix("Key").or_else(|| name.strip_prefix("Num")) {
+        Some(short) if !short.is_empty() && !short.starts_with("Lock") => short.to_string(),
+        _ => name,
+    }
+}
+
+/// Display name for a mouse button ("Left" -> "Left click")
+pub fn button_name(button: Button) -> String {
+    match button {
+        Button::Unknown(number) => format!("Mouse {}", number),
+        button => format!("{:?} click", button),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_bindings() {
+        let keymap = KeyMap::default();
+        assert_eq!(keymap.action_for(Key::KeyA), Some(Action::StrafeLeft));
+        assert_eq!(keymap.action_for(Key::Alt), Some(Action::StrafeLeft));
+        assert_eq!(keymap.action_for(Key::KeyD), Some(Action::StrafeRight));
+        assert_eq!(keymap.action_for(Key::KeyW), Some(Action::StrafeForward));
+        assert_eq!(keymap.action_for(Key::KeyS), Some(Action::StrafeBack));
+        assert_eq!(keymap.action_for(Key::Space), Some(Action::Fire));
+        assert_eq!(keymap.action_for(Key::Escape), Some(Action::Quit));
+        assert_eq!(keymap.action_for(Key::KeyE), None);
+        assert_eq!(keymap.action_for_button(Button::Left), Some(Action::Fire));
+        assert_eq!(keymap.action_for_button(Button::Right), None);
+        assert_eq!(keymap.label_for(Action::Fire), "Space/Left click");
+    }
+
+    #[test]
+    fn test_click_fire_can_be_disabled() {
+        let mut keymap = KeyMap::default();
+        keymap.set_click_fires(false);
+        assert!(!keymap.click_fires());
+        assert_eq!(keymap.action_for_button(Button::Left), None);
+        assert_eq!(keymap.label_for(Action::Fire), "Space");
+
+        let parsed: KeyMap = toml::from_str(&toml::to_string_pretty(&keymap).unwrap()).unwrap();
+        assert!(parsed.fire_buttons.is_empty());
+    }
+
+    #[test]
+    fn test_arrow_preset() {
+        let keymap = KeyMap::arrows();
+        assert_eq!(keymap.action_for(Key::LeftArrow), Some(Action::StrafeLeft));
+        assert_eq!(keymap.action_for(Key::DownArrow), Some(Action::StrafeBack));
+        assert_eq!(keymap.action_for(Key::KeyA), None);
+        assert_eq!(keymap.fire, KeyMap::default().fire);
+    }
+
+    #[test]
+    fn test_bind_moves_key_between_actions() {
+        let mut keymap = KeyMap::default();
+        keymap.bind(Action::StrafeLeft, Key::LeftArrow);
+        keymap.bind(Action::StrafeRight, Key::LeftArrow);
+
+        assert_eq!(keymap.action_for(Key::LeftArrow), Some(Action::StrafeRight));
+        assert!(keymap.strafe_left.is_empty());
+        assert_eq!(keymap.action_for(Key::KeyD), None);
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut keymap = KeyMap::default();
+        keymap.bind(Action::StrafeLeft, Key::KeyQ);
+
+        let text = toml::to_string_pretty(&keymap).unwrap();
+        let parsed: KeyMap = toml::from_str(&text).unwrap();
+        assert_eq!(parsed, keymap);
+    }
+
+    #[test]
+    fn test_partial_file_keeps_defaults() {
+        let parsed: KeyMap = toml::from_str("strafe_left = [\"KeyQ\"]").unwrap();
+        assert_eq!(parsed.strafe_left, vec![Key::KeyQ]);
+        assert_eq!(parsed.strafe_right, vec![Key::KeyD]);
+    }
+
+    #[test]
+    fn test_key_names() {
+        assert_eq!(key_name(Key::KeyA), "A");
+        assert_eq!(key_name(Key::Num1), "1");
+        assert_eq!(key_name(Key::LeftArrow), "LeftArrow");
+        assert_eq!(key_name(Key::NumLock), "NumLock");
+    }
+}
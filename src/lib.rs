@@ -0,0 +1,27 @@
+//! Counter-strafe trainer core and frontends. The binary only parses the command
+//! line; everything else lives here so integration tests can drive it.
+
+pub mod app;
+pub mod cli;
+pub mod config;
+pub mod diagnostics;
+pub mod diagonal;
+pub mod drills;
+pub mod events;
+#[cfg(target_os = "linux")]
+pub mod evdev_source;
+pub mod feedback;
+pub mod gui;
+pub mod history;
+pub mod input;
+pub mod keymap;
+pub mod metronome;
+pub mod profiles;
+pub mod reaction;
+pub mod recording;
+pub mod shots;
+pub mod simulation;
+pub mod state;
+pub mod stats;
+pub mod tui;
+pub mod ui;
@@ -0,0 +1,208 @@
+use crate::state::{Quality, StrafeKey};
+use std::time::{Duration, Instant};
+
+/// Tempo limits for drills and the config
+pub const BPM_RANGE: (f32, f32) = (30.0, 240.0);
+pub const DEFAULT_BPM: f32 = 100.0;
+
+// How far a stop may land from its beat (seconds)
+pub const ON_BEAT: f32 = 0.030;
+pub const NEAR_BEAT: f32 = 0.080;
+
+/// What the current beat, or reaction prompt, asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    /// Nothing yet, a reaction prompt is coming
+    Wait,
+    /// Start strafing with this key
+    Strafe(StrafeKey),
+    /// Counter-strafe: the counter key should go down now
+    Stop,
+}
+
+impl Cue {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Cue::Wait => "WAIT…",
+            Cue::Strafe(StrafeKey::A) => "◀ STRAFE LEFT",
+            Cue::Strafe(StrafeKey::D) => "STRAFE RIGHT ▶",
+            Cue::Strafe(_) => "STRAFE",
+            Cue::Stop => "STOP",
+        }
+    }
+}
+
+/// Beat clock for rhythm drills. Beats alternate between a strafe cue and a stop cue,
+/// and the strafe direction alternates every cycle: A, stop, D, stop - the ADAD jiggle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metronome {
+    bpm: f32,
+    /// Time of beat 0
+    start: Instant,
+}
+
+impl Metronome {
+    pub fn new(bpm: f32, start: Instant) -> Self {
+        Self { bpm, start }
+    }
+
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    fn interval(&self) -> f32 {
+        60.0 / self.bpm
+    }
+
+    /// Time of beat `beat`
+    pub fn beat_time(&self, beat: u64) -> Instant {
+        self.start + Duration::from_secs_f32(beat as f32 * self.interval())
+    }
+
+    fn beat_cue(beat: u64) -> Cue {
+        match (beat % 2, beat / 2 % 2) {
+            (1, _) => Cue::Stop,
+            (_, 0) => Cue::Strafe(StrafeKey::A),
+            _ => Cue::Strafe(StrafeKey::D),
+        }
+    }
+
+    /// Cue of the beat at `now` and how far into that beat we are (0.0 - 1.0), for the visual pulse
+    pub fn cue(&self, now: Instant) -> (Cue, f32) {
+        let beats = now.saturating_duration_since(self.start).as_secs_f32() / self.interval();
+        (Self::beat_cue(beats as u64), beats.fract())
+    }
+
+    /// Seconds from the nearest stop beat to `time`; negative means early
+    pub fn stop_offset(&self, time: Instant) -> f32 {
+        let since_start = if time >= self.start {
+            time.duration_since(self.start).as_secs_f32()
+        } else {
+            -self.start.duration_since(time).as_secs_f32()
+        };
+        // Stop beats are the odd beats, one every cycle of two beats
+        let cycle = 2.0 * self.interval();
+        let since_first_stop = since_start - self.interval();
+        since_first_stop - (since_first_stop / cycle).round() * cycle
+    }
+}
+
+/// Rate how close a stop landed to its beat
+pub fn rate_offset(offset: f32) -> Quality {
+    match offset.abs() {
+        offset if offset <= ON_BEAT => Quality::Perfect,
+        offset if offset <= NEAR_BEAT => Quality::Good,
+        _ => Quality::Failed,
+    }
+}
+
+/// Audible click on every beat, higher on stop beats. Plays on its own thread, scheduled
+/// from the beat clock so it does not depend on the frame rate; stops when dropped.
+#[cfg(feature = "sound")]
+pub struct Click {
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[cfg(feature = "sound")]
+impl Click {
+    const STRAFE_PITCH: f32 = 880.0;
+    const STOP_PITCH: f32 = 1760.0;
+    const LENGTH: Duration = Duration::from_millis(25);
+
+    pub fn start(metronome: Metronome) -> Self {
+        use rodio::Source;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            // The output stream must stay on the thread that opened it
+            let (_stream, handle) = match rodio::OutputStream::try_default() {
+                Ok(output) => output,
+                Err(e) => {
+                    log::warn!("No audio output, metronome click disabled: {}", e);
+                    return;
+                }
+            };
+            let mut beat = 0;
+            while metronome.beat_time(beat) < Instant::now() {
+                beat += 1;
+            }
+            loop {
+                std::thread::sleep(metronome.beat_time(beat).saturating_duration_since(Instant::now()));
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                let pitch = match Metronome::beat_cue(beat) {
+                    Cue::Stop => Self::STOP_PITCH,
+                    Cue::Strafe(_) | Cue::Wait => Self::STRAFE_PITCH,
+                };
+                let click = rodio::source::SineWave::new(pitch).take_duration(Self::LENGTH).amplify(0.3);
+                if let Err(e) = handle.play_raw(click) {
+                    log::warn!("Metronome click failed: {}", e);
+                    return;
+                }
+                beat += 1;
+            }
+        });
+        Self { stop }
+    }
+}
+
+#[cfg(feature = "sound")]
+impl Drop for Click {
+    fn drop(&mut self) {
+        self.stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// Without the `sound` feature the metronome is visual only
+#[cfg(not(feature = "sound"))]
+pub struct Click;
+
+#[cfg(not(feature = "sound"))]
+impl Click {
+    pub fn start(_metronome: Metronome) -> Self {
+        log::warn!("Built without the sound feature, the metronome click is disabled");
+        Self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_cues_alternate() {
+        // 120 BPM: a beat every 500ms
+        let start = Instant::now();
+        let metronome = Metronome::new(120.0, start);
+        let cues: Vec<Cue> = (0..5).map(|beat| metronome.cue(start + ms(beat * 500 + 100)).0).collect();
+        assert_eq!(
+            cues,
+            vec![Cue::Strafe(StrafeKey::A), Cue::Stop, Cue::Strafe(StrafeKey::D), Cue::Stop, Cue::Strafe(StrafeKey::A)]
+        );
+        let (_, fraction) = metronome.cue(start + ms(1250));
+        assert!((fraction - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_stop_offset() {
+        let start = Instant::now();
+        let metronome = Metronome::new(120.0, start);
+        // Stop beats at 500ms, 1500ms, ...
+        assert!((metronome.stop_offset(start + ms(520)) - 0.020).abs() < 1e-3);
+        assert!((metronome.stop_offset(start + ms(1440)) + 0.060).abs() < 1e-3);
+        // Halfway between two stops rounds to either, never more than one beat off
+        assert!(metronome.stop_offset(start + ms(1000)).abs() <= 0.5 + 1e-3);
+
+        assert_eq!(rate_offset(-0.020), Quality::Perfect);
+        assert_eq!(rate_offset(0.060), Quality::Good);
+        assert_eq!(rate_offset(0.150), Quality::Failed);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::simulation::{SimulationParams, ACCURATE_SPEED_RATIO, DEFAULT_MAX_SPEED};
+use crate::state::TimingWindows;
+use serde::{Deserialize, Serialize};
+
+/// Weapon profile: sets the movement speeds and the counter-strafe timing windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, clap::ValueEnum)]
+pub enum Weapon {
+    // Lowercase as on the command line, for the config file
+    #[default]
+    #[serde(alias = "rifle")]
+    Rifle,
+    #[serde(alias = "awp")]
+    Awp,
+    #[serde(alias = "pistol")]
+    Pistol,
+    #[serde(alias = "knife")]
+    Knife,
+}
+
+impl Weapon {
+    pub const ALL: [Weapon; 4] = [Weapon::Rifle, Weapon::Awp, Weapon::Pistol, Weapon::Knife];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weapon::Rifle => "Rifle",
+            Weapon::Awp => "AWP",
+            Weapon::Pistol => "Pistol",
+            Weapon::Knife => "Knife",
+        }
+    }
+
+    /// Hold-time windows for this weapon
+    pub fn timing(&self) -> TimingWindows {
+        match self {
+            // AK-47/M4: the classic 80ms target
+            Weapon::Rifle => TimingWindows::DEFAULT,
+            // Needs to be nearly stopped, so a longer and tighter counter
+            Weapon::Awp => TimingWindows {
+                optimal: 0.100,
+                min: 0.080,
+                max: 0.140,
+                perfect_tolerance: 0.012,
+                ..TimingWindows::DEFAULT
+            },
+            // Forgiving accuracy threshold, a short tap is enough
+            Weapon::Pistol => TimingWindows {
+                optimal: 0.065,
+                min: 0.045,
+                max: 0.100,
+                perfect_tolerance: 0.015,
+                ..TimingWindows::DEFAULT
+            },
+            // No accuracy to reach, just stop
+            Weapon::Knife => TimingWindows {
+                optimal: 0.080,
+                min: 0.050,
+                max: 0.130,
+                perfect_tolerance: 0.025,
+                ..TimingWindows::DEFAULT
+            },
+        }
+    }
+
+    /// Max running speed and accurate-speed threshold (units/s)
+    pub fn movement(&self) -> SimulationParams {
+        match self {
+            Weapon::Rifle => SimulationParams {
+                max_speed: DEFAULT_MAX_SPEED,
+                accurate_speed: DEFAULT_MAX_SPEED * ACCURATE_SPEED_RATIO,
+            },
+            Weapon::Awp => SimulationParams {
+                max_speed: 200.0,
+                accurate_speed: 40.0,
+            },
+            Weapon::Pistol => SimulationParams {
+                max_speed: 240.0,
+                accurate_speed: 96.0,
+            },
+            Weapon::Knife => SimulationParams {
+                max_speed: 250.0,
+                accurate_speed: 250.0 * ACCURATE_SPEED_RATIO,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{evaluate_hold_time, Quality};
+
+    #[test]
+    fn test_rifle_matches_default_timing() {
+        assert_eq!(Weapon::Rifle.timing(), TimingWindows::DEFAULT);
+        assert_eq!(Weapon::Rifle.movement(), SimulationParams::default());
+    }
+
+    #[test]
+    fn test_profiles_evaluate_differently() {
+        // 60ms is a good rifle counter but too short for the AWP
+        assert_eq!(evaluate_hold_time(0.060, &Weapon::Rifle.timing()), Quality::Good);
+        assert_eq!(evaluate_hold_time(0.060, &Weapon::Awp.timing()), Quality::Failed);
+        assert_eq!(evaluate_hold_time(0.060, &Weapon::Pistol.timing()), Quality::Perfect);
+    }
+
+    #[test]
+    fn test_windows_are_consistent() {
+        for weapon in Weapon::ALL {
+            let timing = weapon.timing();
+            assert!(timing.min < timing.optimal && timing.optimal < timing.max, "{}", weapon.name());
+            let movement = weapon.movement();
+            assert!(movement.accurate_speed < movement.max_speed, "{}", weapon.name());
+        }
+    }
+}
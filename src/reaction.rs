@@ -0,0 +1,241 @@
+use crate::metronome::Cue;
+use crate::state::{Quality, StrafeKey};
+use std::time::{Duration, Instant};
+
+// Random wait before each cue (seconds)
+const STRAFE_DELAY: (f32, f32) = (0.8, 2.0);
+const STOP_DELAY: (f32, f32) = (0.3, 1.2);
+
+/// A STOP cue without a counter-strafe within this long is missed
+pub const MISS_AFTER: f32 = 1.0;
+
+// Reaction ratings (seconds from the STOP cue to the counter key)
+pub const FAST_REACTION: f32 = 0.200;
+pub const SLOW_REACTION: f32 = 0.300;
+
+/// How a STOP cue was answered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// Counter key went down this many seconds after the cue
+    Reaction(f32),
+    /// Countered before the cue appeared
+    Early,
+    /// No counter-strafe in time
+    Missed,
+}
+
+/// Latencies of an answered reaction prompt in seconds, as saved with its attempt
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReactionTimes {
+    /// Strafe cue -> first press of the cued key
+    pub strafe: Option<f32>,
+    /// STOP cue -> counter key
+    pub stop: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    /// Nothing shown until `at`
+    Waiting { at: Instant },
+    /// Strafe cue shown since `shown`, waiting for the player to move that way
+    Strafe { key: StrafeKey, shown: Instant },
+    /// Player is strafing as asked `strafe` seconds after the cue, STOP appears at `at`
+    Holding { key: StrafeKey, at: Instant, strafe: f32 },
+    /// STOP shown since `shown`, waiting for the counter key
+    Stop { shown: Instant, strafe: f32 },
+}
+
+/// Reaction prompts: after a random wait the player is told which way to strafe, then
+/// after another random wait to stop. Scores the latency from each cue to the key answering it.
+pub struct ReactionMode {
+    phase: Phase,
+    rng: fastrand::Rng,
+}
+
+impl ReactionMode {
+    pub fn new(now: Instant) -> Self {
+        Self::with_rng(fastrand::Rng::new(), now)
+    }
+
+    /// Repeatable delays and directions, for tests
+    pub fn with_seed(seed: u64, now: Instant) -> Self {
+        Self::with_rng(fastrand::Rng::with_seed(seed), now)
+    }
+
+    fn with_rng(rng: fastrand::Rng, now: Instant) -> Self {
+        let mut mode = Self {
+            phase: Phase::Waiting { at: now },
+            rng,
+        };
+        mode.reset(now);
+        mode
+    }
+
+    fn delay(&mut self, (min, max): (f32, f32)) -> Duration {
+        Duration::from_secs_f32(min + self.rng.f32() * (max - min))
+    }
+
+    fn reset(&mut self, now: Instant) {
+        self.phase = Phase::Waiting {
+            at: now + self.delay(STRAFE_DELAY),
+        };
+    }
+
+    /// What to show the player
+    pub fn cue(&self) -> Cue {
+        match self.phase {
+            Phase::Waiting { .. } => Cue::Wait,
+            Phase::Strafe { key, .. } | Phase::Holding { key, .. } => Cue::Strafe(key),
+            Phase::Stop { .. } => Cue::Stop,
+        }
+    }
+
+    /// A movement key went down; pressing the cued direction arms the STOP cue
+    pub fn on_key_press(&mut self, key: StrafeKey, now: Instant) {
+        if let Phase::Strafe { key: cued, shown } = self.phase
+            && key == cued
+        {
+            self.phase = Phase::Holding {
+                key,
+                at: now + self.delay(STOP_DELAY),
+                strafe: now.duration_since(shown).as_secs_f32(),
+            };
+        }
+    }
+
+    /// A movement key went up; letting go of the cued direction before STOP shows starts a new prompt
+    pub fn on_key_release(&mut self, key: StrafeKey, now: Instant) {
+        if let Phase::Holding { key: held, .. } = self.phase
+            && key == held
+        {
+            self.reset(now);
+        }
+    }
+
+    /// A stop finished with the counter key pressed at `counter_time`. Returns how the STOP cue
+    /// was answered and how many seconds the strafe cue took to be followed.
+    /// Stops while no STOP cue is pending are free play and not scored.
+    pub fn on_stop(&mut self, counter_time: Instant, now: Instant) -> Option<(Outcome, f32)> {
+        let answer = match self.phase {
+            Phase::Holding { strafe, .. } => (Outcome::Early, strafe),
+            Phase::Stop { shown, strafe } if counter_time < shown => (Outcome::Early, strafe),
+            Phase::Stop { shown, strafe } => (Outcome::Reaction(counter_time.duration_since(shown).as_secs_f32()), strafe),
+            Phase::Waiting { .. } | Phase::Strafe { .. } => return None,
+        };
+        self.reset(now);
+        Some(answer)
+    }
+
+    /// Show the next cue once its wait is over; reports a STOP cue that went unanswered
+    pub fn update(&mut self, now: Instant) -> Option<Outcome> {
+        match self.phase {
+            Phase::Waiting { at } if now >= at => {
+                let key = if self.rng.bool() { StrafeKey::A } else { StrafeKey::D };
+                self.phase = Phase::Strafe { key, shown: now };
+            }
+            // The cue becomes visible with this frame, so latency counts from now rather than `at`
+            Phase::Holding { at, strafe, .. } if now >= at => self.phase = Phase::Stop { shown: now, strafe },
+            Phase::Stop { shown, .. } if now.duration_since(shown).as_secs_f32() > MISS_AFTER => {
+                self.reset(now);
+                return Some(Outcome::Missed);
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Rate a reaction time
+pub fn rate_reaction(latency: f32) -> Quality {
+    if latency <= FAST_REACTION {
+        Quality::Perfect
+    } else if latency <= SLOW_REACTION {
+        Quality::Good
+    } else {
+        Quality::Failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// Advance until the strafe cue shows, returning its key and the time
+    fn strafe_cue(mode: &mut ReactionMode, start: Instant) -> (StrafeKey, Instant) {
+        let now = start + Duration::from_secs_f32(STRAFE_DELAY.1);
+        mode.update(now);
+        match mode.cue() {
+            Cue::Strafe(key) => (key, now),
+            cue => panic!("expected a strafe cue, got {:?}", cue),
+        }
+    }
+
+    #[test]
+    fn test_reaction_from_stop_cue() {
+        let start = Instant::now();
+        let mut mode = ReactionMode::with_seed(7, start);
+        assert_eq!(mode.cue(), Cue::Wait);
+        assert_eq!(mode.update(start), None);
+        assert_eq!(mode.cue(), Cue::Wait);
+
+        let (key, now) = strafe_cue(&mut mode, start);
+        // The wrong direction does not arm the STOP cue
+        mode.on_key_press(key.opposite(), now);
+        mode.update(now + ms(2000));
+        assert_eq!(mode.cue(), Cue::Strafe(key));
+
+        mode.on_key_press(key, now + ms(2000));
+        let shown = now + ms(2000) + Duration::from_secs_f32(STOP_DELAY.1);
+        mode.update(shown);
+        assert_eq!(mode.cue(), Cue::Stop);
+
+        let answer = mode.on_stop(shown + ms(230), shown + ms(310));
+        assert!(matches!(answer, Some((Outcome::Reaction(latency), _)) if (latency - 0.230).abs() < 1e-3));
+        // The strafe cue counts from when it showed to the first press of the cued key
+        assert!(matches!(answer, Some((_, strafe)) if (strafe - 2.0).abs() < 1e-3));
+        assert_eq!(mode.cue(), Cue::Wait);
+        assert_eq!(rate_reaction(0.230), Quality::Good);
+    }
+
+    #[test]
+    fn test_early_and_missed_stops() {
+        let start = Instant::now();
+        let mut mode = ReactionMode::with_seed(3, start);
+        // Free stops before any cue are not scored
+        assert_eq!(mode.on_stop(start, start), None);
+
+        let (key, now) = strafe_cue(&mut mode, start);
+        mode.on_key_press(key, now + ms(150));
+        assert!(matches!(mode.on_stop(now + ms(200), now + ms(280)), Some((Outcome::Early, strafe)) if (strafe - 0.150).abs() < 1e-3));
+
+        let (key, now) = strafe_cue(&mut mode, now + ms(280));
+        mode.on_key_press(key, now);
+        let shown = now + Duration::from_secs_f32(STOP_DELAY.1);
+        mode.update(shown);
+        assert_eq!(mode.update(shown + ms(500)), None);
+        assert_eq!(mode.update(shown + ms(1100)), Some(Outcome::Missed));
+        assert_eq!(mode.cue(), Cue::Wait);
+    }
+
+    #[test]
+    fn test_releasing_the_cued_key_restarts_the_prompt() {
+        let start = Instant::now();
+        let mut mode = ReactionMode::with_seed(5, start);
+        let (key, now) = strafe_cue(&mut mode, start);
+        mode.on_key_press(key, now + ms(180));
+        // Letting go of the other key changes nothing
+        mode.on_key_release(key.opposite(), now + ms(200));
+        assert_eq!(mode.cue(), Cue::Strafe(key));
+
+        mode.on_key_release(key, now + ms(300));
+        assert_eq!(mode.cue(), Cue::Wait);
+        // STOP never shows for the abandoned prompt, and a stop now is free play
+        mode.update(now + ms(1000));
+        assert_eq!(mode.cue(), Cue::Wait);
+        assert_eq!(mode.on_stop(now + ms(400), now + ms(450)), None);
+    }
+}
@@ -0,0 +1,417 @@
+use crate::diagonal::{MovementState, StopResult};
+use crate::events::GameEventKind;
+use crate::keymap::Action;
+use crate::profiles::Weapon;
+use crate::state::{CompletionResult, ErrorKind, Quality, StrafeKey, TimingWindows};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// File layout: header (magic, version, weapon, overlap window), then one entry per record:
+// tag byte, microseconds since the previous entry (u32 LE), payload. All numbers little endian.
+const MAGIC: &[u8; 4] = b"CS2R";
+const VERSION: u8 = 1;
+
+const TAG_PRESS: u8 = 0;
+const TAG_RELEASE: u8 = 1;
+const TAG_RESULT: u8 = 2;
+const TAG_WEAPON: u8 = 3;
+const TAG_OVERLAP_WINDOW: u8 = 4;
+
+const STRAFE_KEYS: [StrafeKey; 4] = [StrafeKey::A, StrafeKey::D, StrafeKey::W, StrafeKey::S];
+const QUALITIES: [Quality; 3] = [Quality::Perfect, Quality::Good, Quality::Failed];
+const ERROR_KINDS: [ErrorKind; 3] = [ErrorKind::TooFast, ErrorKind::TooSlow, ErrorKind::BothKeys];
+
+/// Whether a file starts like a recording (as opposed to a JSON lines history)
+pub fn is_recording(path: &Path) -> bool {
+    let mut magic = [0; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| &magic == MAGIC)
+}
+
+/// Axis result as recorded, enough to compare against a re-scored run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordedResult {
+    pub original_key: StrafeKey,
+    pub counter_key: StrafeKey,
+    pub hold_time: f32,
+    pub quality: Quality,
+    pub error_kind: Option<ErrorKind>,
+}
+
+impl From<&CompletionResult> for RecordedResult {
+    fn from(result: &CompletionResult) -> Self {
+        Self {
+            original_key: result.original_key,
+            counter_key: result.counter_key,
+            hold_time: result.hold_time,
+            quality: result.quality,
+            error_kind: result.error_kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    /// Key press or release as delivered by the listener
+    Input(GameEventKind),
+    Result(RecordedResult),
+    Weapon(Weapon),
+    /// Tolerated overlap in seconds
+    OverlapWindow(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entry {
+    /// Time since the recording started
+    pub offset: Duration,
+    pub kind: EntryKind,
+}
+
+/// A recorded session: the raw input with capture timestamps and the results it produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    /// Weapon and overlap window when the recording started
+    pub weapon: Weapon,
+    pub overlap_window: f32,
+    pub entries: Vec<Entry>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::read(&mut reader).map_err(|e| format!("Invalid recording {}: {}", path.display(), e).into())
+    }
+
+    fn read(reader: &mut impl Read) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err("not a recording".into());
+        }
+        let version = read_u8(reader)?;
+        if version != VERSION {
+            return Err(format!("unsupported version {}", version).into());
+        }
+        let weapon = decode(&Weapon::ALL, read_u8(reader)?)?;
+        let overlap_window = micros(read_u32(reader)?);
+
+        let mut entries = Vec::new();
+        let mut offset = Duration::ZERO;
+        loop {
+            // A missing tag is the regular end of the file; a truncated entry is not
+            let mut tag = [0; 1];
+            if reader.read(&mut tag)? == 0 {
+                break;
+            }
+            offset += Duration::from_micros(read_u32(reader)? as u64);
+
+            let kind = match tag[0] {
+                TAG_PRESS => EntryKind::Input(GameEventKind::Press(decode(&Action::ALL, read_u8(reader)?)?)),
+                TAG_RELEASE => EntryKind::Input(GameEventKind::Release(decode(&Action::ALL, read_u8(reader)?)?)),
+                TAG_RESULT => EntryKind::Result(RecordedResult {
+                    original_key: decode(&STRAFE_KEYS, read_u8(reader)?)?,
+                    counter_key: decode(&STRAFE_KEYS, read_u8(reader)?)?,
+                    hold_time: micros(read_u32(reader)?),
+                    quality: decode(&QUALITIES, read_u8(reader)?)?,
+                    error_kind: match read_u8(reader)? {
+                        0 => None,
+                        index => Some(decode(&ERROR_KINDS, index - 1)?),
+                    },
+                }),
+                TAG_WEAPON => EntryKind::Weapon(decode(&Weapon::ALL, read_u8(reader)?)?),
+                TAG_OVERLAP_WINDOW => EntryKind::OverlapWindow(micros(read_u32(reader)?)),
+                tag => return Err(format!("unknown entry {}", tag).into()),
+            };
+            entries.push(Entry { offset, kind });
+        }
+
+        Ok(Self {
+            weapon,
+            overlap_window,
+            entries,
+        })
+    }
+
+    /// Results stored while recording
+    pub fn results(&self) -> impl Iterator<Item = (Duration, &RecordedResult)> {
+        self.entries.iter().filter_map(|entry| match &entry.kind {
+            EntryKind::Result(result) => Some((entry.offset, result)),
+            _ => None,
+        })
+    }
+
+    /// Feed the recorded input through a fresh state machine, instantly, returning every stop.
+    /// `timing` gives the windows per weapon, so recorded weapon switches are respected.
+    pub fn rescore(&self, timing: impl Fn(Weapon) -> TimingWindows) -> Vec<(Duration, StopResult)> {
+        // Any instant works as the base, only the differences matter
+        let start = Instant::now();
+        let mut movement = MovementState::new();
+        movement.set_timing(timing(self.weapon));
+        movement.set_overlap_window(self.overlap_window);
+
+        let mut results = Vec::new();
+        let mut collect = |stop: Option<StopResult>, offset: Duration| results.extend(stop.map(|stop| (offset, stop)));
+
+        let mut last = Duration::ZERO;
+        for entry in &self.entries {
+            let now = start + entry.offset;
+            last = entry.offset;
+            // The live app checks every frame, so a timeout happens before the next input
+            collect(movement.check_timeout(now), entry.offset);
+
+            match entry.kind {
+                EntryKind::Input(GameEventKind::Press(action)) => {
+                    if let Some(key) = action.strafe_key() {
+                        collect(movement.on_key_press(key, now), entry.offset);
+                    }
+                }
+                EntryKind::Input(GameEventKind::Release(action)) => {
+                    if let Some(key) = action.strafe_key() {
+                        collect(movement.on_key_release(key, now), entry.offset);
+                    }
+                }
+                EntryKind::Weapon(weapon) => movement.set_timing(timing(weapon)),
+                EntryKind::OverlapWindow(window) => movement.set_overlap_window(window),
+                EntryKind::Input(GameEventKind::KeyCaptured(_) | GameEventKind::Control(_)) | EntryKind::Result(_) => {}
+            }
+        }
+
+        // Let a pending attempt time out after the last entry
+        let end = last + Duration::from_secs(1);
+        collect(movement.check_timeout(start + end), end);
+        results
+    }
+}
+
+/// Writes a recording while training. Entries are buffered; results flush the file
+/// so an abrupt exit loses at most the input since the last stop.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    start: Instant,
+    last: Duration,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, weapon: Weapon, overlap_window: f32) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION, encode(&Weapon::ALL, weapon)])?;
+        writer.write_all(&to_micros(overlap_window).to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            last: Duration::ZERO,
+        })
+    }
+
+    /// Record a key press/release captured at `time`. Quit and rebinding captures are
+    /// not recorded, so a replay does not end early or change the bindings.
+    pub fn record_input(&mut self, kind: GameEventKind, time: Instant) -> std::io::Result<()> {
+        let (tag, action) = match kind {
+            GameEventKind::Press(Action::Quit) | GameEventKind::Release(Action::Quit) => return Ok(()),
+            GameEventKind::Press(action) => (TAG_PRESS, action),
+            GameEventKind::Release(action) => (TAG_RELEASE, action),
+            // Setting changes are recorded by the trainer when it applies them
+            GameEventKind::KeyCaptured(_) | GameEventKind::Control(_) => return Ok(()),
+        };
+        self.write_entry(tag, time, &[encode(&Action::ALL, action)])
+    }
+
+    pub fn record_result(&mut self, result: &CompletionResult, time: Instant) -> std::io::Result<()> {
+        let mut payload = vec![
+            encode(&STRAFE_KEYS, result.original_key),
+            encode(&STRAFE_KEYS, result.counter_key),
+        ];
+        payload.extend(to_micros(result.hold_time).to_le_bytes());
+        payload.push(encode(&QUALITIES, result.quality));
+        payload.push(result.error_kind.map_or(0, |kind| encode(&ERROR_KINDS, kind) + 1));
+        self.write_entry(TAG_RESULT, time, &payload)?;
+        self.writer.flush()
+    }
+
+    pub fn record_weapon(&mut self, weapon: Weapon) -> std::io::Result<()> {
+        self.write_entry(TAG_WEAPON, Instant::now(), &[encode(&Weapon::ALL, weapon)])
+    }
+
+    pub fn record_overlap_window(&mut self, overlap_window: f32) -> std::io::Result<()> {
+        self.write_entry(TAG_OVERLAP_WINDOW, Instant::now(), &to_micros(overlap_window).to_le_bytes())
+    }
+
+    fn write_entry(&mut self, tag: u8, time: Instant, payload: &[u8]) -> std::io::Result<()> {
+        // Entries are stored in order; an event captured before the previous entry gets delta 0
+        let offset = time.saturating_duration_since(self.start).max(self.last);
+        let delta = (offset - self.last).as_micros().min(u32::MAX as u128) as u32;
+        self.last = offset;
+
+        self.writer.write_all(&[tag])?;
+        self.writer.write_all(&delta.to_le_bytes())?;
+        self.writer.write_all(payload)
+    }
+}
+
+fn encode<T: PartialEq>(values: &[T], value: T) -> u8 {
+    values.iter().position(|v| *v == value).unwrap_or_default() as u8
+}
+
+fn decode<T: Copy>(values: &[T], index: u8) -> Result<T, Box<dyn std::error::Error>> {
+    values
+        .get(index as usize)
+        .copied()
+        .ok_or_else(|| format!("invalid value {}", index).into())
+}
+
+fn to_micros(seconds: f32) -> u32 {
+    (seconds * 1_000_000.0).round() as u32
+}
+
+fn micros(value: u32) -> f32 {
+    value as f32 / 1_000_000.0
+}
+
+fn read_u8(reader: &mut impl Read) -> std::io::Result<u8> {
+    let mut buffer = [0; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer)?;
+    Ok(u32::from_le_bytes(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("cs2st-{}-{}.cs2r", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// A -> D counter-strafe: A held 200ms, D pressed 10ms after the release and held `hold_ms`
+    fn record_counter_strafe(recorder: &mut Recorder, start: Instant, hold_ms: u64) {
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        recorder.record_input(GameEventKind::Press(Action::StrafeLeft), at(0)).unwrap();
+        recorder.record_input(GameEventKind::Release(Action::StrafeLeft), at(200)).unwrap();
+        recorder.record_input(GameEventKind::Press(Action::StrafeRight), at(210)).unwrap();
+        recorder.record_input(GameEventKind::Release(Action::StrafeRight), at(210 + hold_ms)).unwrap();
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = temp_path("round-trip");
+        let mut recorder = Recorder::create(&path, Weapon::Awp, 0.020).unwrap();
+        let start = recorder.start;
+        record_counter_strafe(&mut recorder, start, 85);
+
+        let result = CompletionResult {
+            original_key: StrafeKey::A,
+            counter_key: StrafeKey::D,
+            hold_time: 0.085,
+            quality: Quality::Failed,
+            error_kind: Some(ErrorKind::TooFast),
+            error_message: None,
+            transition: None,
+            stop: None,
+            counter_time: None,
+        };
+        recorder.record_result(&result, start + Duration::from_millis(295)).unwrap();
+        drop(recorder);
+
+        assert!(is_recording(&path));
+        let recording = Recording::load(&path).unwrap();
+        assert_eq!(recording.weapon, Weapon::Awp);
+        assert!((recording.overlap_window - 0.020).abs() < 1e-6);
+        assert_eq!(recording.entries.len(), 5);
+        assert_eq!(recording.entries[2].offset, Duration::from_millis(210));
+        assert_eq!(
+            recording.entries[1].kind,
+            EntryKind::Input(GameEventKind::Release(Action::StrafeLeft))
+        );
+
+        let (offset, recorded) = recording.results().next().unwrap();
+        assert_eq!(offset, Duration::from_millis(295));
+        assert_eq!(*recorded, RecordedResult::from(&result));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rescore_with_other_thresholds() {
+        let path = temp_path("rescore");
+        let mut recorder = Recorder::create(&path, Weapon::Rifle, 0.0).unwrap();
+        let start = recorder.start;
+        record_counter_strafe(&mut recorder, start, 70);
+        recorder.record_input(GameEventKind::Press(Action::Quit), start + Duration::from_millis(400)).unwrap();
+        drop(recorder);
+
+        let recording = Recording::load(&path).unwrap();
+        let rifle = recording.rescore(|weapon| weapon.timing());
+        assert_eq!(rifle.len(), 1);
+        assert_eq!(rifle[0].0, Duration::from_millis(280));
+        let StopResult::Single(result) = &rifle[0].1 else { panic!("expected a single-axis stop") };
+        assert!((result.hold_time - 0.070).abs() < 1e-4);
+        assert_eq!(result.quality, Quality::Perfect);
+        assert_eq!(recording.entries.len(), 4, "quit is not recorded");
+
+        // 70ms is too short for the AWP windows
+        let awp = recording.rescore(|_| Weapon::Awp.timing());
+        assert_eq!(awp[0].1.quality(), Quality::Failed);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rescore_keeps_diagonal_stops_together() {
+        let path = temp_path("rescore-diagonal");
+        let mut recorder = Recorder::create(&path, Weapon::Rifle, 0.0).unwrap();
+        let start = recorder.start;
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        for (kind, ms) in [
+            (GameEventKind::Press(Action::StrafeForward), 0),
+            (GameEventKind::Press(Action::StrafeLeft), 10),
+            (GameEventKind::Release(Action::StrafeForward), 300),
+            (GameEventKind::Release(Action::StrafeLeft), 302),
+            (GameEventKind::Press(Action::StrafeBack), 305),
+            (GameEventKind::Press(Action::StrafeRight), 306),
+            (GameEventKind::Release(Action::StrafeRight), 386),
+            (GameEventKind::Release(Action::StrafeBack), 405),
+        ] {
+            recorder.record_input(kind, at(ms)).unwrap();
+        }
+        drop(recorder);
+
+        let rescored = Recording::load(&path).unwrap().rescore(|weapon| weapon.timing());
+        assert_eq!(rescored.len(), 1);
+        assert_eq!(rescored[0].0, Duration::from_millis(405));
+        assert!(matches!(rescored[0].1, StopResult::Diagonal(_)));
+        assert_eq!(rescored[0].1.axes().count(), 2);
+        assert_eq!(rescored[0].1.quality(), Quality::Good);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let path = temp_path("invalid");
+        std::fs::write(&path, "{\"timestamp\": 1}\n").unwrap();
+        assert!(!is_recording(&path));
+        assert!(Recording::load(&path).is_err());
+
+        // Truncated entry
+        std::fs::write(&path, [b'C', b'S', b'2', b'R', VERSION, 0, 0, 0, 0, 0, TAG_PRESS, 1]).unwrap();
+        assert!(Recording::load(&path).is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
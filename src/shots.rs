@@ -0,0 +1,130 @@
+use crate::state::Quality;
+use std::time::Instant;
+
+// Shot timing relative to the end of a counter-strafe
+pub const SHOT_LATE_AFTER: f32 = 0.100;    // Accurate shots later than 100ms waste time
+pub const SHOT_WINDOW: f32 = 0.500;        // Shots more than 500ms after a stop are not part of the drill
+
+/// When a shot was fired relative to the stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShotTiming {
+    /// Still too fast to be accurate
+    TooEarly,
+    OnTime,
+    /// Accurate, but the time after the stop was wasted
+    Late,
+}
+
+impl ShotTiming {
+    pub fn quality(&self) -> Quality {
+        match self {
+            ShotTiming::OnTime => Quality::Perfect,
+            ShotTiming::Late => Quality::Good,
+            ShotTiming::TooEarly => Quality::Failed,
+        }
+    }
+}
+
+/// Evaluated shot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShotResult {
+    pub timing: ShotTiming,
+    /// Seconds since the counter key was released, `None` if no stop preceded the shot
+    pub since_stop: Option<f32>,
+    /// Simulated speed when the shot was fired (units/s)
+    pub speed: f32,
+}
+
+/// Pairs fire events with the most recent counter-strafe
+#[derive(Debug, Clone, Default)]
+pub struct ShotTracker {
+    last_stop: Option<Instant>,
+}
+
+impl ShotTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A counter-strafe ended at `time` (counter key released)
+    pub fn on_stop(&mut self, time: Instant) {
+        self.last_stop = Some(time);
+    }
+
+    /// Evaluate a shot fired at `now`. Speed and accuracy come from the movement simulation.
+    /// Shots while standing still with no recent stop are ignored.
+    pub fn on_fire(&mut self, now: Instant, speed: f32, accurate: bool) -> Option<ShotResult> {
+        let since_stop = self
+            .last_stop
+            .take()
+            .map(|stop| now.saturating_duration_since(stop).as_secs_f32());
+
+        let timing = if !accurate {
+            ShotTiming::TooEarly
+        } else {
+            match since_stop {
+                Some(since) if since <= SHOT_LATE_AFTER => ShotTiming::OnTime,
+                Some(since) if since <= SHOT_WINDOW => ShotTiming::Late,
+                _ => return None,
+            }
+        };
+
+        Some(ShotResult {
+            timing,
+            since_stop,
+            speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_shot_classification() {
+        let start = Instant::now();
+        let mut shots = ShotTracker::new();
+
+        shots.on_stop(at(start, 0));
+        let result = shots.on_fire(at(start, 40), 10.0, true).unwrap();
+        assert_eq!(result.timing, ShotTiming::OnTime);
+        assert!((result.since_stop.unwrap() - 0.040).abs() < 1e-4);
+
+        shots.on_stop(at(start, 1000));
+        assert_eq!(shots.on_fire(at(start, 1250), 0.0, true).unwrap().timing, ShotTiming::Late);
+
+        shots.on_stop(at(start, 2000));
+        assert_eq!(shots.on_fire(at(start, 2010), 150.0, false).unwrap().timing, ShotTiming::TooEarly);
+    }
+
+    #[test]
+    fn test_shots_without_stop_are_ignored() {
+        let start = Instant::now();
+        let mut shots = ShotTracker::new();
+
+        assert!(shots.on_fire(start, 0.0, true).is_none());
+
+        // Only the first shot after a stop counts
+        shots.on_stop(start);
+        assert!(shots.on_fire(at(start, 50), 0.0, true).is_some());
+        assert!(shots.on_fire(at(start, 80), 0.0, true).is_none());
+
+        shots.on_stop(start);
+        assert!(shots.on_fire(at(start, 900), 0.0, true).is_none());
+    }
+
+    #[test]
+    fn test_moving_shot_is_too_early() {
+        let mut shots = ShotTracker::new();
+        let result = shots.on_fire(Instant::now(), 215.0, false).unwrap();
+
+        assert_eq!(result.timing, ShotTiming::TooEarly);
+        assert!(result.since_stop.is_none());
+    }
+}
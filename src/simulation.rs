@@ -0,0 +1,326 @@
+use crate::state::{Axis, StrafeKey, TIMEOUT_NO_COUNTER};
+use std::time::Instant;
+
+// CS2 movement convars
+pub const SV_ACCELERATE: f32 = 5.5;
+pub const SV_FRICTION: f32 = 5.2;
+pub const SV_STOPSPEED: f32 = 80.0;
+
+// Rifle (AK-47) running speed; weapons are accurate below 34% of their max speed
+pub const DEFAULT_MAX_SPEED: f32 = 215.0;
+pub const ACCURATE_SPEED_RATIO: f32 = 0.34;
+
+const STEP: f32 = 0.001;            // 1ms integration step
+const MAX_SETTLE_TIME: f32 = 1.0;   // Stop projecting a stop after 1 second
+
+/// Weapon dependent movement parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationParams {
+    pub max_speed: f32,
+    pub accurate_speed: f32,
+}
+
+impl Default for SimulationParams {
+    fn default() -> Self {
+        Self {
+            max_speed: DEFAULT_MAX_SPEED,
+            accurate_speed: DEFAULT_MAX_SPEED * ACCURATE_SPEED_RATIO,
+        }
+    }
+}
+
+/// Simulated outcome of one stop on an axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StopAnalysis {
+    /// Seconds from releasing the movement key until speed is below the accurate threshold
+    pub time_to_accurate: Option<f32>,
+    /// Peak speed reached in the opposite direction (units/s), 0.0 if none
+    pub overshoot: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StopTracking {
+    direction: f32,
+    elapsed: f32,
+    countered: bool,
+    accurate_after: Option<f32>,
+    peak_reverse: f32,
+}
+
+impl StopTracking {
+    fn new(direction: f32) -> Self {
+        Self {
+            direction,
+            elapsed: 0.0,
+            countered: false,
+            accurate_after: None,
+            peak_reverse: 0.0,
+        }
+    }
+}
+
+/// Integrates CS2-style ground movement (friction, stop-speed, acceleration) from the raw
+/// key timeline, so a stop can be judged by when the player could actually shoot accurately.
+#[derive(Debug, Clone)]
+pub struct MovementSimulation {
+    params: SimulationParams,
+    velocity: [f32; 2],
+    held: [bool; 4],
+    last_update: Option<Instant>,
+    stops: [Option<StopTracking>; 2],
+}
+
+impl MovementSimulation {
+    pub fn new(params: SimulationParams) -> Self {
+        Self {
+            params,
+            velocity: [0.0; 2],
+            held: [false; 4],
+            last_update: None,
+            stops: [None; 2],
+        }
+    }
+
+    pub fn params(&self) -> SimulationParams {
+        self.params
+    }
+
+    /// Switch weapon parameters; current velocity is kept
+    pub fn set_params(&mut self, params: SimulationParams) {
+        self.params = params;
+    }
+
+    /// Handle key press event
+    pub fn on_key_press(&mut self, key: StrafeKey, now: Instant) {
+        self.update(now);
+        self.held[key_index(key)] = true;
+
+        let axis = axis_index(key.axis());
+        if let Some(stop) = &mut self.stops[axis] {
+            let is_counter = direction(key) != stop.direction && !stop.countered;
+            if is_counter && stop.elapsed <= TIMEOUT_NO_COUNTER {
+                stop.countered = true;
+            } else {
+                // Same key again or a fresh strafe: the previous stop is over
+                self.stops[axis] = None;
+            }
+        }
+    }
+
+    /// Handle key release event; releasing a movement key starts tracking a stop
+    pub fn on_key_release(&mut self, key: StrafeKey, now: Instant) {
+        self.update(now);
+        self.held[key_index(key)] = false;
+
+        let axis = axis_index(key.axis());
+        let is_counter_release = self.stops[axis]
+            .is_some_and(|stop| stop.direction != direction(key));
+        if !is_counter_release {
+            self.stops[axis] = Some(StopTracking::new(direction(key)));
+        }
+    }
+
+    /// Advance the simulation to `now`. Earlier instants are ignored.
+    pub fn update(&mut self, now: Instant) {
+        let Some(last) = self.last_update else {
+            self.last_update = Some(now);
+            return;
+        };
+        if now <= last {
+            return;
+        }
+
+        let mut remaining = now.duration_since(last).as_secs_f32();
+        while remaining > 0.0 {
+            if self.is_at_rest() {
+                break;
+            }
+            let dt = remaining.min(STEP);
+            self.step(dt);
+            remaining -= dt;
+        }
+        self.last_update = Some(now);
+    }
+
+    /// Finish the stop on an axis. If the player is not accurate yet, the rest of the stop
+    /// is projected assuming no further input.
+    pub fn take_stop_analysis(&mut self, axis: Axis) -> Option<StopAnalysis> {
+        let index = axis_index(axis);
+        let stop = self.stops[index].take()?;
+
+        let stop = if stop.accurate_after.is_some() {
+            stop
+        } else {
+            let mut projection = self.clone();
+            projection.stops[index] = Some(stop);
+            let mut projected = 0.0;
+            while projected < MAX_SETTLE_TIME
+                && projection.stops[index].is_some_and(|s| s.accurate_after.is_none())
+            {
+                projection.step(STEP);
+                projected += STEP;
+            }
+            projection.stops[index].unwrap_or(stop)
+        };
+
+        Some(StopAnalysis {
+            time_to_accurate: stop.accurate_after,
+            overshoot: stop.peak_reverse,
+        })
+    }
+
+    /// Current horizontal speed (units/s)
+    pub fn speed(&self) -> f32 {
+        self.velocity[0].hypot(self.velocity[1])
+    }
+
+    /// Whether the current speed allows accurate shooting
+    pub fn is_accurate(&self) -> bool {
+        self.speed() < self.params.accurate_speed
+    }
+
+    fn is_at_rest(&self) -> bool {
+        self.velocity == [0.0; 2]
+            && !self.held.iter().any(|held| *held)
+            && self.stops.iter().all(Option::is_none)
+    }
+
+    /// One integration step: friction first, then acceleration (Source movement order)
+    fn step(&mut self, dt: f32) {
+        let speed = self.speed();
+        if speed > 0.0 {
+            let control = speed.max(SV_STOPSPEED);
+            let new_speed = (speed - control * SV_FRICTION * dt).max(0.0);
+            let scale = new_speed / speed;
+            self.velocity[0] *= scale;
+            self.velocity[1] *= scale;
+        }
+
+        let wish = [self.wish_input(Axis::Horizontal), self.wish_input(Axis::Vertical)];
+        let wish_len = wish[0].hypot(wish[1]);
+        if wish_len > 0.0 {
+            let wish_dir = [wish[0] / wish_len, wish[1] / wish_len];
+            let wish_speed = self.params.max_speed;
+            let current_speed = self.velocity[0] * wish_dir[0] + self.velocity[1] * wish_dir[1];
+            let add_speed = wish_speed - current_speed;
+            if add_speed > 0.0 {
+                let accel_speed = (SV_ACCELERATE * dt * wish_speed).min(add_speed);
+                self.velocity[0] += accel_speed * wish_dir[0];
+                self.velocity[1] += accel_speed * wish_dir[1];
+            }
+        }
+
+        let speed = self.speed();
+        for (index, stop) in self.stops.iter_mut().enumerate() {
+            if let Some(stop) = stop {
+                stop.elapsed += dt;
+                if stop.accurate_after.is_none() && speed < self.params.accurate_speed {
+                    stop.accurate_after = Some(stop.elapsed);
+                }
+                stop.peak_reverse = stop.peak_reverse.max(-stop.direction * self.velocity[index]);
+            }
+        }
+    }
+
+    /// Input along an axis: +1, -1, or 0 when neither or both keys are held
+    fn wish_input(&self, axis: Axis) -> f32 {
+        let (positive, negative) = match axis {
+            Axis::Horizontal => (StrafeKey::D, StrafeKey::A),
+            Axis::Vertical => (StrafeKey::W, StrafeKey::S),
+        };
+        let mut input = 0.0;
+        if self.held[key_index(positive)] {
+            input += 1.0;
+        }
+        if self.held[key_index(negative)] {
+            input -= 1.0;
+        }
+        input
+    }
+}
+
+impl Default for MovementSimulation {
+    fn default() -> Self {
+        Self::new(SimulationParams::default())
+    }
+}
+
+fn key_index(key: StrafeKey) -> usize {
+    match key {
+        StrafeKey::A => 0,
+        StrafeKey::D => 1,
+        StrafeKey::W => 2,
+        StrafeKey::S => 3,
+    }
+}
+
+fn axis_index(axis: Axis) -> usize {
+    match axis {
+        Axis::Horizontal => 0,
+        Axis::Vertical => 1,
+    }
+}
+
+/// Direction a key moves in along its axis
+fn direction(key: StrafeKey) -> f32 {
+    match key {
+        StrafeKey::D | StrafeKey::W => 1.0,
+        StrafeKey::A | StrafeKey::S => -1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    /// Run at full speed to the right, then stop with a counter hold of `counter_ms`
+    fn simulate_stop(counter_ms: u64) -> StopAnalysis {
+        let mut sim = MovementSimulation::default();
+        let start = Instant::now();
+        sim.update(start);
+        sim.on_key_press(StrafeKey::D, at(start, 0));
+        sim.on_key_release(StrafeKey::D, at(start, 1000));
+        if counter_ms > 0 {
+            sim.on_key_press(StrafeKey::A, at(start, 1000));
+            sim.on_key_release(StrafeKey::A, at(start, 1000 + counter_ms));
+        }
+        sim.take_stop_analysis(Axis::Horizontal).unwrap()
+    }
+
+    #[test]
+    fn test_reaches_max_speed() {
+        let mut sim = MovementSimulation::default();
+        let start = Instant::now();
+        sim.update(start);
+        sim.on_key_press(StrafeKey::D, start);
+        sim.update(at(start, 2000));
+
+        assert!((sim.speed() - DEFAULT_MAX_SPEED).abs() < 1.0);
+        assert!(!sim.is_accurate());
+    }
+
+    #[test]
+    fn test_counter_strafe_is_faster_than_releasing() {
+        let released = simulate_stop(0).time_to_accurate.unwrap();
+        let countered = simulate_stop(80).time_to_accurate.unwrap();
+
+        assert!(countered < released);
+        assert!(countered < 0.120);
+        assert!(released > 0.180);
+    }
+
+    #[test]
+    fn test_long_counter_overshoots() {
+        let good = simulate_stop(80);
+        let overheld = simulate_stop(250);
+
+        assert!(good.overshoot < SimulationParams::default().accurate_speed);
+        assert!(overheld.overshoot > good.overshoot);
+        assert!(overheld.overshoot > SimulationParams::default().accurate_speed);
+    }
+}
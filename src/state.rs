@@ -1,16 +1,63 @@
+use crate::keymap::KeyMap;
+use crate::simulation::StopAnalysis;
+use serde::{Deserialize, Serialize};
 use std::time::Instant;
 
-// Timing constants (DO NOT CHANGE!)
+// Rifle timing constants (DO NOT CHANGE!), other weapons are in profiles.rs
 pub const OPTIMAL_HOLD_TIME: f32 = 0.080;      // 80ms
 pub const MIN_HOLD_TIME: f32 = 0.060;          // 60ms
 pub const MAX_HOLD_TIME: f32 = 0.120;          // 120ms
 pub const PERFECT_TOLERANCE: f32 = 0.015;      // ±15ms from optimal
 pub const TIMEOUT_NO_COUNTER: f32 = 0.180;     // 180ms timeout if no counter-key
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// Release -> counter-press transition windows
+pub const PERFECT_GAP: f32 = 0.015;            // ≤15ms between release and counter
+pub const MAX_GAP: f32 = 0.050;                // Longer gaps coast on friction
+pub const PERFECT_OVERLAP: f32 = 0.010;        // ≤10ms with both keys held
+pub const MAX_OVERLAP: f32 = 0.030;
+
+/// Windows used to evaluate a counter-strafe (seconds)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingWindows {
+    pub optimal: f32,
+    pub min: f32,
+    pub max: f32,
+    pub perfect_tolerance: f32,
+    /// Released without a counter-key for this long: attempt abandoned
+    pub timeout: f32,
+    pub perfect_gap: f32,
+    pub max_gap: f32,
+    pub perfect_overlap: f32,
+    pub max_overlap: f32,
+}
+
+impl TimingWindows {
+    /// Generic rifle timing from the constants above
+    pub const DEFAULT: TimingWindows = TimingWindows {
+        optimal: OPTIMAL_HOLD_TIME,
+        min: MIN_HOLD_TIME,
+        max: MAX_HOLD_TIME,
+        perfect_tolerance: PERFECT_TOLERANCE,
+        timeout: TIMEOUT_NO_COUNTER,
+        perfect_gap: PERFECT_GAP,
+        max_gap: MAX_GAP,
+        perfect_overlap: PERFECT_OVERLAP,
+        max_overlap: MAX_OVERLAP,
+    };
+}
+
+impl Default for TimingWindows {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StrafeKey {
     A,
     D,
+    W,
+    S,
 }
 
 impl StrafeKey {
@@ -18,6 +65,8 @@ impl StrafeKey {
         match self {
             StrafeKey::A => StrafeKey::D,
             StrafeKey::D => StrafeKey::A,
+            StrafeKey::W => StrafeKey::S,
+            StrafeKey::S => StrafeKey::W,
         }
     }
 
@@ -25,17 +74,65 @@ impl StrafeKey {
         match self {
             StrafeKey::A => 'A',
             StrafeKey::D => 'D',
+            StrafeKey::W => 'W',
+            StrafeKey::S => 'S',
+        }
+    }
+
+    pub fn axis(&self) -> Axis {
+        match self {
+            StrafeKey::A | StrafeKey::D => Axis::Horizontal,
+            StrafeKey::W | StrafeKey::S => Axis::Vertical,
         }
     }
+
+    /// Stop direction when this key was the movement key, e.g. "A→D"
+    pub fn direction_label(&self) -> String {
+        format!("{}→{}", self.as_char(), self.opposite().as_char())
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Movement axis a counter-strafe happens on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Axis {
+    Horizontal, // A/D
+    Vertical,   // W/S
+}
+
+impl Axis {
+    pub const ALL: [Axis; 2] = [Axis::Horizontal, Axis::Vertical];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Axis::Horizontal => "↔",
+            Axis::Vertical => "↕",
+        }
+    }
+
+    /// Movement keys on this axis
+    pub fn keys(&self) -> [StrafeKey; 2] {
+        match self {
+            Axis::Horizontal => [StrafeKey::A, StrafeKey::D],
+            Axis::Vertical => [StrafeKey::W, StrafeKey::S],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Quality {
     Perfect,  // Within 65-95ms (80ms ±15ms)
     Good,     // 60-120ms
     Failed,   // <60ms or >120ms
 }
 
+/// Why an attempt failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorKind {
+    TooFast,
+    TooSlow,
+    BothKeys,
+}
+
 impl Quality {
     pub fn symbol(&self) -> &'static str {
         match self {
@@ -46,6 +143,48 @@ impl Quality {
     }
 }
 
+/// How the counter key followed the release of the movement key (seconds)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Transition {
+    /// Counter pressed after the release: time spent coasting on friction alone
+    Gap(f32),
+    /// Counter pressed before the release: time both keys were held
+    Overlap(f32),
+}
+
+impl Transition {
+    pub fn duration(&self) -> f32 {
+        match self {
+            Transition::Gap(time) | Transition::Overlap(time) => *time,
+        }
+    }
+
+    pub fn quality(&self, timing: &TimingWindows) -> Quality {
+        let (perfect, max) = match self {
+            Transition::Gap(_) => (timing.perfect_gap, timing.max_gap),
+            Transition::Overlap(_) => (timing.perfect_overlap, timing.max_overlap),
+        };
+        let duration = self.duration();
+        if duration <= perfect {
+            Quality::Perfect
+        } else if duration <= max {
+            Quality::Good
+        } else {
+            Quality::Failed
+        }
+    }
+
+    /// Feed text, e.g. "gap 12ms"
+    pub fn label(&self) -> String {
+        let name = match self {
+            Transition::Gap(_) => "gap",
+            Transition::Overlap(_) => "overlap",
+        };
+        format!("{} {:.0}ms", name, self.duration() * 1000.0)
+    }
+}
+
+/// Tracks counter-strafes along a single axis; keys from the other axis must not be fed in
 #[derive(Debug, Clone)]
 pub enum CounterStrafeState {
     Idle,
@@ -53,6 +192,13 @@ pub enum CounterStrafeState {
         key: StrafeKey,
         start_time: Instant,
     },
+    /// Counter key pressed while the movement key is still held
+    Overlapping {
+        original_key: StrafeKey,
+        counter_key: StrafeKey,
+        strafe_start: Instant,
+        counter_start: Instant,
+    },
     Released {
         original_key: StrafeKey,
         release_time: Instant,
@@ -61,6 +207,7 @@ pub enum CounterStrafeState {
         original_key: StrafeKey,
         counter_key: StrafeKey,
         start_time: Instant,
+        transition: Transition,
     },
     Completed {
         hold_time: f32,
@@ -69,6 +216,12 @@ pub enum CounterStrafeState {
     },
 }
 
+impl Default for CounterStrafeState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CounterStrafeState {
     pub fn new() -> Self {
         CounterStrafeState::Idle
@@ -84,25 +237,24 @@ impl CounterStrafeState {
                 };
                 None
             }
-            CounterStrafeState::Strafing { key: current_key, .. } => {
+            CounterStrafeState::Strafing { key: current_key, start_time } => {
                 // Pressing same key again = key repeat, ignore
-                if *current_key == key {
-                    None
-                } else {
-                    // Pressing opposite key before releasing = error
-                    *self = CounterStrafeState::Completed {
-                        hold_time: 0.0,
-                        quality: Quality::Failed,
-                        error_message: Some("Both keys pressed".to_string()),
+                if *current_key != key {
+                    // Pressing opposite key before releasing: measure the overlap
+                    *self = CounterStrafeState::Overlapping {
+                        original_key: *current_key,
+                        counter_key: key,
+                        strafe_start: *start_time,
+                        counter_start: now,
                     };
-                    Some(CompletionResult {
-                        hold_time: 0.0,
-                        quality: Quality::Failed,
-                        error_message: Some("Both keys pressed".to_string()),
-                    })
                 }
+                None
             }
-            CounterStrafeState::Released { original_key, release_time: _ } => {
+            CounterStrafeState::Overlapping { .. } => {
+                // Key repeat, ignore
+                None
+            }
+            CounterStrafeState::Released { original_key, release_time } => {
                 if key == *original_key {
                     // Pressing same key again = restart
                     *self = CounterStrafeState::Strafing {
@@ -112,10 +264,12 @@ impl CounterStrafeState {
                     None
                 } else {
                     // Pressing opposite key = start counter-strafing
+                    let gap = now.duration_since(*release_time).as_secs_f32();
                     *self = CounterStrafeState::CounterStrafing {
                         original_key: *original_key,
                         counter_key: key,
                         start_time: now,
+                        transition: Transition::Gap(gap),
                     };
                     None
                 }
@@ -135,8 +289,15 @@ impl CounterStrafeState {
         }
     }
 
-    /// Handle key release event
-    pub fn on_key_release(&mut self, key: StrafeKey, now: Instant) -> Option<CompletionResult> {
+    /// Handle key release event, evaluating a finished counter-strafe against `timing`.
+    /// An overlap of up to `overlap_window` seconds is tolerated (0.0 = any overlap fails).
+    pub fn on_key_release(
+        &mut self,
+        key: StrafeKey,
+        now: Instant,
+        timing: &TimingWindows,
+        overlap_window: f32,
+    ) -> Option<CompletionResult> {
         match self {
             CounterStrafeState::Idle => None,
             CounterStrafeState::Strafing { key: current_key, .. } => {
@@ -148,23 +309,65 @@ impl CounterStrafeState {
                 }
                 None
             }
+            CounterStrafeState::Overlapping {
+                original_key,
+                counter_key,
+                strafe_start,
+                counter_start,
+            } => {
+                let overlap = now.duration_since(*counter_start).as_secs_f32();
+                if key == *original_key && overlap <= overlap_window {
+                    // Tolerated overlap: the counter hold is timed from the counter press as usual
+                    *self = CounterStrafeState::CounterStrafing {
+                        original_key: *original_key,
+                        counter_key: *counter_key,
+                        start_time: *counter_start,
+                        transition: Transition::Overlap(overlap),
+                    };
+                    return None;
+                }
+
+                let result = both_keys_result(*original_key, *counter_key, Transition::Overlap(overlap));
+                if key == *counter_key {
+                    // Counter let go first: the movement key is still held
+                    *self = CounterStrafeState::Strafing {
+                        key: *original_key,
+                        start_time: *strafe_start,
+                    };
+                    Some(result)
+                } else if key == *original_key {
+                    *self = CounterStrafeState::Completed {
+                        hold_time: 0.0,
+                        quality: Quality::Failed,
+                        error_message: result.error_message.clone(),
+                    };
+                    Some(result)
+                } else {
+                    None
+                }
+            }
             CounterStrafeState::Released { .. } => None,
             CounterStrafeState::CounterStrafing {
-                original_key: _,
+                original_key,
                 counter_key,
                 start_time,
+                transition,
             } => {
                 if *counter_key == key {
+                    let original_key = *original_key;
+                    let transition = *transition;
+                    let counter_time = *start_time;
+
                     // Calculate hold time
                     let hold_time = now.duration_since(*start_time).as_secs_f32();
-                    let quality = evaluate_hold_time(hold_time);
+                    let quality = evaluate_hold_time(hold_time, timing);
 
-                    let error_message = if hold_time < MIN_HOLD_TIME {
-                        Some(format!("Too fast {:.0}ms", hold_time * 1000.0))
-                    } else if hold_time > MAX_HOLD_TIME {
-                        Some(format!("Too slow {:.0}ms", hold_time * 1000.0))
+                    let (error_kind, error_message) = if hold_time < timing.min {
+                        (Some(ErrorKind::TooFast), Some(format!("Too fast {:.0}ms", hold_time * 1000.0)))
+                    } else if hold_time > timing.max {
+                        (Some(ErrorKind::TooSlow), Some(format!("Too slow {:.0}ms", hold_time * 1000.0)))
                     } else {
-                        None
+                        (None, None)
                     };
 
                     *self = CounterStrafeState::Completed {
@@ -174,9 +377,15 @@ impl CounterStrafeState {
                     };
 
                     Some(CompletionResult {
+                        original_key,
+                        counter_key: key,
                         hold_time,
                         quality,
+                        error_kind,
                         error_message,
+                        transition: Some(transition),
+                        stop: None,
+                        counter_time: Some(counter_time),
                     })
                 } else {
                     None
@@ -186,11 +395,11 @@ impl CounterStrafeState {
         }
     }
 
-    /// Check for timeout (180ms without counter-key)
-    pub fn check_timeout(&mut self, now: Instant) -> bool {
+    /// Check for timeout (`timing.timeout` without counter-key)
+    pub fn check_timeout(&mut self, now: Instant, timing: &TimingWindows) -> bool {
         if let CounterStrafeState::Released { release_time, .. } = self {
             let elapsed = now.duration_since(*release_time).as_secs_f32();
-            if elapsed >= TIMEOUT_NO_COUNTER {
+            if elapsed >= timing.timeout {
                 *self = CounterStrafeState::Idle;
                 return true;
             }
@@ -203,6 +412,16 @@ impl CounterStrafeState {
         *self = CounterStrafeState::Idle;
     }
 
+    /// Whether an attempt is in progress (anything but idle or completed)
+    pub fn is_active(&self) -> bool {
+        !matches!(self, CounterStrafeState::Idle | CounterStrafeState::Completed { .. })
+    }
+
+    /// Whether the movement key is currently held
+    pub fn is_strafing(&self) -> bool {
+        matches!(self, CounterStrafeState::Strafing { .. } | CounterStrafeState::Overlapping { .. })
+    }
+
     /// Get current hold time if counter-strafing
     pub fn get_current_hold_time(&self, now: Instant) -> Option<f32> {
         if let CounterStrafeState::CounterStrafing { start_time, .. } = self {
@@ -212,22 +431,35 @@ impl CounterStrafeState {
         }
     }
 
-    /// Get display info for UI
-    pub fn get_display_info(&self) -> StateDisplayInfo {
+    /// Get display info for UI, naming keys by their current bindings
+    pub fn get_display_info(&self, keymap: &KeyMap) -> StateDisplayInfo {
+        let idle_hint = format!(
+            "Press {}/{} or {}/{}",
+            keymap.strafe_label(StrafeKey::A),
+            keymap.strafe_label(StrafeKey::D),
+            keymap.strafe_label(StrafeKey::W),
+            keymap.strafe_label(StrafeKey::S)
+        );
+
         match self {
             CounterStrafeState::Idle => StateDisplayInfo {
                 main_text: "READY".to_string(),
-                sub_text: Some("Press A or D".to_string()),
+                sub_text: Some(idle_hint),
                 show_target: false,
             },
             CounterStrafeState::Strafing { key, .. } => StateDisplayInfo {
                 main_text: "RELEASE".to_string(),
-                sub_text: Some(format!("Release {}", key.as_char())),
+                sub_text: Some(format!("Release {}", keymap.strafe_label(*key))),
+                show_target: false,
+            },
+            CounterStrafeState::Overlapping { original_key, .. } => StateDisplayInfo {
+                main_text: "BOTH KEYS".to_string(),
+                sub_text: Some(format!("Release {}", keymap.strafe_label(*original_key))),
                 show_target: false,
             },
             CounterStrafeState::Released { original_key, .. } => StateDisplayInfo {
                 main_text: "COUNTER".to_string(),
-                sub_text: Some(format!("Press {}", original_key.opposite().as_char())),
+                sub_text: Some(format!("Press {}", keymap.strafe_label(original_key.opposite()))),
                 show_target: false,
             },
             CounterStrafeState::CounterStrafing { .. } => StateDisplayInfo {
@@ -237,7 +469,7 @@ impl CounterStrafeState {
             },
             CounterStrafeState::Completed { .. } => StateDisplayInfo {
                 main_text: "READY".to_string(),
-                sub_text: Some("Press A or D".to_string()),
+                sub_text: Some(idle_hint),
                 show_target: false,
             },
         }
@@ -251,16 +483,46 @@ pub struct StateDisplayInfo {
 }
 
 pub struct CompletionResult {
+    pub original_key: StrafeKey,
+    pub counter_key: StrafeKey,
     pub hold_time: f32,
     pub quality: Quality,
+    pub error_kind: Option<ErrorKind>,
     pub error_message: Option<String>,
+    /// Release -> counter-press gap or overlap
+    pub transition: Option<Transition>,
+    /// Simulated movement outcome, attached by the app from `MovementSimulation`
+    pub stop: Option<StopAnalysis>,
+    /// When the counter key went down; `None` without a counter hold (both keys pressed)
+    pub counter_time: Option<Instant>,
+}
+
+impl CompletionResult {
+    pub fn axis(&self) -> Axis {
+        self.original_key.axis()
+    }
+}
+
+/// Failed attempt where the counter key was pressed before the movement key was released
+fn both_keys_result(original_key: StrafeKey, counter_key: StrafeKey, overlap: Transition) -> CompletionResult {
+    CompletionResult {
+        original_key,
+        counter_key,
+        hold_time: 0.0,
+        quality: Quality::Failed,
+        error_kind: Some(ErrorKind::BothKeys),
+        error_message: Some("Both keys pressed".to_string()),
+        transition: Some(overlap),
+        stop: None,
+        counter_time: None,
+    }
 }
 
-/// Evaluate hold time quality
-pub fn evaluate_hold_time(hold_time: f32) -> Quality {
-    if hold_time < MIN_HOLD_TIME || hold_time > MAX_HOLD_TIME {
+/// Evaluate hold time quality against the active timing windows
+pub fn evaluate_hold_time(hold_time: f32, timing: &TimingWindows) -> Quality {
+    if !(timing.min..=timing.max).contains(&hold_time) {
         Quality::Failed
-    } else if (hold_time - OPTIMAL_HOLD_TIME).abs() <= PERFECT_TOLERANCE {
+    } else if (hold_time - timing.optimal).abs() <= timing.perfect_tolerance {
         Quality::Perfect
     } else {
         Quality::Good
@@ -273,28 +535,28 @@ mod tests {
 
     #[test]
     fn test_perfect_timing() {
-        assert_eq!(evaluate_hold_time(0.080), Quality::Perfect);
-        assert_eq!(evaluate_hold_time(0.066), Quality::Perfect);  // 66ms: within tolerance
-        assert_eq!(evaluate_hold_time(0.094), Quality::Perfect);  // 94ms: within tolerance
-        assert_eq!(evaluate_hold_time(0.070), Quality::Perfect);  // 70ms: clearly within
-        assert_eq!(evaluate_hold_time(0.090), Quality::Perfect);  // 90ms: clearly within
+        assert_eq!(evaluate_hold_time(0.080, &TimingWindows::DEFAULT), Quality::Perfect);
+        assert_eq!(evaluate_hold_time(0.066, &TimingWindows::DEFAULT), Quality::Perfect);  // 66ms: within tolerance
+        assert_eq!(evaluate_hold_time(0.094, &TimingWindows::DEFAULT), Quality::Perfect);  // 94ms: within tolerance
+        assert_eq!(evaluate_hold_time(0.070, &TimingWindows::DEFAULT), Quality::Perfect);  // 70ms: clearly within
+        assert_eq!(evaluate_hold_time(0.090, &TimingWindows::DEFAULT), Quality::Perfect);  // 90ms: clearly within
     }
 
     #[test]
     fn test_good_timing() {
-        assert_eq!(evaluate_hold_time(0.060), Quality::Good);   // 60ms: at min boundary
-        assert_eq!(evaluate_hold_time(0.065), Quality::Good);   // 65ms: boundary case
-        assert_eq!(evaluate_hold_time(0.095), Quality::Good);   // 95ms: boundary case
-        assert_eq!(evaluate_hold_time(0.100), Quality::Good);   // 100ms: good but not perfect
-        assert_eq!(evaluate_hold_time(0.120), Quality::Good);   // 120ms: at max boundary
+        assert_eq!(evaluate_hold_time(0.060, &TimingWindows::DEFAULT), Quality::Good);   // 60ms: at min boundary
+        assert_eq!(evaluate_hold_time(0.065, &TimingWindows::DEFAULT), Quality::Good);   // 65ms: boundary case
+        assert_eq!(evaluate_hold_time(0.095, &TimingWindows::DEFAULT), Quality::Good);   // 95ms: boundary case
+        assert_eq!(evaluate_hold_time(0.100, &TimingWindows::DEFAULT), Quality::Good);   // 100ms: good but not perfect
+        assert_eq!(evaluate_hold_time(0.120, &TimingWindows::DEFAULT), Quality::Good);   // 120ms: at max boundary
     }
 
     #[test]
     fn test_failed_timing() {
-        assert_eq!(evaluate_hold_time(0.050), Quality::Failed);
-        assert_eq!(evaluate_hold_time(0.059), Quality::Failed);
-        assert_eq!(evaluate_hold_time(0.121), Quality::Failed);
-        assert_eq!(evaluate_hold_time(0.200), Quality::Failed);
+        assert_eq!(evaluate_hold_time(0.050, &TimingWindows::DEFAULT), Quality::Failed);
+        assert_eq!(evaluate_hold_time(0.059, &TimingWindows::DEFAULT), Quality::Failed);
+        assert_eq!(evaluate_hold_time(0.121, &TimingWindows::DEFAULT), Quality::Failed);
+        assert_eq!(evaluate_hold_time(0.200, &TimingWindows::DEFAULT), Quality::Failed);
     }
 
     #[test]
@@ -307,7 +569,7 @@ mod tests {
         assert!(matches!(state, CounterStrafeState::Strafing { .. }));
 
         // Strafing -> Released
-        state.on_key_release(StrafeKey::A, now);
+        state.on_key_release(StrafeKey::A, now, &TimingWindows::DEFAULT, 0.0);
         assert!(matches!(state, CounterStrafeState::Released { .. }));
 
         // Released -> CounterStrafing
@@ -315,16 +577,123 @@ mod tests {
         assert!(matches!(state, CounterStrafeState::CounterStrafing { .. }));
     }
 
+    #[test]
+    fn test_hold_time_uses_event_timestamps() {
+        let mut state = CounterStrafeState::new();
+        let start = Instant::now();
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+
+        state.on_key_press(StrafeKey::A, at(0));
+        state.on_key_release(StrafeKey::A, at(200), &TimingWindows::DEFAULT, 0.0);
+        state.on_key_press(StrafeKey::D, at(210));
+        let result = state.on_key_release(StrafeKey::D, at(290), &TimingWindows::DEFAULT, 0.0).unwrap();
+
+        assert!((result.hold_time - 0.080).abs() < 1e-4);
+        assert_eq!(result.quality, Quality::Perfect);
+        assert_eq!(result.counter_time, Some(at(210)));
+    }
+
+    #[test]
+    fn test_vertical_counter_strafe() {
+        let mut state = CounterStrafeState::new();
+        let start = Instant::now();
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+
+        state.on_key_press(StrafeKey::W, at(0));
+        state.on_key_release(StrafeKey::W, at(300), &TimingWindows::DEFAULT, 0.0);
+        state.on_key_press(StrafeKey::S, at(310));
+        let result = state.on_key_release(StrafeKey::S, at(410), &TimingWindows::DEFAULT, 0.0).unwrap();
+
+        assert_eq!(result.axis(), Axis::Vertical);
+        assert_eq!(result.counter_key, StrafeKey::S);
+        assert_eq!(result.quality, Quality::Good);
+    }
+
     #[test]
     fn test_both_keys_pressed() {
         let mut state = CounterStrafeState::new();
-        let now = Instant::now();
+        let start = Instant::now();
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
 
-        state.on_key_press(StrafeKey::A, now);
-        let result = state.on_key_press(StrafeKey::D, now);
+        state.on_key_press(StrafeKey::A, at(0));
+        assert!(state.on_key_press(StrafeKey::D, at(200)).is_none());
+        assert!(matches!(state, CounterStrafeState::Overlapping { .. }));
 
-        assert!(result.is_some());
-        assert_eq!(result.unwrap().quality, Quality::Failed);
+        let result = state.on_key_release(StrafeKey::A, at(240), &TimingWindows::DEFAULT, 0.0).unwrap();
+        assert_eq!(result.quality, Quality::Failed);
+        assert_eq!(result.error_kind, Some(ErrorKind::BothKeys));
+        assert!(matches!(result.transition, Some(Transition::Overlap(t)) if (t - 0.040).abs() < 1e-4));
+        assert_eq!(result.counter_time, None);
         assert!(matches!(state, CounterStrafeState::Completed { .. }));
     }
+
+    #[test]
+    fn test_counter_released_first_keeps_strafing() {
+        let mut state = CounterStrafeState::new();
+        let start = Instant::now();
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+
+        state.on_key_press(StrafeKey::A, at(0));
+        state.on_key_press(StrafeKey::D, at(200));
+        let result = state.on_key_release(StrafeKey::D, at(220), &TimingWindows::DEFAULT, 0.0).unwrap();
+
+        assert_eq!(result.error_kind, Some(ErrorKind::BothKeys));
+        assert!(matches!(state, CounterStrafeState::Strafing { key: StrafeKey::A, .. }));
+    }
+
+    #[test]
+    fn test_overlap_within_window_is_tolerated() {
+        let mut state = CounterStrafeState::new();
+        let start = Instant::now();
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+
+        state.on_key_press(StrafeKey::A, at(0));
+        state.on_key_press(StrafeKey::D, at(200));
+        assert!(state.on_key_release(StrafeKey::A, at(208), &TimingWindows::DEFAULT, 0.020).is_none());
+        assert!(matches!(state, CounterStrafeState::CounterStrafing { .. }));
+
+        let result = state.on_key_release(StrafeKey::D, at(280), &TimingWindows::DEFAULT, 0.020).unwrap();
+        assert!((result.hold_time - 0.080).abs() < 1e-4);
+        assert_eq!(result.quality, Quality::Perfect);
+        assert!(matches!(result.transition, Some(Transition::Overlap(t)) if (t - 0.008).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_overlap_beyond_window_fails() {
+        let mut state = CounterStrafeState::new();
+        let start = Instant::now();
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+
+        state.on_key_press(StrafeKey::A, at(0));
+        state.on_key_press(StrafeKey::D, at(200));
+        let result = state.on_key_release(StrafeKey::A, at(230), &TimingWindows::DEFAULT, 0.020).unwrap();
+
+        assert_eq!(result.error_kind, Some(ErrorKind::BothKeys));
+    }
+
+    #[test]
+    fn test_gap_is_measured() {
+        let mut state = CounterStrafeState::new();
+        let start = Instant::now();
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+
+        state.on_key_press(StrafeKey::A, at(0));
+        state.on_key_release(StrafeKey::A, at(200), &TimingWindows::DEFAULT, 0.0);
+        state.on_key_press(StrafeKey::D, at(230));
+        let result = state.on_key_release(StrafeKey::D, at(310), &TimingWindows::DEFAULT, 0.0).unwrap();
+
+        let transition = result.transition.unwrap();
+        assert!(matches!(transition, Transition::Gap(t) if (t - 0.030).abs() < 1e-4));
+        assert_eq!(transition.quality(&TimingWindows::DEFAULT), Quality::Good);
+        assert_eq!(transition.label(), "gap 30ms");
+    }
+
+    #[test]
+    fn test_transition_quality() {
+        assert_eq!(Transition::Gap(0.005).quality(&TimingWindows::DEFAULT), Quality::Perfect);
+        assert_eq!(Transition::Gap(0.080).quality(&TimingWindows::DEFAULT), Quality::Failed);
+        assert_eq!(Transition::Overlap(0.008).quality(&TimingWindows::DEFAULT), Quality::Perfect);
+        assert_eq!(Transition::Overlap(0.020).quality(&TimingWindows::DEFAULT), Quality::Good);
+        assert_eq!(Transition::Overlap(0.050).quality(&TimingWindows::DEFAULT), Quality::Failed);
+    }
 }
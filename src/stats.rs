@@ -1,4 +1,13 @@
-use crate::state::Quality;
+use crate::metronome::rate_offset;
+use crate::shots::{ShotResult, ShotTiming};
+use crate::state::{Axis, CompletionResult, Quality, StrafeKey, Transition};
+use std::collections::HashMap;
+
+/// Number of most recent attempts in the rolling average
+pub const ROLLING_WINDOW: usize = 20;
+
+/// Attempts needed in both directions of an axis before one is called the weaker one
+pub const MIN_DIRECTION_ATTEMPTS: u32 = 5;
 
 #[derive(Debug, Clone, Default)]
 pub struct Stats {
@@ -6,6 +15,117 @@ pub struct Stats {
     pub perfect_count: u32,
     pub good_count: u32,
     pub failed_count: u32,
+    pub horizontal: AxisStats,
+    pub vertical: AxisStats,
+    /// Diagonal stops, each counted as a single attempt
+    pub diagonal: AxisStats,
+    /// Counter hold time of every attempt in seconds, oldest first
+    pub hold_times: Vec<f32>,
+    /// Release -> counter-press gaps in seconds, oldest first
+    pub gaps: Vec<f32>,
+    /// Overlaps with both keys held in seconds, oldest first
+    pub overlaps: Vec<f32>,
+    /// Signed distance of each stop from its metronome beat in seconds, oldest first
+    pub beat_offsets: Vec<f32>,
+    /// STOP cue -> counter key latency of each reaction prompt in seconds, oldest first
+    pub reaction_times: Vec<f32>,
+    /// Strafe cue -> first press of the cued key of each answered reaction prompt in seconds, oldest first
+    pub strafe_reaction_times: Vec<f32>,
+    /// Per stop direction, keyed by the released movement key (A→D is `A`)
+    pub directions: HashMap<StrafeKey, DirectionStats>,
+    pub shots: ShotStats,
+}
+
+/// Shot timing counts from the fire drill
+#[derive(Debug, Clone, Default)]
+pub struct ShotStats {
+    pub on_time: u32,
+    pub too_early: u32,
+    pub late: u32,
+}
+
+impl ShotStats {
+    pub fn total(&self) -> u32 {
+        self.on_time + self.too_early + self.late
+    }
+}
+
+/// One counter-strafe on a single axis, as counted by `Stats`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attempt {
+    /// Released movement key (A→D is `A`)
+    pub key: StrafeKey,
+    pub quality: Quality,
+    /// Counter hold in seconds, 0.0 if there was none (both keys pressed)
+    pub hold_time: f32,
+    pub transition: Option<Transition>,
+}
+
+impl From<&CompletionResult> for Attempt {
+    fn from(result: &CompletionResult) -> Self {
+        Self {
+            key: result.original_key,
+            quality: result.quality,
+            hold_time: result.hold_time,
+            transition: result.transition,
+        }
+    }
+}
+
+/// Attempts stopping from one direction
+#[derive(Debug, Clone, Default)]
+pub struct DirectionStats {
+    pub attempts: u32,
+    pub perfect_count: u32,
+    measured: u32,
+    hold_time_sum: f32,
+}
+
+impl DirectionStats {
+    /// Get perfect percentage in this direction
+    pub fn perfect_percentage(&self) -> f32 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            (self.perfect_count as f32 / self.attempts as f32) * 100.0
+        }
+    }
+
+    /// Average counter hold time, `None` if no attempt had a measurable hold
+    pub fn mean_hold_time(&self) -> Option<f32> {
+        (self.measured > 0).then(|| self.hold_time_sum / self.measured as f32)
+    }
+}
+
+/// Distribution of measured times (seconds)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingSummary {
+    pub count: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub std_dev: f32,
+    pub p10: f32,
+    pub p90: f32,
+    /// Mean of the last `ROLLING_WINDOW` attempts
+    pub rolling_average: f32,
+}
+
+/// Attempt counts for one movement axis
+#[derive(Debug, Clone, Default)]
+pub struct AxisStats {
+    pub attempts: u32,
+    pub perfect_count: u32,
+}
+
+impl AxisStats {
+    /// Get perfect percentage on this axis
+    pub fn perfect_percentage(&self) -> f32 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            (self.perfect_count as f32 / self.attempts as f32) * 100.0
+        }
+    }
 }
 
 impl Stats {
@@ -14,13 +134,160 @@ impl Stats {
     }
 
     /// Record a completed counter-strafe attempt
-    pub fn record(&mut self, quality: Quality) {
+    pub fn record(&mut self, attempt: Attempt) {
+        let quality = attempt.quality;
         self.total_attempts += 1;
+        self.record_measurements(attempt);
         match quality {
             Quality::Perfect => self.perfect_count += 1,
             Quality::Good => self.good_count += 1,
             Quality::Failed => self.failed_count += 1,
         }
+
+        let axis_stats = match attempt.key.axis() {
+            Axis::Horizontal => &mut self.horizontal,
+            Axis::Vertical => &mut self.vertical,
+        };
+        axis_stats.attempts += 1;
+        if quality == Quality::Perfect {
+            axis_stats.perfect_count += 1;
+        }
+    }
+
+    /// Record a diagonal stop with its combined quality. Each countered axis
+    /// still counts towards its direction and the timing distributions.
+    pub fn record_diagonal(&mut self, quality: Quality, axes: impl IntoIterator<Item = Attempt>) {
+        self.total_attempts += 1;
+        for attempt in axes {
+            self.record_measurements(attempt);
+        }
+        match quality {
+            Quality::Perfect => self.perfect_count += 1,
+            Quality::Good => self.good_count += 1,
+            Quality::Failed => self.failed_count += 1,
+        }
+
+        self.diagonal.attempts += 1;
+        if quality == Quality::Perfect {
+            self.diagonal.perfect_count += 1;
+        }
+    }
+
+    /// Direction counts plus hold time and transition measurements.
+    /// Attempts without a counter hold (both keys pressed) count, but have no hold time.
+    fn record_measurements(&mut self, attempt: Attempt) {
+        let direction = self.directions.entry(attempt.key).or_default();
+        direction.attempts += 1;
+        if attempt.quality == Quality::Perfect {
+            direction.perfect_count += 1;
+        }
+
+        if attempt.hold_time > 0.0 {
+            direction.measured += 1;
+            direction.hold_time_sum += attempt.hold_time;
+            self.hold_times.push(attempt.hold_time);
+        }
+
+        match attempt.transition {
+            Some(Transition::Gap(gap)) => self.gaps.push(gap),
+            Some(Transition::Overlap(overlap)) => self.overlaps.push(overlap),
+            None => {}
+        }
+    }
+
+    /// Record an evaluated shot
+    pub fn record_shot(&mut self, shot: &ShotResult) {
+        match shot.timing {
+            ShotTiming::OnTime => self.shots.on_time += 1,
+            ShotTiming::TooEarly => self.shots.too_early += 1,
+            ShotTiming::Late => self.shots.late += 1,
+        }
+    }
+
+    /// Record how far a stop landed from its beat in a rhythm drill
+    pub fn record_beat_offset(&mut self, offset: f32) {
+        self.beat_offsets.push(offset);
+    }
+
+    /// Record how fast the counter key followed a reaction prompt's STOP cue
+    pub fn record_reaction(&mut self, latency: f32) {
+        self.reaction_times.push(latency);
+    }
+
+    /// Distribution of reaction times
+    pub fn reaction_summary(&self) -> Option<TimingSummary> {
+        summarize(&self.reaction_times)
+    }
+
+    /// Record how fast the cued key followed a reaction prompt's strafe cue
+    pub fn record_strafe_reaction(&mut self, latency: f32) {
+        self.strafe_reaction_times.push(latency);
+    }
+
+    /// Distribution of strafe cue reaction times
+    pub fn strafe_reaction_summary(&self) -> Option<TimingSummary> {
+        summarize(&self.strafe_reaction_times)
+    }
+
+    /// Share of stops that landed on the beat, in percent
+    pub fn on_beat_percentage(&self) -> f32 {
+        if self.beat_offsets.is_empty() {
+            0.0
+        } else {
+            let on_beat = self.beat_offsets.iter().filter(|offset| rate_offset(**offset) == Quality::Perfect).count();
+            on_beat as f32 / self.beat_offsets.len() as f32 * 100.0
+        }
+    }
+
+    /// Distribution of beat offsets; the mean shows a habit of stopping early or late
+    pub fn beat_offset_summary(&self) -> Option<TimingSummary> {
+        summarize(&self.beat_offsets)
+    }
+
+    /// Get the stats of one stop direction
+    pub fn direction(&self, key: StrafeKey) -> Option<&DirectionStats> {
+        self.directions.get(&key)
+    }
+
+    /// The direction on `axis` with the lower perfect rate, once both directions have
+    /// enough attempts. Ties go to the direction further from `optimal` on average.
+    pub fn weaker_direction(&self, axis: Axis, optimal: f32) -> Option<StrafeKey> {
+        let [first, second] = axis.keys();
+        let a = self.direction(first).filter(|d| d.attempts >= MIN_DIRECTION_ATTEMPTS)?;
+        let b = self.direction(second).filter(|d| d.attempts >= MIN_DIRECTION_ATTEMPTS)?;
+
+        let offset = |d: &DirectionStats| d.mean_hold_time().map_or(f32::MAX, |t| (t - optimal).abs());
+        let (pa, pb) = (a.perfect_percentage(), b.perfect_percentage());
+        if pa < pb || (pa == pb && offset(a) > offset(b)) {
+            Some(first)
+        } else if pb < pa || offset(b) > offset(a) {
+            Some(second)
+        } else {
+            None
+        }
+    }
+
+    /// Hold time distribution, `None` until the first measured attempt
+    pub fn hold_time_summary(&self) -> Option<TimingSummary> {
+        summarize(&self.hold_times)
+    }
+
+    /// Release -> counter-press gap distribution
+    pub fn gap_summary(&self) -> Option<TimingSummary> {
+        summarize(&self.gaps)
+    }
+
+    /// Both-keys overlap distribution
+    pub fn overlap_summary(&self) -> Option<TimingSummary> {
+        summarize(&self.overlaps)
+    }
+
+    /// Get the stats of one axis
+    pub fn axis(&self, axis: Axis) -> &AxisStats {
+        match axis {
+            Axis::Horizontal => &self.horizontal,
+            Axis::Vertical => &self.vertical,
+        }
     }
 
     /// Get perfect percentage
@@ -56,10 +323,55 @@ impl Stats {
     }
 }
 
+/// Distribution of `values` (oldest first), `None` if empty
+fn summarize(values: &[f32]) -> Option<TimingSummary> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let count = values.len();
+    let mean = values.iter().sum::<f32>() / count as f32;
+    let variance = values.iter().map(|t| (t - mean).powi(2)).sum::<f32>() / count as f32;
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+
+    let recent = &values[count.saturating_sub(ROLLING_WINDOW)..];
+    let rolling_average = recent.iter().sum::<f32>() / recent.len() as f32;
+
+    Some(TimingSummary {
+        count,
+        mean,
+        median: percentile(&sorted, 0.5),
+        std_dev: variance.sqrt(),
+        p10: percentile(&sorted, 0.1),
+        p90: percentile(&sorted, 0.9),
+        rolling_average,
+    })
+}
+
+/// Percentile of sorted values with linear interpolation, `p` in 0.0..=1.0
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let position = p * (sorted.len() - 1) as f32;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    let fraction = position - lower as f32;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * fraction
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn attempt(key: StrafeKey, quality: Quality, hold_time: f32) -> Attempt {
+        Attempt {
+            key,
+            quality,
+            hold_time,
+            transition: None,
+        }
+    }
+
     #[test]
     fn test_empty_stats() {
         let stats = Stats::new();
@@ -70,10 +382,10 @@ mod tests {
     #[test]
     fn test_record_attempts() {
         let mut stats = Stats::new();
-        stats.record(Quality::Perfect);
-        stats.record(Quality::Perfect);
-        stats.record(Quality::Good);
-        stats.record(Quality::Failed);
+        stats.record(attempt(StrafeKey::A, Quality::Perfect, 0.080));
+        stats.record(attempt(StrafeKey::A, Quality::Perfect, 0.080));
+        stats.record(attempt(StrafeKey::A, Quality::Good, 0.080));
+        stats.record(attempt(StrafeKey::A, Quality::Failed, 0.080));
 
         assert_eq!(stats.total_attempts, 4);
         assert_eq!(stats.perfect_count, 2);
@@ -82,11 +394,150 @@ mod tests {
         assert_eq!(stats.perfect_percentage(), 50.0);
     }
 
+    #[test]
+    fn test_axes_counted_separately() {
+        let mut stats = Stats::new();
+        stats.record(attempt(StrafeKey::A, Quality::Perfect, 0.080));
+        stats.record(attempt(StrafeKey::W, Quality::Failed, 0.080));
+        stats.record(attempt(StrafeKey::W, Quality::Perfect, 0.080));
+
+        assert_eq!(stats.total_attempts, 3);
+        assert_eq!(stats.axis(Axis::Horizontal).attempts, 1);
+        assert_eq!(stats.axis(Axis::Vertical).attempts, 2);
+        assert_eq!(stats.axis(Axis::Vertical).perfect_percentage(), 50.0);
+    }
+
+    #[test]
+    fn test_diagonal_counted_once() {
+        let mut stats = Stats::new();
+        stats.record_diagonal(
+            Quality::Good,
+            [
+                attempt(StrafeKey::A, Quality::Perfect, 0.080),
+                attempt(StrafeKey::W, Quality::Good, 0.100),
+            ],
+        );
+
+        assert_eq!(stats.total_attempts, 1);
+        assert_eq!(stats.good_count, 1);
+        assert_eq!(stats.diagonal.attempts, 1);
+        assert_eq!(stats.horizontal.attempts, 0);
+        assert_eq!(stats.hold_times.len(), 2);
+        assert_eq!(stats.direction(StrafeKey::W).unwrap().attempts, 1);
+    }
+
+    #[test]
+    fn test_directions_counted_separately() {
+        let mut stats = Stats::new();
+        for _ in 0..MIN_DIRECTION_ATTEMPTS {
+            stats.record(attempt(StrafeKey::A, Quality::Perfect, 0.080));
+            stats.record(attempt(StrafeKey::D, Quality::Good, 0.110));
+        }
+
+        let a_to_d = stats.direction(StrafeKey::A).unwrap();
+        let d_to_a = stats.direction(StrafeKey::D).unwrap();
+        assert_eq!(a_to_d.perfect_percentage(), 100.0);
+        assert!((d_to_a.mean_hold_time().unwrap() - 0.110).abs() < 1e-6);
+        assert_eq!(stats.weaker_direction(Axis::Horizontal, 0.080), Some(StrafeKey::D));
+        assert_eq!(stats.weaker_direction(Axis::Vertical, 0.080), None);
+    }
+
+    #[test]
+    fn test_weaker_direction_needs_enough_attempts() {
+        let mut stats = Stats::new();
+        stats.record(attempt(StrafeKey::A, Quality::Perfect, 0.080));
+        stats.record(attempt(StrafeKey::D, Quality::Failed, 0.150));
+
+        assert_eq!(stats.weaker_direction(Axis::Horizontal, 0.080), None);
+    }
+
+    #[test]
+    fn test_hold_time_summary() {
+        let mut stats = Stats::new();
+        assert!(stats.hold_time_summary().is_none());
+
+        for ms in [60.0, 70.0, 80.0, 90.0, 100.0] {
+            stats.record(attempt(StrafeKey::A, Quality::Good, ms / 1000.0));
+        }
+        // Both keys pressed: no hold time to measure
+        stats.record(attempt(StrafeKey::A, Quality::Failed, 0.0));
+
+        let summary = stats.hold_time_summary().unwrap();
+        assert_eq!(summary.count, 5);
+        assert!((summary.mean - 0.080).abs() < 1e-6);
+        assert!((summary.median - 0.080).abs() < 1e-6);
+        assert!((summary.std_dev - 0.01414).abs() < 1e-4);
+        assert!((summary.p10 - 0.064).abs() < 1e-6);
+        assert!((summary.p90 - 0.096).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_transitions_tracked() {
+        let mut stats = Stats::new();
+        stats.record(Attempt {
+            transition: Some(Transition::Gap(0.012)),
+            ..attempt(StrafeKey::A, Quality::Perfect, 0.080)
+        });
+        stats.record(Attempt {
+            transition: Some(Transition::Overlap(0.040)),
+            ..attempt(StrafeKey::D, Quality::Failed, 0.0)
+        });
+
+        assert!((stats.gap_summary().unwrap().mean - 0.012).abs() < 1e-6);
+        assert_eq!(stats.overlap_summary().unwrap().count, 1);
+        assert_eq!(stats.hold_times.len(), 1);
+    }
+
+    #[test]
+    fn test_shots_counted() {
+        let mut stats = Stats::new();
+        let shot = |timing| ShotResult {
+            timing,
+            since_stop: None,
+            speed: 0.0,
+        };
+        stats.record_shot(&shot(ShotTiming::OnTime));
+        stats.record_shot(&shot(ShotTiming::Late));
+
+        assert_eq!(stats.shots.total(), 2);
+        assert_eq!(stats.shots.on_time, 1);
+        assert_eq!(stats.total_attempts, 0);
+    }
+
+    #[test]
+    fn test_beat_offsets() {
+        let mut stats = Stats::new();
+        assert_eq!(stats.on_beat_percentage(), 0.0);
+        for offset in [-0.010, 0.020, 0.060, -0.150] {
+            stats.record_beat_offset(offset);
+        }
+
+        assert_eq!(stats.on_beat_percentage(), 50.0);
+        let summary = stats.beat_offset_summary().unwrap();
+        assert!((summary.mean + 0.020).abs() < 1e-6);
+        assert_eq!(stats.total_attempts, 0);
+    }
+
+    #[test]
+    fn test_rolling_average_uses_recent_attempts() {
+        let mut stats = Stats::new();
+        for _ in 0..ROLLING_WINDOW {
+            stats.record(attempt(StrafeKey::A, Quality::Failed, 0.200));
+        }
+        for _ in 0..ROLLING_WINDOW {
+            stats.record(attempt(StrafeKey::A, Quality::Perfect, 0.080));
+        }
+
+        let summary = stats.hold_time_summary().unwrap();
+        assert!((summary.rolling_average - 0.080).abs() < 1e-6);
+        assert!((summary.mean - 0.140).abs() < 1e-6);
+    }
+
     #[test]
     fn test_reset() {
         let mut stats = Stats::new();
-        stats.record(Quality::Perfect);
-        stats.record(Quality::Good);
+        stats.record(attempt(StrafeKey::A, Quality::Perfect, 0.080));
+        stats.record(attempt(StrafeKey::W, Quality::Good, 0.080));
         stats.reset();
 
         assert_eq!(stats.total_attempts, 0);
@@ -0,0 +1,314 @@
+use crate::app::Trainer;
+use crate::drills::DrillSummary;
+use crate::events::ListenerStatus;
+use crate::metronome::Cue;
+use crate::profiles::Weapon;
+use crate::state::{evaluate_hold_time, Axis};
+use crate::stats::Stats;
+use crate::ui::Theme;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::time::{Duration, Instant};
+
+const FRAME_TIME: Duration = Duration::from_millis(8);  // ~120 FPS redraw while waiting for terminal input
+const SPEED_BAR_WIDTH: usize = 30;
+
+/// Run the trainer in the terminal until the quit key (or Ctrl+C) is pressed.
+/// Game input still comes from the global listener, so the terminal does not need focus.
+pub fn run(mut trainer: Trainer) -> Result<(), Box<dyn std::error::Error>> {
+    let mut terminal = ratatui::init();
+    let result = run_loop(&mut terminal, &mut trainer);
+    ratatui::restore();
+    result
+}
+
+fn run_loop(terminal: &mut DefaultTerminal, trainer: &mut Trainer) -> Result<(), Box<dyn std::error::Error>> {
+    while !trainer.should_quit() {
+        trainer.process_events();
+        terminal.draw(|frame| render(frame, trainer))?;
+
+        // Terminal-only controls; keys typed here are not part of the training input
+        if event::poll(FRAME_TIME)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Tab => trainer.select_weapon(next_weapon(trainer.weapon())),
+                KeyCode::Char('o') => trainer.cycle_overlap_window(),
+                // Input problem screen
+                KeyCode::Char('r') if trainer.listener_problem().is_some() && trainer.can_restart_input() => {
+                    trainer.restart_input()
+                }
+                KeyCode::Char('c') if trainer.listener_problem().is_some() => trainer.dismiss_listener_problem(),
+                // Drill summary and drill in progress
+                KeyCode::Char('a') if trainer.drill_summary().is_some() => trainer.repeat_drill(),
+                KeyCode::Enter if trainer.drill_summary().is_some() => trainer.close_drill_summary(),
+                KeyCode::Char('x') if trainer.drill().is_some() => trainer.stop_drill(),
+                // Reaction prompts
+                KeyCode::Char('p') => trainer.toggle_reaction(),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn next_weapon(current: Weapon) -> Weapon {
+    let index = Weapon::ALL.iter().position(|weapon| *weapon == current).unwrap_or(0);
+    Weapon::ALL[(index + 1) % Weapon::ALL.len()]
+}
+
+fn render(frame: &mut Frame, trainer: &Trainer) {
+    let [header, drill_line, timer, feed, stats, hint] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(u16::from(trainer.drill().is_some())),
+        Constraint::Length(if trainer.cue(Instant::now()).is_some() { 6 } else { 5 }),
+        Constraint::Min(7),
+        Constraint::Length(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let timing = trainer.movement().timing();
+    let theme = trainer.theme();
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("CS2 Counter-Strafe Trainer", bold(theme.accent)),
+            Span::styled(
+                format!(
+                    "  {} · target {:.0}ms · {}",
+                    trainer.weapon().name(),
+                    timing.optimal * 1000.0,
+                    match trainer.movement().overlap_window() {
+                        window if window > 0.0 => format!("overlap ≤{:.0}ms", window * 1000.0),
+                        _ => "overlap off".to_string(),
+                    }
+                ),
+                style(theme.neutral),
+            ),
+        ])),
+        header,
+    );
+
+    if let Some(drill) = trainer.drill() {
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(format!("Drill {} ", drill.drill().name), bold(theme.accent)),
+                Span::styled(drill.progress(Instant::now()), style(theme.text)),
+            ])),
+            drill_line,
+        );
+    }
+
+    if let Some(problem) = trainer.listener_problem() {
+        render_listener_problem(frame, timer.union(stats), &problem, &theme);
+        let retry = if trainer.can_restart_input() { "r retry · " } else { "" };
+        frame.render_widget(
+            Paragraph::new(Line::styled(format!("{}c continue anyway · Ctrl+C quit", retry), style(theme.neutral))),
+            hint,
+        );
+        return;
+    }
+
+    if let Some(summary) = trainer.drill_summary() {
+        render_drill_summary(frame, timer.union(stats), summary, &theme);
+        frame.render_widget(
+            Paragraph::new(Line::styled("a again · Enter close · Ctrl+C quit", style(theme.neutral))),
+            hint,
+        );
+        return;
+    }
+
+    render_timer(frame, timer, trainer, &theme);
+    render_feed(frame, feed, trainer, &theme);
+    render_stats(frame, stats, trainer.stats(), trainer.all_time_stats(), &theme);
+
+    let keymap = trainer.keymap();
+    frame.render_widget(
+        Paragraph::new(Line::styled(
+            format!(
+                "Tab weapon · o overlap · p reaction {} · {}{} / Ctrl+C quit",
+                if trainer.is_reaction_mode() { "off" } else { "on" },
+                if trainer.drill().is_some() { "x stop drill · " } else { "" },
+                keymap.label_for(crate::keymap::Action::Quit)
+            ),
+            style(theme.neutral),
+        )),
+        hint,
+    );
+}
+
+/// What went wrong with the input backend and how to fix it, instead of the trainer
+fn render_listener_problem(frame: &mut Frame, area: Rect, status: &ListenerStatus, theme: &Theme) {
+    let mut lines = vec![
+        Line::styled(status.title(), bold(theme.bad)),
+        Line::styled(status.detail(), style(theme.text)),
+        Line::default(),
+        Line::styled("What to try", style(theme.neutral)),
+    ];
+    for (number, step) in status.remediation().iter().enumerate() {
+        lines.push(Line::styled(format!("{}. {}", number + 1, step), style(theme.text)));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).block(Block::bordered()),
+        area,
+    );
+}
+
+/// Pass or fail of the finished drill, instead of the trainer
+fn render_drill_summary(frame: &mut Frame, area: Rect, summary: &DrillSummary, theme: &Theme) {
+    let stats = &summary.stats;
+    let (verdict, color) = if summary.passed { ("PASSED", theme.good) } else { ("FAILED", theme.bad) };
+    let mut lines = vec![
+        Line::styled(format!("{} · {}", verdict, summary.drill.name), bold(color)),
+        Line::styled(summary.drill.describe(), style(theme.neutral)),
+        Line::default(),
+        Line::styled(summary.drill.goal.progress(stats), style(theme.text)),
+        Line::styled(
+            format!(
+                "{} attempts in {:.0}s · ★ {} · ● {} · ✕ {}",
+                stats.total_attempts,
+                summary.elapsed.as_secs_f32(),
+                stats.perfect_count,
+                stats.good_count,
+                stats.failed_count
+            ),
+            style(theme.neutral),
+        ),
+    ];
+    if let Some(beat) = stats.beat_offset_summary() {
+        lines.push(Line::styled(
+            format!("{:.0}% on the beat · {:+.0}ms mean offset", stats.on_beat_percentage(), beat.mean * 1000.0),
+            style(theme.neutral),
+        ));
+    }
+    frame.render_widget(Paragraph::new(lines).centered().block(Block::bordered().title(" Drill ")), area);
+}
+
+/// Live hold timer while counter-strafing, the state prompt otherwise, plus the speed bar
+fn render_timer(frame: &mut Frame, area: Rect, trainer: &Trainer, theme: &Theme) {
+    let state = trainer.movement().display_state();
+    let timing = trainer.movement().timing();
+    let mut lines = Vec::new();
+
+    if let Some((cue, _)) = trainer.cue(Instant::now()) {
+        let color = match cue {
+            Cue::Wait => theme.neutral,
+            Cue::Stop => theme.warning,
+            Cue::Strafe(_) => theme.accent,
+        };
+        lines.push(Line::styled(cue.label(), bold(color)));
+    }
+
+    match state.get_current_hold_time(Instant::now()) {
+        Some(hold_time) => {
+            let symbol = if hold_time < timing.min {
+                "⚡"
+            } else if hold_time > timing.max {
+                "⏱"
+            } else {
+                evaluate_hold_time(hold_time, timing).symbol()
+            };
+            let color = theme.quality_color(evaluate_hold_time(hold_time, timing));
+            lines.push(Line::styled(format!("{:.0} ms {}", hold_time * 1000.0, symbol), bold(color)));
+            lines.push(Line::styled(format!("target {:.0}ms", timing.optimal * 1000.0), style(theme.neutral)));
+        }
+        None => {
+            let info = state.get_display_info(&trainer.keymap());
+            lines.push(Line::styled(info.main_text, bold(theme.accent)));
+            lines.push(Line::styled(info.sub_text.unwrap_or_default(), style(theme.neutral)));
+        }
+    }
+
+    let simulation = trainer.simulation();
+    let params = simulation.params();
+    let filled = ((simulation.speed() / params.max_speed).clamp(0.0, 1.0) * SPEED_BAR_WIDTH as f32).round() as usize;
+    let speed_color = if simulation.is_accurate() { theme.good } else { theme.bad };
+    lines.push(Line::from(vec![
+        Span::styled("█".repeat(filled), style(speed_color)),
+        Span::styled("░".repeat(SPEED_BAR_WIDTH - filled), style(theme.neutral)),
+        Span::styled(format!(" {:.0} u/s", simulation.speed()), style(theme.neutral)),
+    ]));
+
+    frame.render_widget(Paragraph::new(lines).centered().block(Block::bordered()), area);
+}
+
+/// Feed entries, newest first; fading is approximated by blending the color into the background
+fn render_feed(frame: &mut Frame, area: Rect, trainer: &Trainer, theme: &Theme) {
+    let now = Instant::now();
+    let lines: Vec<Line> = trainer
+        .feed()
+        .get_entries_with_opacity(now)
+        .into_iter()
+        .map(|(entry, opacity)| {
+            Line::from(vec![
+                Span::styled(format!("{} ", entry.symbol), faded(theme.quality_color(entry.quality), opacity)),
+                Span::styled(entry.message.clone(), faded(theme.text, opacity)),
+            ])
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Feed ")), area);
+}
+
+fn render_stats(frame: &mut Frame, area: Rect, stats: &Stats, all_time: &Stats, theme: &Theme) {
+    let mut axes = Vec::new();
+    for axis in Axis::ALL {
+        let axis_stats = stats.axis(axis);
+        axes.push(Span::styled(
+            format!("{} {} · {:.0}% ★   ", axis.symbol(), axis_stats.attempts, axis_stats.perfect_percentage()),
+            style(theme.neutral),
+        ));
+    }
+    axes.push(Span::styled(
+        format!("↔↕ {} · {:.0}% ★", stats.diagonal.attempts, stats.diagonal.perfect_percentage()),
+        style(theme.neutral),
+    ));
+
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(format!("★ {}", stats.perfect_count), bold(theme.good)),
+            Span::styled(format!(" {:.0}%   ", stats.perfect_percentage()), style(theme.neutral)),
+            Span::styled(format!("● {}   ", stats.good_count), bold(theme.warning)),
+            Span::styled(format!("✕ {}", stats.failed_count), bold(theme.bad)),
+            Span::styled(
+                stats
+                    .reaction_summary()
+                    .map(|reaction| format!("   ⚑ {:.0}ms median reaction", reaction.median * 1000.0))
+                    .unwrap_or_default(),
+                style(theme.neutral),
+            ),
+        ]),
+        Line::from(axes),
+        Line::styled(
+            format!(
+                "All time: {} attempts · {:.0}% ★",
+                all_time.total_attempts,
+                all_time.perfect_percentage()
+            ),
+            style(theme.neutral),
+        ),
+    ];
+
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Stats ")), area);
+}
+
+/// Terminal style using a color of the GUI theme
+fn style(color: egui::Color32) -> Style {
+    Style::default().fg(Color::Rgb(color.r(), color.g(), color.b()))
+}
+
+fn bold(color: egui::Color32) -> Style {
+    style(color).add_modifier(Modifier::BOLD)
+}
+
+/// Approximate opacity by scaling the color towards black
+fn faded(color: egui::Color32, opacity: f32) -> Style {
+    let scale = |channel: u8| (channel as f32 * opacity.clamp(0.2, 1.0)) as u8;
+    Style::default().fg(Color::Rgb(scale(color.r()), scale(color.g()), scale(color.b())))
+}
@@ -1,16 +1,31 @@
 use egui::{Color32, RichText, Stroke, Frame, Rounding};
-use crate::feedback::FeedSystem;
-use crate::state::{CounterStrafeState, Quality, OPTIMAL_HOLD_TIME};
-use crate::stats::Stats;
+use crate::config::{Config, MAX_FEED_LENGTH, MIN_OPACITY};
+use crate::diagnostics::{Report, Severity};
+use crate::drills::{Drill, DrillRun, DrillSummary};
+use crate::diagonal::MovementState;
+use crate::events::ListenerStatus;
+use crate::feedback::{FeedSettings, FeedSystem};
+use crate::keymap::{Action, KeyMap};
+use crate::metronome::Cue;
+use crate::reaction::rate_reaction;
+use crate::profiles::Weapon;
+use crate::simulation::MovementSimulation;
+use crate::state::{evaluate_hold_time, Axis, CounterStrafeState, Quality, TimingWindows, Transition};
+use crate::stats::{Stats, ROLLING_WINDOW};
+use std::ops::RangeInclusive;
 use std::time::Instant;
 
 // Window dimensions (initial size, will adapt to content)
 pub const WINDOW_WIDTH: f32 = 460.0;
-pub const WINDOW_HEIGHT: f32 = 480.0;
+pub const WINDOW_HEIGHT: f32 = 740.0;
 const PADDING: f32 = 20.0;
 const SPACING: f32 = 15.0;
+const VELOCITY_BAR_HEIGHT: f32 = 8.0;
+const CHART_HEIGHT: f32 = 180.0;
+const CHART_MAX_ATTEMPTS: usize = 100;   // Timeline shows the most recent attempts
+const HISTOGRAM_BIN_MS: f32 = 10.0;
 
-// Modern color scheme with vibrant accents
+// Modern color scheme with vibrant accents (default theme)
 pub const BG_COLOR: Color32 = Color32::from_rgba_premultiplied(10, 12, 20, 220);
 pub const CARD_BG: Color32 = Color32::from_rgba_premultiplied(18, 22, 32, 235);
 pub const TEXT_COLOR: Color32 = Color32::from_rgb(245, 248, 255);
@@ -26,22 +41,199 @@ pub const BIG_FONT: f32 = 28.0;
 pub const NORMAL_FONT: f32 = 18.0;
 pub const SMALL_FONT: f32 = 14.0;
 
+/// Colors and font sizes used by the UI, defaults from the constants above
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: Color32,
+    pub card: Color32,
+    pub text: Color32,
+    pub accent: Color32,
+    pub good: Color32,
+    pub bad: Color32,
+    pub warning: Color32,
+    pub neutral: Color32,
+    pub huge_font: f32,
+    pub big_font: f32,
+    pub normal_font: f32,
+    pub small_font: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: BG_COLOR,
+            card: CARD_BG,
+            text: TEXT_COLOR,
+            accent: ACCENT_COLOR,
+            good: GOOD_COLOR,
+            bad: BAD_COLOR,
+            warning: WARNING_COLOR,
+            neutral: NEUTRAL_COLOR,
+            huge_font: HUGE_FONT,
+            big_font: BIG_FONT,
+            normal_font: NORMAL_FONT,
+            small_font: SMALL_FONT,
+        }
+    }
+}
+
+impl Theme {
+    /// Fill of the smaller panels: stats bar, distribution, drill progress
+    pub fn panel(&self) -> Color32 {
+        self.card.gamma_multiply(0.75)
+    }
+
+    /// Outline of the cards
+    pub fn border(&self) -> Color32 {
+        self.accent.gamma_multiply(0.16)
+    }
+
+    /// Outline of the smaller panels
+    pub fn panel_border(&self) -> Color32 {
+        self.accent.gamma_multiply(0.12)
+    }
+
+    pub fn quality_color(&self, quality: Quality) -> Color32 {
+        match quality {
+            Quality::Perfect => self.good,
+            Quality::Good => self.warning,
+            Quality::Failed => self.bad,
+        }
+    }
+}
+
+/// Use `theme` for everything rendered from now on
+pub fn set_theme(ctx: &egui::Context, theme: Theme) {
+    ctx.data_mut(|data| data.insert_temp(egui::Id::NULL, theme));
+}
+
+fn current_theme(ctx: &egui::Context) -> Theme {
+    ctx.data(|data| data.get_temp(egui::Id::NULL)).unwrap_or_default()
+}
+
+/// Screen currently shown in the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Trainer,
+    Bindings,
+    Charts,
+    Settings,
+    Diagnostics,
+    Drills,
+}
+
+/// Interaction on the trainer screen, handled by the app
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrainerAction {
+    SelectWeapon(Weapon),
+    /// Step the tolerated key overlap: off, perfect overlap, max overlap
+    CycleOverlapWindow,
+    StopDrill,
+}
+
+/// Interaction on the settings screen, handled by the app
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsAction {
+    /// Use new settings; `save` once the change is final (not while a slider is dragged)
+    Apply { config: Box<Config>, save: bool },
+    SelectWeapon(Weapon),
+    SelectKeyMap(KeyMap),
+    EditBindings,
+    Close,
+}
+
+/// Interaction on the input problem screen, handled by the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListenerAction {
+    /// Restart the input backend
+    Retry,
+    /// Keep training anyway
+    Dismiss,
+    /// Open the diagnostics report
+    Diagnostics,
+    /// The quit key may not work without input
+    Quit,
+}
+
+/// Interaction on the diagnostics screen, handled by the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticsAction {
+    /// Check again, e.g. after fixing group membership
+    Refresh,
+    Close,
+}
+
+/// Interaction on the drills screen, handled by the app
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrillsAction {
+    Start(Drill),
+    Stop,
+    Close,
+}
+
+/// Interaction on the drill summary screen, handled by the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrillSummaryAction {
+    Again,
+    Close,
+}
+
+/// Interaction on the key bindings screen, handled by the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingsAction {
+    Rebind(Action),
+    CancelRebind,
+    /// Let a left click fire, or stop it
+    ToggleClickFire,
+    ResetDefaults,
+    Close,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn render_ui(
     ctx: &egui::Context,
-    state: &CounterStrafeState,
+    movement: &MovementState,
+    simulation: &MovementSimulation,
+    weapon: Weapon,
     feed: &FeedSystem,
     stats: &Stats,
-) {
+    all_time: &Stats,
+    keymap: &KeyMap,
+    drill: Option<&DrillRun>,
+    cue: Option<(Cue, f32)>,
+) -> Option<TrainerAction> {
+    let theme = current_theme(ctx);
     let now = Instant::now();
+    let timing = movement.timing();
+    let mut action = None;
 
     egui::CentralPanel::default()
-        .frame(Frame::none().fill(BG_COLOR).inner_margin(PADDING))
+        .frame(Frame::none().fill(theme.background).inner_margin(PADDING))
         .show(ctx, |ui| {
             ui.vertical_centered(|ui| {
+                // Weapon profile selector and overlap mode
+                ui.horizontal(|ui| {
+                    if let Some(selected) = render_weapon_selector(ui, weapon) {
+                        action = Some(TrainerAction::SelectWeapon(selected));
+                    }
+                    ui.separator();
+                    if render_overlap_toggle(ui, movement.overlap_window()) {
+                        action = Some(TrainerAction::CycleOverlapWindow);
+                    }
+                });
+
                 ui.add_space(SPACING);
 
+                // Drill progress
+                if let Some(drill) = drill {
+                    if render_drill_progress(ui, drill, now) {
+                        action = Some(TrainerAction::StopDrill);
+                    }
+                    ui.add_space(SPACING);
+                }
+
                 // Main display card
-                render_main_display(ui, state, now);
+                render_main_display(ui, movement.display_state(), simulation, timing, keymap, cue, now);
 
                 ui.add_space(SPACING);
 
@@ -51,23 +243,94 @@ pub fn render_ui(
                 ui.add_space(SPACING);
 
                 // Stats bar
-                render_stats_bar(ui, stats);
+                render_stats_bar(ui, stats, all_time);
+
+                ui.add_space(SPACING);
+
+                // Hold time distribution
+                render_distribution_panel(ui, stats, timing);
 
                 ui.add_space(SPACING);
 
                 // Controls hint
-                render_controls_hint(ui);
+                render_controls_hint(ui, keymap);
 
                 ui.add_space(SPACING);
             });
         });
+
+    action
+}
+
+/// Name and progress of the drill in progress. Returns true when the user stops it.
+fn render_drill_progress(ui: &mut egui::Ui, run: &DrillRun, now: Instant) -> bool {
+    let theme = current_theme(ui.ctx());
+    let mut stop = false;
+
+    let drill_frame = Frame::none()
+        .fill(theme.panel())
+        .rounding(Rounding::same(10.0))
+        .inner_margin(egui::Margin::symmetric(15.0, 8.0))
+        .stroke(Stroke::new(1.0, theme.panel_border()));
+
+    drill_frame.show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!("DRILL {}", run.drill().name.to_uppercase()))
+                    .color(theme.accent)
+                    .size(theme.small_font)
+                    .strong()
+            );
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                stop = ui.small_button("Stop").clicked();
+            });
+        });
+        ui.label(RichText::new(run.progress(now)).color(theme.text).size(theme.small_font));
+    });
+
+    stop
+}
+
+fn render_weapon_selector(ui: &mut egui::Ui, active: Weapon) -> Option<Weapon> {
+    let theme = current_theme(ui.ctx());
+    let mut selected = None;
+    for weapon in Weapon::ALL {
+        let color = if weapon == active { theme.accent } else { theme.neutral };
+        let label = RichText::new(weapon.name()).color(color).size(theme.small_font).strong();
+        if ui.selectable_label(weapon == active, label).clicked() && weapon != active {
+            selected = Some(weapon);
+        }
+    }
+    selected
 }
 
-fn render_main_display(ui: &mut egui::Ui, state: &CounterStrafeState, now: Instant) {
+/// Button cycling through the tolerated overlap windows; true when clicked
+fn render_overlap_toggle(ui: &mut egui::Ui, current: f32) -> bool {
+    let theme = current_theme(ui.ctx());
+    let (text, color) = if current > 0.0 {
+        (format!("Overlap ≤{:.0}ms", current * 1000.0), theme.accent)
+    } else {
+        ("Overlap off".to_string(), theme.neutral)
+    };
+    let button = egui::Button::new(RichText::new(text).color(color).size(theme.small_font));
+    ui.add(button).on_hover_text("Tolerate pressing the counter key shortly before releasing").clicked()
+}
+
+fn render_main_display(
+    ui: &mut egui::Ui,
+    state: &CounterStrafeState,
+    simulation: &MovementSimulation,
+    timing: &TimingWindows,
+    keymap: &KeyMap,
+    cue: Option<(Cue, f32)>,
+    now: Instant,
+) {
+    let theme = current_theme(ui.ctx());
     let available_width = ui.available_width();
 
     let card_frame = Frame::none()
-        .fill(CARD_BG)
+        .fill(theme.card)
         .rounding(Rounding::same(12.0))
         .inner_margin(20.0)
         .shadow(egui::epaint::Shadow {
@@ -76,7 +339,7 @@ fn render_main_display(ui: &mut egui::Ui, state: &CounterStrafeState, now: Insta
             spread: 0.0,
             color: Color32::from_black_alpha(100),
         })
-        .stroke(Stroke::new(1.5, Color32::from_rgba_premultiplied(88, 166, 255, 40)));
+        .stroke(Stroke::new(1.5, theme.border()));
 
     card_frame.show(ui, |ui| {
         ui.set_min_height(140.0);
@@ -85,7 +348,23 @@ fn render_main_display(ui: &mut egui::Ui, state: &CounterStrafeState, now: Insta
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
 
-            let display_info = state.get_display_info();
+            // Metronome or reaction cue; metronome cues fade over the beat so each beat lands as a flash
+            if let Some((cue, fraction)) = cue {
+                let color = match cue {
+                    Cue::Wait => theme.neutral,
+                    Cue::Stop => theme.warning,
+                    Cue::Strafe(_) => theme.accent,
+                };
+                ui.label(
+                    RichText::new(cue.label())
+                        .color(color.gamma_multiply(1.0 - 0.7 * fraction))
+                        .size(theme.big_font)
+                        .strong()
+                );
+                ui.add_space(8.0);
+            }
+
+            let display_info = state.get_display_info(keymap);
 
             if display_info.show_target {
                 // Show current hold time with symbol
@@ -93,37 +372,37 @@ fn render_main_display(ui: &mut egui::Ui, state: &CounterStrafeState, now: Insta
                     let hold_time_ms = (hold_time * 1000.0).round() as i32;
 
                     // Determine color and symbol based on timing
-                    let (color, symbol) = if hold_time < 0.060 {
-                        (BAD_COLOR, "⚡")
-                    } else if hold_time > 0.120 {
-                        (BAD_COLOR, "⏱")
-                    } else if (hold_time - OPTIMAL_HOLD_TIME).abs() <= 0.015 {
-                        (GOOD_COLOR, "★")
+                    let (color, symbol) = if hold_time < timing.min {
+                        (theme.bad, "⚡")
+                    } else if hold_time > timing.max {
+                        (theme.bad, "⏱")
+                    } else if (hold_time - timing.optimal).abs() <= timing.perfect_tolerance {
+                        (theme.good, "★")
                     } else {
-                        (WARNING_COLOR, "●")
+                        (theme.warning, "●")
                     };
 
                     let text = format!("{}{} ms", hold_time_ms, symbol);
                     ui.label(
                         RichText::new(text)
                             .color(color)
-                            .size(HUGE_FONT)
+                            .size(theme.huge_font)
                             .strong()
                     );
 
                     ui.add_space(8.0);
                     ui.label(
-                        RichText::new("🎯 TARGET: 80ms")
-                            .color(NEUTRAL_COLOR)
-                            .size(NORMAL_FONT)
+                        RichText::new(format!("🎯 TARGET: {:.0}ms", timing.optimal * 1000.0))
+                            .color(theme.neutral)
+                            .size(theme.normal_font)
                     );
                 }
             } else {
                 // Show status text
                 ui.label(
                     RichText::new(&display_info.main_text)
-                        .color(ACCENT_COLOR)
-                        .size(BIG_FONT)
+                        .color(theme.accent)
+                        .size(theme.big_font)
                         .strong()
                 );
 
@@ -131,20 +410,56 @@ fn render_main_display(ui: &mut egui::Ui, state: &CounterStrafeState, now: Insta
                     ui.add_space(5.0);
                     ui.label(
                         RichText::new(sub_text)
-                            .color(NEUTRAL_COLOR)
-                            .size(NORMAL_FONT)
+                            .color(theme.neutral)
+                            .size(theme.normal_font)
                     );
                 }
             }
+
+            ui.add_space(12.0);
+            render_velocity_bar(ui, simulation);
         });
     });
 }
 
+/// Live simulated speed with a marker at the weapon's accurate-speed threshold
+fn render_velocity_bar(ui: &mut egui::Ui, simulation: &MovementSimulation) {
+    let theme = current_theme(ui.ctx());
+    let params = simulation.params();
+    let speed = simulation.speed();
+
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), VELOCITY_BAR_HEIGHT),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter();
+    painter.rect_filled(rect, Rounding::same(3.0), theme.text.gamma_multiply(0.06));
+
+    let fill_color = if simulation.is_accurate() { theme.good } else { theme.bad };
+    let fill_width = rect.width() * (speed / params.max_speed).clamp(0.0, 1.0);
+    let fill_rect = egui::Rect::from_min_size(rect.min, egui::vec2(fill_width, rect.height()));
+    painter.rect_filled(fill_rect, Rounding::same(3.0), fill_color);
+
+    let threshold_x = rect.left() + rect.width() * (params.accurate_speed / params.max_speed);
+    painter.line_segment(
+        [egui::pos2(threshold_x, rect.top() - 3.0), egui::pos2(threshold_x, rect.bottom() + 3.0)],
+        Stroke::new(2.0, theme.text),
+    );
+
+    ui.add_space(4.0);
+    ui.label(
+        RichText::new(format!("{:.0} u/s", speed))
+            .color(theme.neutral)
+            .size(theme.small_font)
+    );
+}
+
 fn render_feed_card(ui: &mut egui::Ui, feed: &FeedSystem, now: Instant) {
+    let theme = current_theme(ui.ctx());
     let available_width = ui.available_width();
 
     let card_frame = Frame::none()
-        .fill(CARD_BG)
+        .fill(theme.card)
         .rounding(Rounding::same(12.0))
         .inner_margin(15.0)
         .shadow(egui::epaint::Shadow {
@@ -153,7 +468,7 @@ fn render_feed_card(ui: &mut egui::Ui, feed: &FeedSystem, now: Instant) {
             spread: 0.0,
             color: Color32::from_black_alpha(80),
         })
-        .stroke(Stroke::new(1.5, Color32::from_rgba_premultiplied(88, 166, 255, 40)));
+        .stroke(Stroke::new(1.5, theme.border()));
 
     card_frame.show(ui, |ui| {
         ui.set_min_height(120.0);
@@ -166,17 +481,17 @@ fn render_feed_card(ui: &mut egui::Ui, feed: &FeedSystem, now: Instant) {
                 ui.add_space(30.0);
                 ui.label(
                     RichText::new("No attempts yet")
-                        .color(NEUTRAL_COLOR)
-                        .size(SMALL_FONT)
+                        .color(theme.neutral)
+                        .size(theme.small_font)
                 );
             });
         } else {
             ui.vertical(|ui| {
                 for (entry, opacity) in entries.iter().take(5) {
                     let color = match entry.quality {
-                        Quality::Perfect => GOOD_COLOR,
-                        Quality::Good => WARNING_COLOR,
-                        Quality::Failed => BAD_COLOR,
+                        Quality::Perfect => theme.good,
+                        Quality::Good => theme.warning,
+                        Quality::Failed => theme.bad,
                     };
 
                     // Apply opacity
@@ -191,7 +506,7 @@ fn render_feed_card(ui: &mut egui::Ui, feed: &FeedSystem, now: Instant) {
                     ui.label(
                         RichText::new(text)
                             .color(faded_color)
-                            .size(NORMAL_FONT)
+                            .size(theme.normal_font)
                     );
                 }
             });
@@ -199,14 +514,15 @@ fn render_feed_card(ui: &mut egui::Ui, feed: &FeedSystem, now: Instant) {
     });
 }
 
-fn render_stats_bar(ui: &mut egui::Ui, stats: &Stats) {
+fn render_stats_bar(ui: &mut egui::Ui, stats: &Stats, all_time: &Stats) {
+    let theme = current_theme(ui.ctx());
     let available_width = ui.available_width();
 
     let stats_frame = Frame::none()
-        .fill(Color32::from_rgba_premultiplied(18, 22, 32, 180))
+        .fill(theme.panel())
         .rounding(Rounding::same(10.0))
         .inner_margin(egui::Margin::symmetric(15.0, 10.0))
-        .stroke(Stroke::new(1.0, Color32::from_rgba_premultiplied(88, 166, 255, 30)));
+        .stroke(Stroke::new(1.0, theme.panel_border()));
 
     stats_frame.show(ui, |ui| {
         ui.set_width(available_width);
@@ -215,15 +531,15 @@ fn render_stats_bar(ui: &mut egui::Ui, stats: &Stats) {
             // Perfect count
             ui.label(
                 RichText::new(format!("★ {}", stats.perfect_count))
-                    .color(GOOD_COLOR)
-                    .size(NORMAL_FONT)
+                    .color(theme.good)
+                    .size(theme.normal_font)
                     .strong()
             );
 
             ui.label(
                 RichText::new(format!("{:.0}%", stats.perfect_percentage()))
-                    .color(NEUTRAL_COLOR)
-                    .size(SMALL_FONT)
+                    .color(theme.neutral)
+                    .size(theme.small_font)
             );
 
             ui.add_space(15.0);
@@ -233,8 +549,8 @@ fn render_stats_bar(ui: &mut egui::Ui, stats: &Stats) {
             // Good count
             ui.label(
                 RichText::new(format!("● {}", stats.good_count))
-                    .color(WARNING_COLOR)
-                    .size(NORMAL_FONT)
+                    .color(theme.warning)
+                    .size(theme.normal_font)
                     .strong()
             );
 
@@ -245,54 +561,961 @@ fn render_stats_bar(ui: &mut egui::Ui, stats: &Stats) {
             // Failed count
             ui.label(
                 RichText::new(format!("✕ {}", stats.failed_count))
-                    .color(BAD_COLOR)
-                    .size(NORMAL_FONT)
+                    .color(theme.bad)
+                    .size(theme.normal_font)
                     .strong()
             );
         });
+
+        // Per-axis attempts and perfect rate
+        ui.horizontal(|ui| {
+            ui.add_space(5.0);
+            for axis in Axis::ALL {
+                let axis_stats = stats.axis(axis);
+                ui.label(
+                    RichText::new(format!(
+                        "{} {} · {:.0}% ★",
+                        axis.symbol(),
+                        axis_stats.attempts,
+                        axis_stats.perfect_percentage()
+                    ))
+                    .color(theme.neutral)
+                    .size(theme.small_font)
+                );
+                ui.add_space(15.0);
+            }
+            ui.label(
+                RichText::new(format!(
+                    "↔↕ {} · {:.0}% ★",
+                    stats.diagonal.attempts,
+                    stats.diagonal.perfect_percentage()
+                ))
+                .color(theme.neutral)
+                .size(theme.small_font)
+            );
+        });
+
+        // Shot timing from the fire drill
+        if stats.shots.total() > 0 {
+            ui.horizontal(|ui| {
+                ui.add_space(5.0);
+                ui.label(RichText::new("⌖").color(theme.accent).size(theme.small_font));
+                for (count, label, color) in [
+                    (stats.shots.on_time, "on time", theme.good),
+                    (stats.shots.late, "late", theme.warning),
+                    (stats.shots.too_early, "too early", theme.bad),
+                ] {
+                    ui.label(RichText::new(count.to_string()).color(color).size(theme.small_font).strong());
+                    ui.label(RichText::new(label).color(theme.neutral).size(theme.small_font));
+                    ui.add_space(10.0);
+                }
+            });
+        }
+
+        // All sessions of this weapon, loaded from the history file
+        ui.horizontal(|ui| {
+            ui.add_space(5.0);
+            ui.label(
+                RichText::new(format!(
+                    "All time: {} attempts · {:.0}% ★",
+                    all_time.total_attempts,
+                    all_time.perfect_percentage()
+                ))
+                .color(theme.neutral)
+                .size(theme.small_font)
+            );
+        });
     });
 }
 
-fn render_controls_hint(ui: &mut egui::Ui) {
-    ui.horizontal(|ui| {
-        ui.add_space(5.0);
+fn render_distribution_panel(ui: &mut egui::Ui, stats: &Stats, timing: &TimingWindows) {
+    let theme = current_theme(ui.ctx());
+    let available_width = ui.available_width();
 
-        ui.label(
-            RichText::new("⌨")
-                .color(ACCENT_COLOR)
-                .size(NORMAL_FONT)
-        );
+    let panel_frame = Frame::none()
+        .fill(theme.panel())
+        .rounding(Rounding::same(10.0))
+        .inner_margin(egui::Margin::symmetric(15.0, 10.0))
+        .stroke(Stroke::new(1.0, theme.panel_border()));
 
-        ui.add_space(5.0);
+    panel_frame.show(ui, |ui| {
+        ui.set_width(available_width);
+
+        let Some(summary) = stats.hold_time_summary() else {
+            ui.label(
+                RichText::new("Hold time distribution appears after the first stop")
+                    .color(theme.neutral)
+                    .size(theme.small_font)
+            );
+            return;
+        };
+
+        // Color a hold time by how close it is to the weapon's optimal time
+        let timing_color = |time: f32| theme.quality_color(evaluate_hold_time(time, timing));
+        let value = |ui: &mut egui::Ui, label: &str, time: f32, color: Color32| {
+            ui.label(RichText::new(label).color(theme.neutral).size(theme.small_font));
+            ui.label(
+                RichText::new(format!("{:.0}ms", time * 1000.0))
+                    .color(color)
+                    .size(theme.small_font)
+                    .strong()
+            );
+            ui.add_space(10.0);
+        };
+
+        ui.horizontal(|ui| {
+            ui.add_space(5.0);
+            value(ui, "Mean", summary.mean, timing_color(summary.mean));
+            value(ui, "Median", summary.median, timing_color(summary.median));
+            // Spread: within the perfect tolerance is consistent
+            let spread_color = if summary.std_dev <= timing.perfect_tolerance {
+                theme.good
+            } else {
+                theme.warning
+            };
+            value(ui, "σ", summary.std_dev, spread_color);
+        });
+
+        ui.horizontal(|ui| {
+            ui.add_space(5.0);
+            value(ui, "P10", summary.p10, timing_color(summary.p10));
+            value(ui, "P90", summary.p90, timing_color(summary.p90));
+            let label = format!("Last {}", ROLLING_WINDOW.min(summary.count));
+            value(ui, &label, summary.rolling_average, timing_color(summary.rolling_average));
+        });
+
+        // Release -> counter transition: gap while coasting, or overlap with both keys held
+        let gap = stats.gap_summary();
+        let overlap = stats.overlap_summary();
+        if gap.is_some() || overlap.is_some() {
+            ui.horizontal(|ui| {
+                ui.add_space(5.0);
+                if let Some(gap) = gap {
+                    let color = theme.quality_color(Transition::Gap(gap.mean).quality(timing));
+                    value(ui, "Gap", gap.mean, color);
+                }
+                if let Some(overlap) = overlap {
+                    let color = theme.quality_color(Transition::Overlap(overlap.mean).quality(timing));
+                    let label = format!("Overlap {}×", overlap.count);
+                    value(ui, &label, overlap.mean, color);
+                }
+            });
+        }
+
+        // Reaction mode: STOP cue -> counter key
+        if let Some(reaction) = stats.reaction_summary() {
+            ui.horizontal(|ui| {
+                ui.add_space(5.0);
+                value(ui, "Reaction", reaction.median, theme.quality_color(rate_reaction(reaction.median)));
+                value(ui, "Best", reaction.p10, theme.quality_color(rate_reaction(reaction.p10)));
+                if let Some(strafe) = stats.strafe_reaction_summary() {
+                    value(ui, "Strafe", strafe.median, theme.quality_color(rate_reaction(strafe.median)));
+                }
+                let label = format!("{}×", reaction.count);
+                ui.label(RichText::new(label).color(theme.neutral).size(theme.small_font));
+            });
+        }
+
+        // Per stop direction; the weaker direction of an axis is highlighted
+        for axis in Axis::ALL {
+            let weaker = stats.weaker_direction(axis, timing.optimal);
+            let directions: Vec<_> = axis
+                .keys()
+                .into_iter()
+                .filter_map(|key| stats.direction(key).map(|direction| (key, direction)))
+                .collect();
+            if directions.is_empty() {
+                continue;
+            }
+
+            ui.horizontal(|ui| {
+                ui.add_space(5.0);
+                for (key, direction) in directions {
+                    let is_weaker = weaker == Some(key);
+                    let offset = direction
+                        .mean_hold_time()
+                        .map(|time| format!(" {:+.0}ms", (time - timing.optimal) * 1000.0))
+                        .unwrap_or_default();
+                    let text = format!(
+                        "{}{} {:.0}% ★{}",
+                        if is_weaker { "▼ " } else { "" },
+                        key.direction_label(),
+                        direction.perfect_percentage(),
+                        offset
+                    );
+                    let label = RichText::new(text).size(theme.small_font);
+                    ui.label(if is_weaker {
+                        label.color(theme.bad).strong()
+                    } else {
+                        label.color(theme.neutral)
+                    });
+                    ui.add_space(15.0);
+                }
+            });
+        }
+    });
+}
 
+fn render_controls_hint(ui: &mut egui::Ui, keymap: &KeyMap) {
+    let theme = current_theme(ui.ctx());
+    let key = |ui: &mut egui::Ui, key: &str, action: &str| {
         ui.label(
-            RichText::new("A/D")
-                .color(TEXT_COLOR)
-                .size(SMALL_FONT)
+            RichText::new(key)
+                .color(theme.text)
+                .size(theme.small_font)
                 .strong()
         );
 
         ui.label(
-            RichText::new("practice")
-                .color(NEUTRAL_COLOR)
-                .size(SMALL_FONT)
+            RichText::new(action)
+                .color(theme.neutral)
+                .size(theme.small_font)
         );
-
+    };
+    let separator = |ui: &mut egui::Ui| {
         ui.add_space(10.0);
-        ui.label(RichText::new("•").color(NEUTRAL_COLOR));
+        ui.label(RichText::new("•").color(theme.neutral));
         ui.add_space(10.0);
+    };
+
+    ui.horizontal(|ui| {
+        ui.add_space(5.0);
 
         ui.label(
-            RichText::new("ESC")
-                .color(TEXT_COLOR)
-                .size(SMALL_FONT)
-                .strong()
+            RichText::new("⌨")
+                .color(theme.accent)
+                .size(theme.normal_font)
         );
 
-        ui.label(
-            RichText::new("quit")
-                .color(NEUTRAL_COLOR)
-                .size(SMALL_FONT)
+        ui.add_space(5.0);
+
+        let strafe_keys = format!(
+            "{}/{}",
+            keymap.label_for(Action::StrafeLeft),
+            keymap.label_for(Action::StrafeRight)
         );
+        key(ui, &strafe_keys, "practice");
+        separator(ui);
+        key(ui, &keymap.label_for(Action::Quit), "quit");
     });
+
+    // Screens
+    ui.horizontal(|ui| {
+        ui.add_space(5.0);
+        key(ui, "F1", "keys");
+        separator(ui);
+        key(ui, "F2", "charts");
+        separator(ui);
+        key(ui, "F3", "settings");
+        separator(ui);
+        key(ui, "F4", "diagnostics");
+        separator(ui);
+        key(ui, "F5", "drills");
+        separator(ui);
+        key(ui, "F6", "reaction");
+    });
+}
+
+/// Key bindings screen: one row per action, click a row to rebind it to the next key pressed
+pub fn render_bindings_screen(
+    ctx: &egui::Context,
+    keymap: &KeyMap,
+    capturing: Option<Action>,
+) -> Option<BindingsAction> {
+    let theme = current_theme(ctx);
+    let mut action = None;
+
+    egui::CentralPanel::default()
+        .frame(Frame::none().fill(theme.background).inner_margin(PADDING))
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(SPACING);
+                ui.label(
+                    RichText::new("KEY BINDINGS")
+                        .color(theme.accent)
+                        .size(theme.big_font)
+                        .strong()
+                );
+                ui.add_space(SPACING);
+            });
+
+            let card_frame = Frame::none()
+                .fill(theme.card)
+                .rounding(Rounding::same(12.0))
+                .inner_margin(15.0)
+                .stroke(Stroke::new(1.5, theme.border()));
+
+            card_frame.show(ui, |ui| {
+                ui.set_width(ui.available_width());
+
+                egui::Grid::new("bindings_grid")
+                    .num_columns(2)
+                    .spacing([20.0, 12.0])
+                    .show(ui, |ui| {
+                        for bound_action in Action::ALL {
+                            ui.label(
+                                RichText::new(bound_action.label())
+                                    .color(theme.text)
+                                    .size(theme.normal_font)
+                            );
+
+                            let (text, color) = if capturing == Some(bound_action) {
+                                ("press a key...".to_string(), theme.warning)
+                            } else {
+                                (keymap.label_for(bound_action), theme.good)
+                            };
+                            let button = egui::Button::new(
+                                RichText::new(text).color(color).size(theme.normal_font).strong()
+                            );
+                            if ui.add(button).clicked() {
+                                action = Some(if capturing == Some(bound_action) {
+                                    BindingsAction::CancelRebind
+                                } else {
+                                    BindingsAction::Rebind(bound_action)
+                                });
+                            }
+                            ui.end_row();
+                        }
+
+                        ui.label(
+                            RichText::new("Fire on left click")
+                                .color(theme.text)
+                                .size(theme.normal_font)
+                        );
+                        let (text, color) = if keymap.click_fires() {
+                            ("on", theme.good)
+                        } else {
+                            ("off", theme.neutral)
+                        };
+                        let button = egui::Button::new(
+                            RichText::new(text).color(color).size(theme.normal_font).strong()
+                        );
+                        if ui.add(button).clicked() {
+                            action = Some(BindingsAction::ToggleClickFire);
+                        }
+                        ui.end_row();
+                    });
+            });
+
+            ui.add_space(SPACING);
+
+            ui.horizontal(|ui| {
+                if ui.button(RichText::new("Reset defaults").size(theme.small_font)).clicked() {
+                    action = Some(BindingsAction::ResetDefaults);
+                }
+                if ui.button(RichText::new("Back (F1)").size(theme.small_font)).clicked() {
+                    action = Some(BindingsAction::Close);
+                }
+            });
+        });
+
+    action
+}
+
+/// Shown instead of the trainer while the input backend has failed or stays silent:
+/// what went wrong and how to fix it
+pub fn render_listener_screen(ctx: &egui::Context, status: &ListenerStatus, can_retry: bool) -> Option<ListenerAction> {
+    let theme = current_theme(ctx);
+    let mut action = None;
+
+    egui::CentralPanel::default()
+        .frame(Frame::none().fill(theme.background).inner_margin(PADDING))
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(SPACING);
+                ui.label(
+                    RichText::new(status.title().to_uppercase())
+                        .color(theme.bad)
+                        .size(theme.big_font)
+                        .strong()
+                );
+                ui.add_space(SPACING);
+            });
+
+            let card_frame = Frame::none()
+                .fill(theme.card)
+                .rounding(Rounding::same(12.0))
+                .inner_margin(15.0)
+                .stroke(Stroke::new(1.5, theme.border()));
+
+            card_frame.show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(RichText::new(status.detail()).color(theme.text).size(theme.normal_font));
+                ui.add_space(SPACING);
+
+                ui.label(RichText::new("What to try").color(theme.neutral).size(theme.small_font));
+                for (number, step) in status.remediation().iter().enumerate() {
+                    ui.label(
+                        RichText::new(format!("{}. {}", number + 1, step))
+                            .color(theme.text)
+                            .size(theme.small_font)
+                    );
+                }
+            });
+
+            ui.add_space(SPACING);
+
+            ui.horizontal(|ui| {
+                if can_retry && ui.button(RichText::new("Retry").size(theme.small_font)).clicked() {
+                    action = Some(ListenerAction::Retry);
+                }
+                if ui.button(RichText::new("Continue anyway").size(theme.small_font)).clicked() {
+                    action = Some(ListenerAction::Dismiss);
+                }
+                if ui.button(RichText::new("Diagnostics").size(theme.small_font)).clicked() {
+                    action = Some(ListenerAction::Diagnostics);
+                }
+                if ui.button(RichText::new("Quit").size(theme.small_font)).clicked() {
+                    action = Some(ListenerAction::Quit);
+                }
+            });
+        });
+
+    action
+}
+
+/// Diagnostics screen: every check of the report with its details and fix
+pub fn render_diagnostics_screen(ctx: &egui::Context, report: &Report) -> Option<DiagnosticsAction> {
+    let theme = current_theme(ctx);
+    let mut action = None;
+
+    egui::CentralPanel::default()
+        .frame(Frame::none().fill(theme.background).inner_margin(PADDING))
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(SPACING);
+                ui.label(
+                    RichText::new("DIAGNOSTICS")
+                        .color(theme.accent)
+                        .size(theme.big_font)
+                        .strong()
+                );
+                ui.add_space(SPACING);
+            });
+
+            let card_frame = Frame::none()
+                .fill(theme.card)
+                .rounding(Rounding::same(12.0))
+                .inner_margin(15.0)
+                .stroke(Stroke::new(1.5, theme.border()));
+
+            egui::ScrollArea::vertical().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                for check in &report.checks {
+                    let color = match check.severity {
+                        Severity::Ok => theme.good,
+                        Severity::Info => theme.neutral,
+                        Severity::Warning => theme.warning,
+                        Severity::Error => theme.bad,
+                    };
+                    card_frame.show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(check.severity.label()).color(color).size(theme.small_font).strong());
+                            ui.label(RichText::new(check.name).color(theme.text).size(theme.small_font).strong());
+                        });
+                        ui.label(RichText::new(&check.summary).color(theme.text).size(theme.small_font));
+                        for detail in &check.details {
+                            ui.label(RichText::new(detail).color(theme.neutral).size(theme.small_font));
+                        }
+                        if let Some(fix) = &check.fix {
+                            ui.label(RichText::new(format!("Fix: {}", fix)).color(color).size(theme.small_font));
+                        }
+                    });
+                    ui.add_space(5.0);
+                }
+            });
+
+            ui.add_space(SPACING);
+
+            ui.horizontal(|ui| {
+                if ui.button(RichText::new("Refresh").size(theme.small_font)).clicked() {
+                    action = Some(DiagnosticsAction::Refresh);
+                }
+                if ui.button(RichText::new("Back (F4)").size(theme.small_font)).clicked() {
+                    action = Some(DiagnosticsAction::Close);
+                }
+            });
+        });
+
+    action
+}
+
+/// Drill list: every built-in and user-defined drill with a start button
+pub fn render_drills_screen(ctx: &egui::Context, drills: &[Drill], active: Option<&DrillRun>) -> Option<DrillsAction> {
+    let theme = current_theme(ctx);
+    let mut action = None;
+
+    egui::CentralPanel::default()
+        .frame(Frame::none().fill(theme.background).inner_margin(PADDING))
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(SPACING);
+                ui.label(
+                    RichText::new("DRILLS")
+                        .color(theme.accent)
+                        .size(theme.big_font)
+                        .strong()
+                );
+                ui.add_space(SPACING);
+            });
+
+            let card_frame = Frame::none()
+                .fill(theme.card)
+                .rounding(Rounding::same(12.0))
+                .inner_margin(15.0)
+                .stroke(Stroke::new(1.5, theme.border()));
+
+            egui::ScrollArea::vertical().max_height(ui.available_height() - 40.0).show(ui, |ui| {
+                for drill in drills {
+                    let running = active.is_some_and(|run| run.drill() == drill);
+                    card_frame.show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        ui.horizontal(|ui| {
+                            let color = if running { theme.good } else { theme.text };
+                            ui.label(RichText::new(&drill.name).color(color).size(theme.normal_font).strong());
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                let label = if running { "Restart" } else { "Start" };
+                                if ui.button(RichText::new(label).size(theme.small_font)).clicked() {
+                                    action = Some(DrillsAction::Start(drill.clone()));
+                                }
+                            });
+                        });
+                        ui.label(RichText::new(drill.describe()).color(theme.neutral).size(theme.small_font));
+                    });
+                    ui.add_space(5.0);
+                }
+            });
+
+            ui.add_space(SPACING);
+
+            ui.horizontal(|ui| {
+                if active.is_some() && ui.button(RichText::new("Stop drill").size(theme.small_font)).clicked() {
+                    action = Some(DrillsAction::Stop);
+                }
+                if ui.button(RichText::new("Back (F5)").size(theme.small_font)).clicked() {
+                    action = Some(DrillsAction::Close);
+                }
+            });
+        });
+
+    action
+}
+
+/// Shown instead of the trainer when a drill ends: pass or fail and the drill's stats
+pub fn render_drill_summary(ctx: &egui::Context, summary: &DrillSummary) -> Option<DrillSummaryAction> {
+    let theme = current_theme(ctx);
+    let mut action = None;
+    let stats = &summary.stats;
+
+    egui::CentralPanel::default()
+        .frame(Frame::none().fill(theme.background).inner_margin(PADDING))
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(SPACING);
+                let (verdict, color) = if summary.passed { ("PASSED", theme.good) } else { ("FAILED", theme.bad) };
+                ui.label(RichText::new(verdict).color(color).size(theme.huge_font).strong());
+                ui.label(RichText::new(&summary.drill.name).color(theme.accent).size(theme.big_font).strong());
+                ui.label(RichText::new(summary.drill.describe()).color(theme.neutral).size(theme.small_font));
+                ui.add_space(SPACING);
+            });
+
+            let card_frame = Frame::none()
+                .fill(theme.card)
+                .rounding(Rounding::same(12.0))
+                .inner_margin(15.0)
+                .stroke(Stroke::new(1.5, theme.border()));
+
+            card_frame.show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(RichText::new(summary.drill.goal.progress(stats)).color(theme.text).size(theme.normal_font));
+                ui.add_space(5.0);
+                ui.label(
+                    RichText::new(format!(
+                        "{} attempts in {:.0}s · ★ {} · ● {} · ✕ {}",
+                        stats.total_attempts,
+                        summary.elapsed.as_secs_f32(),
+                        stats.perfect_count,
+                        stats.good_count,
+                        stats.failed_count
+                    ))
+                    .color(theme.neutral)
+                    .size(theme.small_font)
+                );
+                if let Some(hold) = stats.hold_time_summary() {
+                    ui.label(
+                        RichText::new(format!(
+                            "Hold time {:.0}ms median · ±{:.0}ms",
+                            hold.median * 1000.0,
+                            hold.std_dev * 1000.0
+                        ))
+                        .color(theme.neutral)
+                        .size(theme.small_font)
+                    );
+                }
+                if let Some(beat) = stats.beat_offset_summary() {
+                    ui.label(
+                        RichText::new(format!(
+                            "{:.0}% on the beat · {:+.0}ms mean offset",
+                            stats.on_beat_percentage(),
+                            beat.mean * 1000.0
+                        ))
+                        .color(theme.neutral)
+                        .size(theme.small_font)
+                    );
+                }
+            });
+
+            ui.add_space(SPACING);
+
+            ui.horizontal(|ui| {
+                if ui.button(RichText::new("Again").size(theme.small_font)).clicked() {
+                    action = Some(DrillSummaryAction::Again);
+                }
+                if ui.button(RichText::new("Close").size(theme.small_font)).clicked() {
+                    action = Some(DrillSummaryAction::Close);
+                }
+            });
+        });
+
+    action
+}
+
+/// Chart screen: hold time of every attempt over the session and its distribution.
+/// Returns true when the user asks to go back.
+pub fn render_charts_screen(ctx: &egui::Context, stats: &Stats, timing: &TimingWindows) -> bool {
+    let theme = current_theme(ctx);
+    let mut close = false;
+
+    egui::CentralPanel::default()
+        .frame(Frame::none().fill(theme.background).inner_margin(PADDING))
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(SPACING);
+                ui.label(
+                    RichText::new("SESSION CHARTS")
+                        .color(theme.accent)
+                        .size(theme.big_font)
+                        .strong()
+                );
+                ui.add_space(SPACING);
+            });
+
+            let card_frame = Frame::none()
+                .fill(theme.card)
+                .rounding(Rounding::same(12.0))
+                .inner_margin(15.0)
+                .stroke(Stroke::new(1.5, theme.border()));
+
+            // Same scale on both charts, wide enough for every recorded attempt
+            let longest = stats.hold_times.iter().copied().fold(0.0, f32::max);
+            let scale_max = (timing.max * 1.5).max(longest * 1.05);
+
+            card_frame.show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(RichText::new("Hold time per attempt").color(theme.neutral).size(theme.small_font));
+                render_timeline(ui, &stats.hold_times, timing, scale_max);
+            });
+
+            ui.add_space(SPACING);
+
+            card_frame.show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(RichText::new("Distribution").color(theme.neutral).size(theme.small_font));
+                render_histogram(ui, &stats.hold_times, timing, scale_max);
+            });
+
+            ui.add_space(SPACING);
+
+            if ui.button(RichText::new("Back (F2)").size(theme.small_font)).clicked() {
+                close = true;
+            }
+        });
+
+    close
+}
+
+/// Line chart of hold times (y) by attempt (x) with the Perfect and Good windows shaded
+fn render_timeline(ui: &mut egui::Ui, hold_times: &[f32], timing: &TimingWindows, scale_max: f32) {
+    let theme = current_theme(ui.ctx());
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), CHART_HEIGHT),
+        egui::Sense::hover(),
+    );
+    let rect = response.rect;
+    let y_for = |time: f32| rect.bottom() - (time / scale_max).clamp(0.0, 1.0) * rect.height();
+
+    paint_timing_bands(&painter, rect, timing, |(start, end)| {
+        egui::Rect::from_x_y_ranges(rect.x_range(), y_for(end)..=y_for(start))
+    });
+    painter.hline(
+        rect.x_range(),
+        y_for(timing.optimal),
+        Stroke::new(1.0, theme.good.gamma_multiply(0.6)),
+    );
+
+    let recent = &hold_times[hold_times.len().saturating_sub(CHART_MAX_ATTEMPTS)..];
+    if recent.is_empty() {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "No attempts yet",
+            egui::FontId::proportional(theme.small_font),
+            theme.neutral,
+        );
+        return;
+    }
+
+    let step = rect.width() / CHART_MAX_ATTEMPTS.max(2) as f32;
+    let points: Vec<egui::Pos2> = recent
+        .iter()
+        .enumerate()
+        .map(|(i, time)| egui::pos2(rect.left() + (i as f32 + 0.5) * step, y_for(*time)))
+        .collect();
+
+    painter.add(egui::Shape::line(points.clone(), Stroke::new(1.0, theme.neutral.gamma_multiply(0.5))));
+    for (point, time) in points.iter().zip(recent) {
+        painter.circle_filled(*point, 3.0, theme.quality_color(evaluate_hold_time(*time, timing)));
+    }
+}
+
+/// Histogram of hold times in fixed-width bins on the same scale as the timeline
+fn render_histogram(ui: &mut egui::Ui, hold_times: &[f32], timing: &TimingWindows, scale_max: f32) {
+    let theme = current_theme(ui.ctx());
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), CHART_HEIGHT * 0.6),
+        egui::Sense::hover(),
+    );
+    let rect = response.rect;
+    let x_for = |time: f32| rect.left() + (time / scale_max).clamp(0.0, 1.0) * rect.width();
+
+    paint_timing_bands(&painter, rect, timing, |(start, end)| {
+        egui::Rect::from_x_y_ranges(x_for(start)..=x_for(end), rect.y_range())
+    });
+
+    let bin_width = HISTOGRAM_BIN_MS / 1000.0;
+    let bin_count = (scale_max / bin_width).ceil() as usize;
+    let mut bins = vec![0u32; bin_count.max(1)];
+    for time in hold_times {
+        let index = ((time / bin_width) as usize).min(bins.len() - 1);
+        bins[index] += 1;
+    }
+
+    let tallest = bins.iter().copied().max().unwrap_or(0);
+    if tallest == 0 {
+        return;
+    }
+
+    for (index, count) in bins.iter().enumerate().filter(|(_, count)| **count > 0) {
+        let start = index as f32 * bin_width;
+        let height = *count as f32 / tallest as f32 * rect.height();
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(x_for(start) + 1.0, rect.bottom() - height),
+            egui::pos2(x_for(start + bin_width) - 1.0, rect.bottom()),
+        );
+        let color = theme.quality_color(evaluate_hold_time(start + bin_width / 2.0, timing));
+        painter.rect_filled(bar, Rounding::same(2.0), color);
+    }
+}
+
+/// Shade the Good window and, on top, the Perfect window. `band` maps a (start, end)
+/// time range to its rectangle on the chart.
+fn paint_timing_bands(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    timing: &TimingWindows,
+    band: impl Fn((f32, f32)) -> egui::Rect,
+) {
+    let theme = current_theme(painter.ctx());
+    painter.rect_filled(rect, Rounding::same(4.0), theme.background);
+    painter.rect_filled(
+        band((timing.min, timing.max)).intersect(rect),
+        Rounding::ZERO,
+        theme.warning.gamma_multiply(0.12),
+    );
+    painter.rect_filled(
+        band((
+            timing.optimal - timing.perfect_tolerance,
+            timing.optimal + timing.perfect_tolerance,
+        ))
+        .intersect(rect),
+        Rounding::ZERO,
+        theme.good.gamma_multiply(0.18),
+    );
+}
+
+/// Live-tuning screen for the active weapon's timing, the feed and the window.
+/// Changes are returned as a new config for the app to apply and save.
+pub fn render_settings_screen(
+    ctx: &egui::Context,
+    config: &Config,
+    weapon: Weapon,
+    keymap: &KeyMap,
+) -> Option<SettingsAction> {
+    let theme = current_theme(ctx);
+    let mut action = None;
+    let mut timing = config.timing(weapon);
+    let mut feed = config.feed();
+    let mut opacity = config.opacity();
+    let mut overlap_window = config.overlap_window();
+    // Some(save) once any value changed
+    let mut update: Option<bool> = None;
+
+    egui::CentralPanel::default()
+        .frame(Frame::none().fill(theme.background).inner_margin(PADDING))
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(SPACING);
+                ui.label(
+                    RichText::new("SETTINGS")
+                        .color(theme.accent)
+                        .size(theme.big_font)
+                        .strong()
+                );
+                ui.add_space(SPACING);
+            });
+
+            let card_frame = Frame::none()
+                .fill(theme.card)
+                .rounding(Rounding::same(12.0))
+                .inner_margin(15.0)
+                .stroke(Stroke::new(1.5, theme.border()));
+            let heading = |ui: &mut egui::Ui, text: &str| {
+                ui.label(RichText::new(text).color(theme.neutral).size(theme.small_font));
+                ui.add_space(5.0);
+            };
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                card_frame.show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    heading(ui, "Profile");
+                    ui.horizontal(|ui| {
+                        if let Some(selected) = render_weapon_selector(ui, weapon) {
+                            action = Some(SettingsAction::SelectWeapon(selected));
+                        }
+                    });
+
+                    ui.add_space(SPACING);
+                    heading(ui, &format!("{} timing", weapon.name()));
+                    egui::Grid::new("timing_grid").num_columns(2).spacing([20.0, 8.0]).show(ui, |ui| {
+                        let (min, optimal, max) = (timing.min * 1000.0, timing.optimal * 1000.0, timing.max * 1000.0);
+                        note_change(&mut update, ms_slider_row(ui, "Target", &mut timing.optimal, (min + 1.0)..=(max - 1.0)));
+                        note_change(&mut update, ms_slider_row(ui, "Shortest", &mut timing.min, 10.0..=(optimal - 1.0)));
+                        note_change(&mut update, ms_slider_row(ui, "Longest", &mut timing.max, (optimal + 1.0)..=300.0));
+                        note_change(&mut update, ms_slider_row(ui, "Perfect ±", &mut timing.perfect_tolerance, 1.0..=50.0));
+                        note_change(&mut update, ms_slider_row(ui, "Timeout", &mut timing.timeout, 50.0..=500.0));
+
+                        let (perfect_gap, max_gap) = (timing.perfect_gap * 1000.0, timing.max_gap * 1000.0);
+                        note_change(&mut update, ms_slider_row(ui, "Perfect gap", &mut timing.perfect_gap, 0.0..=max_gap));
+                        note_change(&mut update, ms_slider_row(ui, "Max gap", &mut timing.max_gap, perfect_gap..=150.0));
+                        let (perfect_overlap, max_overlap) = (timing.perfect_overlap * 1000.0, timing.max_overlap * 1000.0);
+                        let window = overlap_window * 1000.0;
+                        note_change(&mut update, ms_slider_row(ui, "Perfect overlap", &mut timing.perfect_overlap, 0.0..=max_overlap));
+                        note_change(&mut update, ms_slider_row(ui, "Max overlap", &mut timing.max_overlap, perfect_overlap.max(window)..=150.0));
+                        note_change(&mut update, ms_slider_row(ui, "Tolerated overlap", &mut overlap_window, 0.0..=max_overlap));
+                    });
+                });
+
+                ui.add_space(SPACING);
+
+                card_frame.show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    heading(ui, "Feed & window");
+                    egui::Grid::new("feed_grid").num_columns(2).spacing([20.0, 8.0]).show(ui, |ui| {
+                        note_change(&mut update, slider_row(ui, "Entries", egui::Slider::new(&mut feed.max_entries, 1..=MAX_FEED_LENGTH)));
+                        note_change(
+                            &mut update,
+                            slider_row(ui, "Visible", egui::Slider::new(&mut feed.visible_duration, 0.0..=10.0).suffix(" s").step_by(0.1)),
+                        );
+                        note_change(
+                            &mut update,
+                            slider_row(ui, "Fade", egui::Slider::new(&mut feed.fade_duration, 0.0..=5.0).suffix(" s").step_by(0.1)),
+                        );
+                        note_change(
+                            &mut update,
+                            slider_row(ui, "Opacity", egui::Slider::new(&mut opacity, MIN_OPACITY..=1.0).step_by(0.05)),
+                        );
+                    });
+                });
+
+                ui.add_space(SPACING);
+
+                card_frame.show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    heading(ui, "Key bindings");
+                    ui.horizontal(|ui| {
+                        for (name, preset) in KeyMap::presets() {
+                            let active = *keymap == preset;
+                            let color = if active { theme.accent } else { theme.neutral };
+                            let label = RichText::new(name).color(color).size(theme.small_font).strong();
+                            if ui.selectable_label(active, label).clicked() && !active {
+                                action = Some(SettingsAction::SelectKeyMap(preset));
+                            }
+                        }
+                        if ui.button(RichText::new("Edit... (F1)").size(theme.small_font)).clicked() {
+                            action = Some(SettingsAction::EditBindings);
+                        }
+                    });
+                });
+
+                ui.add_space(SPACING);
+
+                ui.horizontal(|ui| {
+                    if ui.button(RichText::new("Reset defaults").size(theme.small_font)).clicked() {
+                        timing = weapon.timing();
+                        feed = FeedSettings::default();
+                        opacity = 1.0;
+                        overlap_window = 0.0;
+                        update = Some(true);
+                    }
+                    if ui.button(RichText::new("Back (F3)").size(theme.small_font)).clicked() {
+                        action = Some(SettingsAction::Close);
+                    }
+                });
+            });
+        });
+
+    if action.is_none()
+        && let Some(save) = update
+    {
+        let mut config = config.clone();
+        config.set_timing(weapon, timing);
+        config.set_feed(feed);
+        config.set_opacity(opacity);
+        config.set_overlap_window(overlap_window);
+        action = Some(SettingsAction::Apply { config: Box::new(config), save });
+    }
+    action
+}
+
+/// Labelled slider in a two-column grid. `Some(final)` if the value changed,
+/// where `final` is false while the slider is still being dragged.
+fn slider_row(ui: &mut egui::Ui, label: &str, slider: egui::Slider) -> Option<bool> {
+    let theme = current_theme(ui.ctx());
+    ui.label(RichText::new(label).color(theme.text).size(theme.small_font));
+    let response = ui.add(slider);
+    ui.end_row();
+
+    if response.drag_stopped() {
+        Some(true)
+    } else if response.changed() {
+        Some(!response.dragged())
+    } else {
+        None
+    }
+}
+
+/// `slider_row` for a time in seconds, edited in whole milliseconds
+fn ms_slider_row(ui: &mut egui::Ui, label: &str, seconds: &mut f32, range_ms: RangeInclusive<f32>) -> Option<bool> {
+    let mut ms = (*seconds * 1000.0).round();
+    let change = slider_row(ui, label, egui::Slider::new(&mut ms, range_ms).suffix(" ms").step_by(1.0));
+    if change.is_some() {
+        *seconds = ms / 1000.0;
+    }
+    change
+}
+
+fn note_change(update: &mut Option<bool>, change: Option<bool>) {
+    if let Some(save) = change {
+        *update = Some(update.unwrap_or(false) || save);
+    }
 }
@@ -0,0 +1,298 @@
+use cs2_counter_strafe_trainer::app::{Trainer, TrainerOptions};
+use cs2_counter_strafe_trainer::drills::{Drill, Goal};
+use cs2_counter_strafe_trainer::events::ListenerStatus;
+use cs2_counter_strafe_trainer::gui::CS2TrainerApp;
+use cs2_counter_strafe_trainer::input::{InputSink, InputSource, RawInput, ScriptedSource};
+use cs2_counter_strafe_trainer::metronome::Cue;
+use cs2_counter_strafe_trainer::profiles::Weapon;
+use cs2_counter_strafe_trainer::recording::Recording;
+use cs2_counter_strafe_trainer::state::Quality;
+use cs2_counter_strafe_trainer::ui::View;
+use eframe::egui;
+use rdev::Key;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// A held 200ms, then D pressed 10ms after the release and held `hold_ms`
+fn counter_strafe(hold_ms: u64) -> ScriptedSource {
+    ScriptedSource::new()
+        .tap(Key::KeyA, ms(0), ms(200))
+        .tap(Key::KeyD, ms(210), ms(hold_ms))
+}
+
+/// Trainer without any files, fed by `source`
+fn trainer(source: ScriptedSource, weapon: Weapon) -> Trainer {
+    Trainer::new(TrainerOptions {
+        weapon,
+        source: Some(Box::new(source)),
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+/// Process input like the frontends do, once per frame, until `done` or the timeout
+fn run_until(trainer: &mut Trainer, done: impl Fn(&Trainer) -> bool) {
+    let start = Instant::now();
+    while !done(trainer) {
+        assert!(start.elapsed() < TIMEOUT, "timed out waiting for the trainer");
+        trainer.process_events();
+        std::thread::sleep(ms(5));
+    }
+}
+
+fn key_event(key: egui::Key) -> egui::RawInput {
+    egui::RawInput {
+        events: vec![egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::NONE,
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn scripted_counter_strafe_is_scored() {
+    let mut trainer = trainer(counter_strafe(80), Weapon::Rifle);
+    run_until(&mut trainer, |trainer| trainer.stats().total_attempts == 1);
+
+    assert_eq!(trainer.stats().perfect_count, 1);
+    assert_eq!(trainer.feed().get_entries_with_opacity(Instant::now()).len(), 1);
+}
+
+/// Sends all its input at once, stamped `start` + offset, then flags `sent`
+struct BurstSource {
+    start: Instant,
+    steps: Vec<(Duration, RawInput)>,
+    sent: Arc<AtomicBool>,
+}
+
+impl InputSource for BurstSource {
+    fn run(self: Box<Self>, mut sink: InputSink) -> Result<(), Box<dyn std::error::Error>> {
+        for (offset, input) in self.steps {
+            sink.send(input, self.start + offset);
+        }
+        self.sent.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn events_drained_in_one_frame_keep_their_capture_times() {
+    let sent = Arc::new(AtomicBool::new(false));
+    let source = BurstSource {
+        start: Instant::now(),
+        steps: vec![
+            (ms(0), RawInput::KeyPress(Key::KeyA)),
+            (ms(200), RawInput::KeyRelease(Key::KeyA)),
+            (ms(210), RawInput::KeyPress(Key::KeyD)),
+            (ms(290), RawInput::KeyRelease(Key::KeyD)),
+        ],
+        sent: Arc::clone(&sent),
+    };
+    let mut trainer = Trainer::new(TrainerOptions {
+        source: Some(Box::new(source)),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let start = Instant::now();
+    while !sent.load(Ordering::SeqCst) {
+        assert!(start.elapsed() < TIMEOUT, "input never sent");
+        std::thread::sleep(ms(1));
+    }
+    // A single frame: the hold time comes from the capture stamps, not the frame time
+    trainer.process_events();
+
+    let stats = trainer.stats();
+    assert_eq!(stats.total_attempts, 1);
+    assert_eq!(stats.perfect_count, 1);
+    assert!((stats.hold_times[0] - 0.080).abs() < 1e-4);
+}
+
+#[test]
+fn weapon_profile_changes_the_verdict() {
+    // 70ms is a perfect rifle stop but too short for the AWP
+    let mut trainer = trainer(counter_strafe(70), Weapon::Awp);
+    run_until(&mut trainer, |trainer| trainer.stats().total_attempts == 1);
+    assert_eq!(trainer.stats().perfect_count, 0);
+    assert_eq!(trainer.stats().failed_count, 1);
+}
+
+#[test]
+fn gui_runs_headless() {
+    let script = counter_strafe(80).tap(Key::Escape, ms(400), ms(10));
+    let mut app = CS2TrainerApp::new(trainer(script, Weapon::Rifle));
+    let ctx = egui::Context::default();
+
+    let start = Instant::now();
+    while !app.trainer().should_quit() {
+        assert!(start.elapsed() < TIMEOUT, "quit key never arrived");
+        let _ = ctx.run(egui::RawInput::default(), |ctx| app.ui(ctx));
+        std::thread::sleep(ms(5));
+    }
+    assert_eq!(app.trainer().stats().perfect_count, 1);
+
+    let _ = ctx.run(key_event(egui::Key::F3), |ctx| app.ui(ctx));
+    assert_eq!(app.view(), View::Settings);
+    let _ = ctx.run(key_event(egui::Key::F2), |ctx| app.ui(ctx));
+    assert_eq!(app.view(), View::Charts);
+    let _ = ctx.run(key_event(egui::Key::F2), |ctx| app.ui(ctx));
+    assert_eq!(app.view(), View::Trainer);
+    // Reaction mode starts by waiting for the first prompt
+    let _ = ctx.run(key_event(egui::Key::F6), |ctx| app.ui(ctx));
+    assert!(app.trainer().is_reaction_mode());
+    assert_eq!(app.trainer().cue(Instant::now()), Some((Cue::Wait, 0.0)));
+    let _ = ctx.run(egui::RawInput::default(), |ctx| app.ui(ctx));
+    let _ = ctx.run(key_event(egui::Key::F4), |ctx| app.ui(ctx));
+    assert_eq!(app.view(), View::Diagnostics);
+    assert!(!app.diagnostics().unwrap().checks.is_empty());
+}
+
+#[test]
+fn recorded_session_replays_to_the_same_results() {
+    let path = std::env::temp_dir().join(format!("cs2st-e2e-{}.cs2r", std::process::id()));
+    let mut recorded = Trainer::new(TrainerOptions {
+        record: Some(path.clone()),
+        source: Some(Box::new(counter_strafe(100))),
+        ..Default::default()
+    })
+    .unwrap();
+    run_until(&mut recorded, |trainer| trainer.stats().total_attempts == 1);
+    let recorded_perfects = recorded.stats().perfect_count;
+    drop(recorded);
+
+    let recording = Recording::load(&path).unwrap();
+    let rescored = recording.rescore(|weapon| weapon.timing());
+    assert_eq!(rescored.len(), 1);
+    assert_eq!(rescored[0].1.quality(), Quality::Good);
+
+    let mut replayed = Trainer::new(TrainerOptions {
+        replay: Some(recording),
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(replayed.is_replaying());
+    run_until(&mut replayed, |trainer| trainer.stats().total_attempts == 1);
+    assert_eq!(replayed.stats().perfect_count, recorded_perfects);
+    assert_eq!(replayed.stats().good_count, 1);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Backend that fails like an unreadable device
+struct UnreadableSource;
+
+impl InputSource for UnreadableSource {
+    fn run(self: Box<Self>, _sink: InputSink) -> Result<(), Box<dyn std::error::Error>> {
+        Err("/dev/input/event3: Permission denied".into())
+    }
+}
+
+#[test]
+fn listener_failure_is_shown_until_dismissed() {
+    let trainer = Trainer::new(TrainerOptions {
+        source: Some(Box::new(UnreadableSource)),
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(!trainer.can_restart_input());
+
+    let mut app = CS2TrainerApp::new(trainer);
+    let ctx = egui::Context::default();
+    let start = Instant::now();
+    while app.trainer().listener_problem().is_none() {
+        assert!(start.elapsed() < TIMEOUT, "failure never reported");
+        let _ = ctx.run(egui::RawInput::default(), |ctx| app.ui(ctx));
+        std::thread::sleep(ms(5));
+    }
+    // The problem screen renders in place of the trainer
+    let _ = ctx.run(egui::RawInput::default(), |ctx| app.ui(ctx));
+    assert!(matches!(
+        app.trainer().listener_status(),
+        ListenerStatus::PermissionDenied(ref error) if error.contains("event3")
+    ));
+
+    app.trainer_mut().dismiss_listener_problem();
+    assert!(app.trainer().listener_problem().is_none());
+}
+
+#[test]
+fn drill_ends_with_a_summary() {
+    let script = counter_strafe(80)
+        .tap(Key::KeyA, ms(600), ms(200))
+        .tap(Key::KeyD, ms(810), ms(150));
+    let drill = Drill {
+        name: "Two stops".to_string(),
+        attempts: Some(2),
+        seconds: None,
+        goal: Goal::GoodPercent(100.0),
+        weapon: None,
+        bpm: None,
+    };
+    let trainer = Trainer::new(TrainerOptions {
+        source: Some(Box::new(script)),
+        drill: Some(drill),
+        ..Default::default()
+    })
+    .unwrap();
+    let mut app = CS2TrainerApp::new(trainer);
+    let ctx = egui::Context::default();
+
+    let start = Instant::now();
+    while app.trainer().drill_summary().is_none() {
+        assert!(start.elapsed() < TIMEOUT, "drill never finished");
+        let _ = ctx.run(egui::RawInput::default(), |ctx| app.ui(ctx));
+        std::thread::sleep(ms(5));
+    }
+    // 150ms is too long a hold: one of two attempts is Good or better
+    let summary = app.trainer().drill_summary().unwrap();
+    assert!(!summary.passed);
+    assert_eq!(summary.stats.total_attempts, 2);
+    assert!(app.trainer().drill().is_none());
+
+    let _ = ctx.run(egui::RawInput::default(), |ctx| app.ui(ctx));
+    app.trainer_mut().repeat_drill();
+    assert_eq!(app.trainer().drill().unwrap().drill().name, "Two stops");
+    assert!(app.trainer().drill_summary().is_none());
+}
+
+#[test]
+fn diagonal_stop_counts_on_the_beat() {
+    let script = ScriptedSource::new()
+        .press(Key::KeyW, ms(0))
+        .press(Key::KeyA, ms(10))
+        .release(Key::KeyW, ms(300))
+        .release(Key::KeyA, ms(302))
+        .tap(Key::KeyS, ms(305), ms(100))
+        .tap(Key::KeyD, ms(306), ms(80));
+    let drill = Drill {
+        name: "Diagonal rhythm".to_string(),
+        attempts: Some(5),
+        seconds: None,
+        goal: Goal::OnBeatPercent(50.0),
+        weapon: None,
+        bpm: Some(60.0),
+    };
+    let mut trainer = Trainer::new(TrainerOptions {
+        source: Some(Box::new(script)),
+        drill: Some(drill),
+        ..Default::default()
+    })
+    .unwrap();
+    run_until(&mut trainer, |trainer| trainer.stats().total_attempts == 1);
+
+    assert_eq!(trainer.stats().diagonal.attempts, 1);
+    let drill = trainer.drill().unwrap();
+    assert_eq!(drill.stats().diagonal.attempts, 1);
+    assert_eq!(drill.stats().beat_offsets.len(), 1);
+}